@@ -18,11 +18,21 @@ use pyo3::prelude::*;
 /// Ferogram Python module.
 #[pymodule]
 fn ferogram_py(_py: Python<'_>, module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Client>()?;
+
     module.add_class::<Chat>()?;
     module.add_class::<UserStatus>()?;
 
     module.add_class::<Context>()?;
     module.add_class::<Message>()?;
 
+    module.add_class::<CallbackQuery>()?;
+    module.add_class::<InlineQuery>()?;
+    module.add_class::<Article>()?;
+
+    module.add_class::<Photo>()?;
+    module.add_class::<Document>()?;
+    module.add_class::<Sticker>()?;
+
     Ok(())
 }
@@ -0,0 +1,120 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ferogram::{filter, handler, Client, Dispatcher, Injector};
+use grammers_client::{grammers_tl_types as tl, Update};
+
+fn injector(c: &mut Criterion) {
+    let mut group = c.benchmark_group("injector");
+
+    for count in [1, 10, 100] {
+        group.bench_with_input(
+            BenchmarkId::new("insert_take", count),
+            &count,
+            |b, &count| {
+                b.iter(|| {
+                    let mut injector = Injector::default();
+
+                    for i in 0..count {
+                        injector.insert(i);
+                    }
+                    for _ in 0..count {
+                        black_box(injector.take::<i32>());
+                    }
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn router_tree(c: &mut Criterion) {
+    let mut group = c.benchmark_group("router_tree");
+
+    for count in [10, 100, 1000] {
+        group.bench_with_input(BenchmarkId::new("build", count), &count, |b, &count| {
+            b.iter(|| {
+                Dispatcher::default().router(move |mut router| {
+                    for _ in 0..count {
+                        router = router.register(handler::new_message(filter::command("start")));
+                    }
+
+                    router
+                })
+            })
+        });
+
+        let dispatcher = Dispatcher::default().router(move |mut router| {
+            for _ in 0..count {
+                router = router.register(handler::new_message(
+                    filter::command("start").description("Start the bot."),
+                ));
+            }
+
+            router
+        });
+        group.bench_with_input(
+            BenchmarkId::new("help_text", count),
+            &dispatcher,
+            |b, dispatcher| b.iter(|| black_box(dispatcher.help_text())),
+        );
+    }
+
+    group.finish();
+}
+
+fn handle_update(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let client = runtime
+        .block_on(
+            Client::bot(std::env::var("BOT_TOKEN").unwrap_or_default())
+                .api_id(
+                    std::env::var("API_ID")
+                        .unwrap_or("123456789".to_string())
+                        .parse::<i32>()
+                        .unwrap(),
+                )
+                .api_hash(std::env::var("API_HASH").unwrap_or_default())
+                .build(),
+        )
+        .unwrap();
+
+    let handle = client
+        .dispatcher(|dispatcher| {
+            dispatcher.router(|mut router| {
+                for _ in 0..100 {
+                    router = router.register(handler::new_message(filter::command("start")));
+                }
+
+                router.register(handler::then(
+                    |_: grammers_client::Client, _: Update| async { Ok(()) },
+                ))
+            })
+        })
+        .dispatcher_handle();
+    let update = Update::Raw(tl::enums::Update::MessageId(tl::types::UpdateMessageId {
+        id: 1,
+        random_id: 1,
+    }));
+
+    c.bench_function("handle_update", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(handle.feed(black_box(update.clone())))
+                .unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, injector, router_tree, handle_update);
+criterion_main!(benches);
@@ -0,0 +1,277 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Alias module.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use grammers_client::{grammers_tl_types as tl, Update};
+
+use crate::{filters::DEFAULT_PREFIXES, utils::utf16_len};
+
+/// The aliases of the commands, resolved before the filters run.
+///
+/// Rewrites the first word of the messages, keeping the prefix and the bot
+/// mention, so `/s@bot` becomes `/start@bot`. Only words starting with one of the
+/// prefixes, [`DEFAULT_PREFIXES`] by default, or marked as a command by Telegram
+/// are rewritten, so hashtags and mentions are kept. Can be changed while the
+/// client is running, from the dispatcher or the injected handle.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use ferogram::CommandAliases;
+///
+/// async fn add_alias(aliases: CommandAliases) -> ferogram::Result<()> {
+///     aliases.add("iniciar", "start");
+///
+///     Ok(())
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CommandAliases {
+    /// The canonical command of each alias.
+    aliases: Arc<RwLock<HashMap<String, String>>>,
+    /// The prefixes of the commands.
+    prefixes: Arc<RwLock<Vec<String>>>,
+}
+
+impl CommandAliases {
+    /// Adds an alias of the command, without prefix.
+    pub fn add<A: ToString, C: ToString>(&self, alias: A, command: C) {
+        self.aliases
+            .write()
+            .expect("Failed to lock the aliases")
+            .insert(alias.to_string().to_lowercase(), command.to_string());
+    }
+
+    /// Removes the alias.
+    pub fn remove(&self, alias: &str) {
+        self.aliases
+            .write()
+            .expect("Failed to lock the aliases")
+            .remove(&alias.to_lowercase());
+    }
+
+    /// Sets the prefixes of the commands rewritten, the default is [`DEFAULT_PREFIXES`].
+    pub fn set_prefixes<P: ToString>(&self, prefixes: &[P]) {
+        *self.prefixes.write().expect("Failed to lock the prefixes") =
+            prefixes.iter().map(ToString::to_string).collect();
+    }
+
+    /// Returns the command of the alias.
+    pub fn resolve(&self, alias: &str) -> Option<String> {
+        self.aliases
+            .read()
+            .expect("Failed to lock the aliases")
+            .get(&alias.to_lowercase())
+            .cloned()
+    }
+
+    /// Rewrites the command of the text, if it is an alias.
+    ///
+    /// `is_command` tells if Telegram marked the first word as a bot command.
+    /// Returns the new text and the UTF-16 lengths of the old and new command.
+    fn rewrite_text(&self, text: &str, is_command: bool) -> Option<(String, usize, usize)> {
+        let end = text.find(char::is_whitespace).unwrap_or(text.len());
+        let (word, rest) = text.split_at(end);
+
+        let name_start = word.find(|c: char| !c.is_ascii_punctuation())?;
+        if name_start == 0 {
+            return None;
+        }
+
+        let (prefix, name) = word.split_at(name_start);
+        if !is_command
+            && !self
+                .prefixes
+                .read()
+                .expect("Failed to lock the prefixes")
+                .iter()
+                .any(|other| other == prefix)
+        {
+            return None;
+        }
+
+        let (name, mention) = match name.split_once('@') {
+            Some((name, mention)) => (name, Some(mention)),
+            None => (name, None),
+        };
+
+        let command = self.resolve(name)?;
+        let new_word = match mention {
+            Some(mention) => format!("{}{}@{}", prefix, command, mention),
+            None => format!("{}{}", prefix, command),
+        };
+
+        Some((
+            format!("{}{}", new_word, rest),
+            utf16_len(word),
+            utf16_len(&new_word),
+        ))
+    }
+
+    /// Rewrites the command of the message, if it is an alias.
+    ///
+    /// Returns `None` if the update was not changed.
+    pub(crate) fn rewrite(&self, update: &Update) -> Option<Update> {
+        if self
+            .aliases
+            .read()
+            .expect("Failed to lock the aliases")
+            .is_empty()
+        {
+            return None;
+        }
+
+        let mut update = update.clone();
+        match &mut update {
+            Update::NewMessage(message) | Update::MessageEdited(message) => {
+                let tl::enums::Message::Message(raw) = &mut message.raw else {
+                    return None;
+                };
+
+                let is_command = raw.entities.as_ref().is_some_and(|entities| {
+                    entities.iter().any(|entity| {
+                        matches!(entity, tl::enums::MessageEntity::BotCommand(e) if e.offset == 0)
+                    })
+                });
+                let (text, old_len, new_len) = self.rewrite_text(&raw.message, is_command)?;
+                raw.message = text;
+
+                if let Some(entities) = raw.entities.as_mut() {
+                    let delta = new_len as i32 - old_len as i32;
+                    entities
+                        .iter_mut()
+                        .for_each(|entity| shift_entity(entity, old_len as i32, delta));
+                }
+            }
+            _ => return None,
+        }
+
+        Some(update)
+    }
+}
+
+impl Default for CommandAliases {
+    fn default() -> Self {
+        Self {
+            aliases: Arc::new(RwLock::new(HashMap::new())),
+            prefixes: Arc::new(RwLock::new(
+                DEFAULT_PREFIXES.iter().map(ToString::to_string).collect(),
+            )),
+        }
+    }
+}
+
+/// Shifts the entity after the command, or resizes it if it contains the command.
+fn shift_entity(entity: &mut tl::enums::MessageEntity, end: i32, delta: i32) {
+    macro_rules! shift {
+        ($($variant:ident),*) => {
+            match entity {
+                $(tl::enums::MessageEntity::$variant(e) => {
+                    if e.offset >= end {
+                        e.offset += delta;
+                    } else if e.offset + e.length >= end {
+                        e.length += delta;
+                    }
+                })*
+            }
+        };
+    }
+
+    shift!(
+        Unknown,
+        Mention,
+        Hashtag,
+        BotCommand,
+        Url,
+        Email,
+        Bold,
+        Italic,
+        Code,
+        Pre,
+        TextUrl,
+        MentionName,
+        InputMessageEntityMentionName,
+        Phone,
+        Cashtag,
+        Underline,
+        Strike,
+        BankCard,
+        Spoiler,
+        CustomEmoji,
+        Blockquote
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_text() {
+        let aliases = CommandAliases::default();
+        aliases.add("s", "start");
+
+        assert_eq!(
+            aliases.rewrite_text("/s now", false),
+            Some(("/start now".to_string(), 2, 6))
+        );
+        assert_eq!(
+            aliases.rewrite_text("!S@bot", false),
+            Some(("!start@bot".to_string(), 6, 10))
+        );
+        assert_eq!(aliases.rewrite_text("/help", false), None);
+        assert_eq!(aliases.rewrite_text("s", false), None);
+
+        for text in ["#s", "@s", "$s", "//s"] {
+            assert_eq!(aliases.rewrite_text(text, false), None);
+        }
+
+        aliases.set_prefixes(&["."]);
+        assert_eq!(
+            aliases.rewrite_text(".s", false),
+            Some((".start".to_string(), 2, 6))
+        );
+        assert_eq!(aliases.rewrite_text("/s", false), None);
+        assert_eq!(
+            aliases.rewrite_text("/s", true),
+            Some(("/start".to_string(), 2, 6))
+        );
+    }
+
+    #[test]
+    fn test_shift_entity() {
+        let bold = |offset, length| {
+            tl::enums::MessageEntity::Bold(tl::types::MessageEntityBold { offset, length })
+        };
+
+        // `/s hi` becomes `/start hi`.
+        let mut after = bold(3, 2);
+        shift_entity(&mut after, 2, 4);
+        assert_eq!(after, bold(7, 2));
+
+        let mut containing = bold(0, 5);
+        shift_entity(&mut containing, 2, 4);
+        assert_eq!(containing, bold(0, 9));
+
+        let mut command = bold(0, 2);
+        shift_entity(&mut command, 2, 4);
+        assert_eq!(command, bold(0, 6));
+
+        let mut before = bold(0, 1);
+        shift_entity(&mut before, 2, 4);
+        assert_eq!(before, bold(0, 1));
+    }
+}
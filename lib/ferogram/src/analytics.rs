@@ -0,0 +1,216 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Analytics module.
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use grammers_client::{Client, Update};
+use tokio::sync::Mutex;
+
+use crate::{flow, Flow, Injector, Middleware, Result, Storage};
+
+/// The seconds of an hour, the size of the buckets.
+const HOUR: u64 = 60 * 60;
+
+/// The message counts of the users in a chat, in an hour.
+type Bucket = HashMap<i64, usize>;
+
+/// An after-type middleware which counts the messages per user, chat and hour.
+///
+/// The counts are kept in a [`Storage`], and queried to build `/stats` and
+/// activity reports. Only the handled updates reach the after-type middlewares,
+/// so register a catch-all handler to count every message.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use std::time::{Duration, SystemTime};
+///
+/// use ferogram::{analytics::Analytics, Context, MemoryStorage};
+///
+/// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+/// let analytics = Analytics::new(MemoryStorage::default());
+///
+/// let dispatcher = dispatcher
+///     .middlewares(|middlewares| middlewares.after(analytics.clone()))
+///     .resources(|injector| injector.with(analytics));
+///
+/// async fn stats(ctx: Context, analytics: Analytics) -> ferogram::Result<()> {
+///     let chat = ctx.chat().unwrap();
+///     let since = SystemTime::now() - Duration::from_secs(7 * 24 * 60 * 60);
+///
+///     for (user_id, count) in analytics.top_users(chat.id(), since).await?.iter().take(10) {
+///         // ...
+///     }
+///
+///     Ok(())
+/// }
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Analytics {
+    /// The storage of the counts.
+    storage: Arc<dyn Storage>,
+    /// Serializes the changes to the buckets.
+    lock: Arc<Mutex<()>>,
+}
+
+impl Analytics {
+    /// Creates the analytics backed by `storage`.
+    pub fn new<S: Storage>(storage: S) -> Self {
+        Self {
+            storage: Arc::new(storage),
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Counts a message of the user in the chat, sent at the time.
+    pub async fn record(&self, chat_id: i64, user_id: i64, time: SystemTime) -> Result<()> {
+        let key = bucket_key(chat_id, hour_of(time));
+
+        let _guard = self.lock.lock().await;
+
+        let mut bucket = self.bucket(&key).await?;
+        *bucket.entry(user_id).or_default() += 1;
+
+        let value = bucket
+            .iter()
+            .map(|(user_id, count)| format!("{}:{}", user_id, count))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        self.storage.set(&key, value).await
+    }
+
+    /// Returns the users of the chat by their message count since the time,
+    /// from the most to the least active.
+    pub async fn top_users(&self, chat_id: i64, since: SystemTime) -> Result<Vec<(i64, usize)>> {
+        let mut totals = HashMap::<i64, usize>::new();
+
+        for hour in hours_since(since) {
+            for (user_id, count) in self.bucket(&bucket_key(chat_id, hour)).await? {
+                *totals.entry(user_id).or_default() += count;
+            }
+        }
+
+        let mut users = totals.into_iter().collect::<Vec<_>>();
+        users.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        Ok(users)
+    }
+
+    /// Returns the message count of the user in the chat since the time.
+    pub async fn user_messages(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        since: SystemTime,
+    ) -> Result<usize> {
+        let mut total = 0;
+
+        for hour in hours_since(since) {
+            let bucket = self.bucket(&bucket_key(chat_id, hour)).await?;
+            total += bucket.get(&user_id).copied().unwrap_or(0);
+        }
+
+        Ok(total)
+    }
+
+    /// Returns the message count of the chat since the time.
+    pub async fn chat_messages(&self, chat_id: i64, since: SystemTime) -> Result<usize> {
+        Ok(self
+            .hourly(chat_id, since)
+            .await?
+            .iter()
+            .map(|(_, count)| count)
+            .sum())
+    }
+
+    /// Returns the message count of the chat in each hour since the time,
+    /// from the oldest hour, skipping the ones without messages.
+    pub async fn hourly(
+        &self,
+        chat_id: i64,
+        since: SystemTime,
+    ) -> Result<Vec<(SystemTime, usize)>> {
+        let mut hours = Vec::new();
+
+        for hour in hours_since(since) {
+            let count = self
+                .bucket(&bucket_key(chat_id, hour))
+                .await?
+                .values()
+                .sum::<usize>();
+
+            if count > 0 {
+                hours.push((UNIX_EPOCH + Duration::from_secs(hour * HOUR), count));
+            }
+        }
+
+        Ok(hours)
+    }
+
+    /// Loads the bucket of the key.
+    async fn bucket(&self, key: &str) -> Result<Bucket> {
+        let value = self.storage.get(key).await?.unwrap_or_default();
+
+        Ok(value
+            .lines()
+            .filter_map(|line| {
+                let (user_id, count) = line.split_once(':')?;
+
+                Some((user_id.parse().ok()?, count.parse().ok()?))
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl Middleware for Analytics {
+    async fn handle(&mut self, client: &Client, update: &Update, injector: &mut Injector) -> Flow {
+        if let Err(e) = self.try_handle(client, update, injector).await {
+            log::error!("Error recording the message: {:?}", e);
+        }
+
+        flow::continue_now()
+    }
+
+    async fn try_handle(&mut self, _: &Client, update: &Update, _: &mut Injector) -> Result<Flow> {
+        if let Update::NewMessage(message) = update {
+            if let Some(sender) = message.sender() {
+                self.record(message.chat().id(), sender.id(), message.date().into())
+                    .await?;
+            }
+        }
+
+        Ok(flow::continue_now())
+    }
+}
+
+/// Returns the storage key of the bucket.
+fn bucket_key(chat_id: i64, hour: u64) -> String {
+    format!("stats:{}:{}", chat_id, hour)
+}
+
+/// Returns the hours since the Unix epoch of the time.
+fn hour_of(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs() / HOUR)
+}
+
+/// Returns the hours from the time until now.
+fn hours_since(since: SystemTime) -> std::ops::RangeInclusive<u64> {
+    hour_of(since)..=hour_of(SystemTime::now())
+}
@@ -0,0 +1,432 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Anti-spam module.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, VecDeque},
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use grammers_client::{
+    types::{Chat, Message, PackedChat},
+    Client, Update,
+};
+
+use crate::{
+    flow,
+    utils::{banned_rights, edit_banned},
+    ChatSettings, Entity, Flow, Injector, Middleware,
+};
+
+/// The number of verdicts kept by [`AntiSpam`].
+const MAX_VERDICTS: usize = 256;
+
+/// Scores how likely a message is spam.
+///
+/// The scorers are shared by the updates, so they must keep their state behind
+/// a lock.
+#[async_trait]
+pub trait SpamScorer: Send + Sync + 'static {
+    /// Scores the message, from `0.0` (not spam) to `1.0` (spam).
+    async fn score(&self, message: &Message) -> f32;
+}
+
+/// The action taken when a message is spam.
+#[derive(Clone, Debug)]
+pub enum SpamAction {
+    /// Deletes the message.
+    Delete,
+    /// Mutes the sender for the duration, or forever if `None`.
+    ///
+    /// Only works in supergroups.
+    Mute(Option<Duration>),
+    /// Forwards the message to the chat, like the owner's.
+    Report(PackedChat),
+}
+
+/// A middleware which stops the spam messages.
+///
+/// The scores of the scorers are summed, by their weights, and the actions are
+/// taken when the sum reaches the threshold. The spam messages are not handled.
+///
+/// With [`AntiSpam::chat_settings`], the chats can disable the feature `antispam`
+/// and set their own threshold in the setting `antispam_threshold`.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use std::time::Duration;
+///
+/// use ferogram::antispam::{AntiSpam, FrequencyScorer, LinkScorer, SpamAction};
+///
+/// # let middlewares: ferogram::MiddlewareStack = unimplemented!();
+/// let antispam = AntiSpam::default()
+///     .scorer(FrequencyScorer::new(5, Duration::from_secs(10)), 1.0)
+///     .scorer(LinkScorer::default(), 0.5)
+///     .threshold(1.0)
+///     .action(SpamAction::Delete)
+///     .action(SpamAction::Mute(Some(Duration::from_secs(3600))));
+///
+/// let middlewares = middlewares.before(antispam);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct AntiSpam {
+    /// The scorers and their weights.
+    scorers: Vec<(Arc<dyn SpamScorer>, f32)>,
+    /// The default threshold.
+    threshold: f32,
+    /// The actions taken on spam.
+    actions: Vec<SpamAction>,
+    /// The per-chat settings.
+    settings: Option<ChatSettings>,
+    /// The verdicts of the last messages, since the middleware runs once per handler.
+    verdicts: Arc<Mutex<VecDeque<(i64, i32, bool)>>>,
+}
+
+impl AntiSpam {
+    /// Adds a scorer with the weight of its score.
+    pub fn scorer<S: SpamScorer>(mut self, scorer: S, weight: f32) -> Self {
+        self.scorers.push((Arc::new(scorer), weight));
+        self
+    }
+
+    /// Sets the default threshold.
+    ///
+    /// Default is `1.0`.
+    pub fn threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Adds an action taken on spam.
+    ///
+    /// Default is none, only the handling is stopped.
+    pub fn action(mut self, action: SpamAction) -> Self {
+        self.actions.push(action);
+        self
+    }
+
+    /// Sets the per-chat settings.
+    pub fn chat_settings(mut self, settings: ChatSettings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    /// Scores the message with every scorer.
+    pub async fn score(&self, message: &Message) -> f32 {
+        let mut score = 0.0;
+        for (scorer, weight) in self.scorers.iter() {
+            score += scorer.score(message).await * weight;
+        }
+
+        score
+    }
+
+    /// Returns the threshold of the chat, or `None` if the chat disabled the feature.
+    async fn chat_threshold(&self, chat_id: i64) -> Option<f32> {
+        let Some(settings) = self.settings.as_ref() else {
+            return Some(self.threshold);
+        };
+
+        if let Ok(Some(false)) = settings.feature(chat_id, "antispam").await {
+            return None;
+        }
+
+        match settings.get(chat_id, "antispam_threshold").await {
+            Ok(Some(threshold)) => Some(threshold),
+            _ => Some(self.threshold),
+        }
+    }
+
+    /// Takes the actions on the message.
+    async fn take_actions(&self, client: &Client, message: &Message, score: f32) {
+        let chat = message.chat();
+
+        for action in self.actions.iter() {
+            let result = match action {
+                SpamAction::Delete => message.delete().await,
+                SpamAction::Mute(duration) => match message.sender() {
+                    Some(sender) => {
+                        let until_date = duration.map_or(0, |duration| {
+                            (SystemTime::now() + duration)
+                                .duration_since(UNIX_EPOCH)
+                                .map_or(0, |date| date.as_secs() as i32)
+                        });
+
+                        edit_banned(
+                            client,
+                            &chat,
+                            &sender,
+                            banned_rights(true, false, until_date),
+                        )
+                        .await
+                    }
+                    None => Ok(()),
+                },
+                SpamAction::Report(owner) => {
+                    let text = format!(
+                        "Spam score {:.2} in {} ({}).",
+                        score,
+                        chat.name().unwrap_or_default(),
+                        chat.id()
+                    );
+
+                    match message.forward_to(*owner).await {
+                        Ok(forwarded) => forwarded.reply(text).await.map(|_| ()),
+                        Err(e) => Err(e),
+                    }
+                }
+            };
+
+            if let Err(e) = result {
+                log::error!("Error taking spam action: {:?}", e);
+            }
+        }
+    }
+}
+
+impl Default for AntiSpam {
+    fn default() -> Self {
+        Self {
+            scorers: Vec::new(),
+            threshold: 1.0,
+            actions: Vec::new(),
+            settings: None,
+            verdicts: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl Middleware for AntiSpam {
+    async fn handle(&mut self, client: &Client, update: &Update, _: &mut Injector) -> Flow {
+        let Update::NewMessage(message) = update else {
+            return flow::continue_now();
+        };
+
+        if message.outgoing() || matches!(message.chat(), Chat::User(_)) {
+            return flow::continue_now();
+        }
+
+        let key = (message.chat().id(), message.id());
+        let verdict = self
+            .verdicts
+            .lock()
+            .expect("Failed to lock the verdicts")
+            .iter()
+            .find_map(|(chat_id, message_id, is_spam)| {
+                (*chat_id == key.0 && *message_id == key.1).then_some(*is_spam)
+            });
+
+        let is_spam = match verdict {
+            Some(is_spam) => is_spam,
+            None => {
+                let is_spam = match self.chat_threshold(key.0).await {
+                    Some(threshold) => {
+                        let score = self.score(message).await;
+                        if score >= threshold {
+                            self.take_actions(client, message, score).await;
+                        }
+
+                        score >= threshold
+                    }
+                    None => false,
+                };
+
+                let mut verdicts = self.verdicts.lock().expect("Failed to lock the verdicts");
+                if verdicts.len() >= MAX_VERDICTS {
+                    verdicts.pop_front();
+                }
+                verdicts.push_back((key.0, key.1, is_spam));
+
+                is_spam
+            }
+        };
+
+        if is_spam {
+            flow::break_now()
+        } else {
+            flow::continue_now()
+        }
+    }
+}
+
+/// The recent messages of each sender in each chat.
+type History<T> = Arc<Mutex<HashMap<(i64, i64), VecDeque<T>>>>;
+
+/// Returns the chat and sender of the message, which identify the history.
+fn history_key(message: &Message) -> (i64, i64) {
+    (
+        message.chat().id(),
+        message.sender().map_or(0, |sender| sender.id()),
+    )
+}
+
+/// Scores `1.0` when the sender sends more than `limit` messages in the window.
+#[derive(Clone, Debug)]
+pub struct FrequencyScorer {
+    /// The messages allowed in the window.
+    limit: usize,
+    /// The window.
+    window: Duration,
+    /// The send times of the messages of each sender in each chat.
+    history: History<Instant>,
+}
+
+impl FrequencyScorer {
+    /// Creates a scorer allowing `limit` messages in the window.
+    pub fn new(limit: usize, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            history: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl SpamScorer for FrequencyScorer {
+    async fn score(&self, message: &Message) -> f32 {
+        let now = Instant::now();
+
+        let mut history = self.history.lock().expect("Failed to lock the history");
+        history.retain(|_, times| {
+            times.retain(|time| now.duration_since(*time) < self.window);
+            !times.is_empty()
+        });
+
+        let times = history.entry(history_key(message)).or_default();
+        times.push_back(now);
+
+        if times.len() > self.limit {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Scores `1.0` when the sender repeats the same text more than `repeats` times in the window.
+#[derive(Clone, Debug)]
+pub struct DuplicateScorer {
+    /// The repetitions allowed in the window.
+    repeats: usize,
+    /// The window.
+    window: Duration,
+    /// The text hashes and send times of the messages of each sender in each chat.
+    history: History<(u64, Instant)>,
+}
+
+impl DuplicateScorer {
+    /// Creates a scorer allowing `repeats` repetitions in the window.
+    pub fn new(repeats: usize, window: Duration) -> Self {
+        Self {
+            repeats,
+            window,
+            history: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl SpamScorer for DuplicateScorer {
+    async fn score(&self, message: &Message) -> f32 {
+        let text = message.text().trim().to_lowercase();
+        if text.is_empty() {
+            return 0.0;
+        }
+
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let now = Instant::now();
+
+        let mut history = self.history.lock().expect("Failed to lock the history");
+        history.retain(|_, texts| {
+            texts.retain(|(_, time)| now.duration_since(*time) < self.window);
+            !texts.is_empty()
+        });
+
+        let texts = history.entry(history_key(message)).or_default();
+        texts.push_back((hash, now));
+
+        let count = texts.iter().filter(|(other, _)| *other == hash).count();
+        if count > self.repeats + 1 {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Scores the ratio of links to words in the message.
+///
+/// Ex: a message with 2 links in 4 words scores `0.5`.
+#[derive(Clone, Debug, Default)]
+pub struct LinkScorer;
+
+#[async_trait]
+impl SpamScorer for LinkScorer {
+    async fn score(&self, message: &Message) -> f32 {
+        let text = message.text();
+
+        let words = text.split_whitespace().count();
+        if words == 0 {
+            return 0.0;
+        }
+
+        let links = message.fmt_entities().map_or(0, |entities| {
+            Entity::parse(text, entities)
+                .iter()
+                .filter(|entity| entity.url().is_some())
+                .count()
+        });
+
+        (links as f32 / words as f32).min(1.0)
+    }
+}
+
+/// Scores the senders which look like new accounts.
+///
+/// The user IDs grow over time, so the accounts with an ID above `min_id` are
+/// likely new. Scores `0.7` for those and `0.3` more if there is no username.
+#[derive(Clone, Debug)]
+pub struct NewAccountScorer {
+    /// The lowest ID considered new.
+    min_id: i64,
+}
+
+impl NewAccountScorer {
+    /// Creates a scorer considering new the accounts with an ID above `min_id`.
+    pub fn new(min_id: i64) -> Self {
+        Self { min_id }
+    }
+}
+
+#[async_trait]
+impl SpamScorer for NewAccountScorer {
+    async fn score(&self, message: &Message) -> f32 {
+        match message.sender() {
+            Some(Chat::User(user)) if user.id() >= self.min_id => {
+                if user.username().is_none() {
+                    1.0
+                } else {
+                    0.7
+                }
+            }
+            _ => 0.0,
+        }
+    }
+}
@@ -0,0 +1,251 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Broadcast module.
+
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use grammers_client::{
+    reply_markup::ReplyMarkup,
+    types::{Chat, InputMessage},
+    Client,
+};
+
+use crate::{utils::escape_html, Cache};
+
+/// The message factory of a broadcast.
+type MessageFactory = Arc<dyn Fn(&Recipient) -> InputMessage + Send + Sync>;
+
+/// The keyboard factory of a broadcast.
+type KeyboardFactory = Arc<dyn Fn(&Recipient) -> Option<ReplyMarkup> + Send + Sync>;
+
+/// A recipient of a broadcast, with its cached info.
+#[derive(Clone, Debug)]
+pub struct Recipient {
+    /// The ID of the chat.
+    pub id: i64,
+    /// The cached chat, if it was seen by the dispatcher.
+    pub chat: Option<Chat>,
+}
+
+impl Recipient {
+    /// Returns the name of the chat, the full name for users.
+    pub fn name(&self) -> Option<&str> {
+        self.chat.as_ref().and_then(Chat::name)
+    }
+
+    /// Returns the first name of the user, or the name of the chat.
+    pub fn first_name(&self) -> Option<&str> {
+        match self.chat.as_ref() {
+            Some(Chat::User(user)) => user.first_name(),
+            _ => self.name(),
+        }
+    }
+
+    /// Returns the username of the chat.
+    pub fn username(&self) -> Option<&str> {
+        self.chat.as_ref().and_then(Chat::username)
+    }
+
+    /// Returns the language of the user, as set in their app.
+    pub fn lang_code(&self) -> Option<&str> {
+        match self.chat.as_ref() {
+            Some(Chat::User(user)) => user.lang_code(),
+            _ => None,
+        }
+    }
+
+    /// Renders the HTML template for the recipient.
+    ///
+    /// Accepts the placeholders `{id}`, `{name}`, `{first_name}`, `{username}` and
+    /// `{lang}`, the unknown values are left empty.
+    pub fn render(&self, template: &str) -> String {
+        template
+            .replace("{id}", &self.id.to_string())
+            .replace("{name}", &escape_html(self.name().unwrap_or_default()))
+            .replace(
+                "{first_name}",
+                &escape_html(self.first_name().unwrap_or_default()),
+            )
+            .replace(
+                "{username}",
+                &escape_html(self.username().unwrap_or_default()),
+            )
+            .replace("{lang}", self.lang_code().unwrap_or_default())
+    }
+}
+
+/// The result of a broadcast.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BroadcastReport {
+    /// The recipients which received the message.
+    pub sent: Vec<i64>,
+    /// The recipients whose message could not be sent.
+    pub failed: Vec<i64>,
+    /// The recipients never seen by the dispatcher, so not reachable.
+    pub unknown: Vec<i64>,
+}
+
+/// Sends a personalized message to many chats.
+///
+/// Each recipient is looked up in the [`Cache`], so the message and the keyboard
+/// can use its name, username and language. The recipients not cached can't be
+/// reached and are skipped.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// # let ctx: ferogram::Context = unimplemented!();
+/// use std::time::{Duration, SystemTime};
+///
+/// use ferogram::broadcast::Broadcaster;
+/// use grammers_client::{button, reply_markup};
+///
+/// let broadcaster = Broadcaster::new([123456789, 987654321])
+///     .template("Hello, <b>{first_name}</b>! Version 2.0 is out.")
+///     .keyboard(|recipient| {
+///         let url = match recipient.lang_code() {
+///             Some("pt") => "https://example.com/pt",
+///             _ => "https://example.com",
+///         };
+///
+///         Some(reply_markup::inline(vec![vec![button::url("Changelog", url)]]))
+///     })
+///     .schedule_at(SystemTime::now() + Duration::from_secs(3600));
+///
+/// if let Some(cache) = ctx.cache() {
+///     let report = broadcaster.send(ctx.client(), cache).await;
+///     println!("Sent to {} chats", report.sent.len());
+/// }
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Broadcaster {
+    /// The IDs of the recipients.
+    recipients: Vec<i64>,
+    /// The message factory.
+    message: MessageFactory,
+    /// The keyboard factory, if any.
+    keyboard: Option<KeyboardFactory>,
+    /// The interval between the messages.
+    interval: Duration,
+    /// When to start sending, if scheduled.
+    scheduled_at: Option<SystemTime>,
+}
+
+impl Broadcaster {
+    /// Creates a broadcast to the recipients, with an empty message.
+    pub fn new<I: IntoIterator<Item = i64>>(recipients: I) -> Self {
+        Self {
+            recipients: recipients.into_iter().collect(),
+            message: Arc::new(|_| InputMessage::text("")),
+            keyboard: None,
+            interval: Duration::from_millis(50),
+            scheduled_at: None,
+        }
+    }
+
+    /// Sets the HTML template of the message, see [`Recipient::render`].
+    pub fn template(mut self, template: &str) -> Self {
+        let template = template.to_string();
+
+        self.message = Arc::new(move |recipient| InputMessage::html(recipient.render(&template)));
+        self
+    }
+
+    /// Sets the factory building the message of each recipient.
+    pub fn message<F: Fn(&Recipient) -> InputMessage + Send + Sync + 'static>(
+        mut self,
+        factory: F,
+    ) -> Self {
+        self.message = Arc::new(factory);
+        self
+    }
+
+    /// Sets the factory building the keyboard of each recipient.
+    ///
+    /// Overrides the keyboard set by the message factory, if any is returned.
+    pub fn keyboard<F: Fn(&Recipient) -> Option<ReplyMarkup> + Send + Sync + 'static>(
+        mut self,
+        factory: F,
+    ) -> Self {
+        self.keyboard = Some(Arc::new(factory));
+        self
+    }
+
+    /// Sets the interval between the messages, to not hit the flood limits.
+    ///
+    /// Default is 50 milliseconds.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Schedules the broadcast, so [`Broadcaster::send`] waits until `time`.
+    pub fn schedule_at(mut self, time: SystemTime) -> Self {
+        self.scheduled_at = Some(time);
+        self
+    }
+
+    /// Sends the messages, waiting for the scheduled time, if any.
+    pub async fn send(&self, client: &Client, cache: &Cache) -> BroadcastReport {
+        if let Some(time) = self.scheduled_at {
+            if let Ok(delay) = time.duration_since(SystemTime::now()) {
+                tokio::time::sleep(delay).await;
+            }
+        }
+
+        let mut report = BroadcastReport::default();
+
+        for id in self.recipients.iter().copied() {
+            let Some(chat) = cache.get_chat(id) else {
+                report.unknown.push(id);
+                continue;
+            };
+
+            if !report.sent.is_empty() || !report.failed.is_empty() {
+                tokio::time::sleep(self.interval).await;
+            }
+
+            let recipient = Recipient {
+                id,
+                chat: Some(chat.clone()),
+            };
+
+            let mut message = (self.message)(&recipient);
+            if let Some(keyboard) = self.keyboard.as_ref().and_then(|f| f(&recipient)) {
+                message = message.reply_markup(keyboard);
+            }
+
+            match client.send_message(&chat, message).await {
+                Ok(_) => report.sent.push(id),
+                Err(e) => {
+                    log::error!("Error broadcasting to {}: {:?}", id, e);
+                    report.failed.push(id);
+                }
+            }
+        }
+
+        report
+    }
+}
+
+impl fmt::Debug for Broadcaster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Broadcaster")
+            .field("recipients", &self.recipients)
+            .field("interval", &self.interval)
+            .field("scheduled_at", &self.scheduled_at)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,459 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Cache module.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use grammers_client::{grammers_tl_types as tl, types::Chat, Update};
+
+use crate::{Result, Storage};
+
+/// The storage key of the chat migrations.
+const MIGRATIONS_KEY: &str = "chat_migrations";
+
+/// The cache statistics, by [`Cache::stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The cached chats, including the users.
+    pub chats: usize,
+    /// The cached usernames.
+    pub usernames: usize,
+    /// The lookups which found a chat.
+    pub hits: u64,
+    /// The lookups which found nothing.
+    pub misses: u64,
+}
+
+/// The peers cached by the dispatcher.
+#[derive(Debug)]
+struct Peers<T = Chat> {
+    /// The chats by their IDs, with their lowercase usernames and when they were last seen.
+    chats: HashMap<i64, (T, Vec<String>, u64)>,
+    /// The IDs of the chats by when they were last seen.
+    seen: BTreeMap<u64, i64>,
+    /// The IDs of the chats by their lowercase usernames.
+    usernames: HashMap<String, i64>,
+    /// The counter of the sightings.
+    tick: u64,
+}
+
+impl<T> Default for Peers<T> {
+    fn default() -> Self {
+        Self {
+            chats: HashMap::new(),
+            seen: BTreeMap::new(),
+            usernames: HashMap::new(),
+            tick: 0,
+        }
+    }
+}
+
+impl<T: Clone> Peers<T> {
+    /// Inserts the chat as the most recently seen, dropping the least recently
+    /// seen chats past the capacity.
+    fn insert(&mut self, chat_id: i64, usernames: Vec<String>, chat: T, capacity: usize) {
+        self.remove(chat_id);
+
+        for username in usernames.iter() {
+            self.usernames.insert(username.clone(), chat_id);
+        }
+
+        let tick = self.next_tick();
+        self.seen.insert(tick, chat_id);
+        self.chats.insert(chat_id, (chat, usernames, tick));
+
+        while self.chats.len() > capacity {
+            let Some((_, chat_id)) = self.seen.pop_first() else {
+                break;
+            };
+            self.remove(chat_id);
+        }
+    }
+
+    /// Gets the chat by its ID, marking it as the most recently seen.
+    fn get(&mut self, chat_id: i64) -> Option<T> {
+        let tick = self.next_tick();
+        let (chat, _, seen) = self.chats.get_mut(&chat_id)?;
+
+        self.seen.remove(seen);
+        self.seen.insert(tick, chat_id);
+        *seen = tick;
+
+        Some(chat.clone())
+    }
+
+    /// Gets the chat by its lowercase username, marking it as the most recently seen.
+    fn get_by_username(&mut self, username: &str) -> Option<T> {
+        let chat_id = *self.usernames.get(username)?;
+        self.get(chat_id)
+    }
+
+    /// Removes the chat and its usernames.
+    fn remove(&mut self, chat_id: i64) -> Option<T> {
+        let (chat, usernames, tick) = self.chats.remove(&chat_id)?;
+
+        self.seen.remove(&tick);
+        for username in usernames {
+            if self.usernames.get(&username) == Some(&chat_id) {
+                self.usernames.remove(&username);
+            }
+        }
+
+        Some(chat)
+    }
+
+    /// Returns the next sighting.
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+/// A bounded cache of the peers seen by the dispatcher.
+///
+/// Once attached with [`crate::Dispatcher::peer_cache`], the chats and senders of
+/// every update are cached, so the handlers and filters can look them up without
+/// calling Telegram. The least recently seen or looked up peers are dropped past
+/// the capacity.
+/// Get it from [`crate::Context::cache`] or inject it.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// # let ctx: ferogram::Context = unimplemented!();
+/// if let Some(chat) = ctx.cache().and_then(|cache| cache.resolve_username("ferogram")) {
+///     println!("Found {:?}", chat.name());
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Cache {
+    /// The max peers kept.
+    capacity: usize,
+    /// The cached peers.
+    peers: Arc<Mutex<Peers>>,
+    /// The lookups which found a chat.
+    hits: Arc<AtomicU64>,
+    /// The lookups which found nothing.
+    misses: Arc<AtomicU64>,
+}
+
+impl Cache {
+    /// Creates a cache keeping up to `capacity` peers.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            peers: Arc::new(Mutex::new(Peers::default())),
+            hits: Arc::new(AtomicU64::new(0)),
+            misses: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Gets the chat by its ID.
+    pub fn get_chat(&self, chat_id: i64) -> Option<Chat> {
+        let chat = self
+            .peers
+            .lock()
+            .expect("Failed to lock the cache")
+            .get(chat_id);
+
+        self.count(chat.is_some());
+        chat
+    }
+
+    /// Gets the chat by its username, with or without the `@`.
+    pub fn resolve_username(&self, username: &str) -> Option<Chat> {
+        let username = username.trim_start_matches('@').to_lowercase();
+
+        let chat = self
+            .peers
+            .lock()
+            .expect("Failed to lock the cache")
+            .get_by_username(&username);
+
+        self.count(chat.is_some());
+        chat
+    }
+
+    /// Returns the cache statistics.
+    pub fn stats(&self) -> CacheStats {
+        let peers = self.peers.lock().expect("Failed to lock the cache");
+
+        CacheStats {
+            chats: peers.chats.len(),
+            usernames: peers.usernames.len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Caches the chats and the senders of the update.
+    pub(crate) fn record(&self, update: &Update) {
+        let chats = match update {
+            Update::NewMessage(message) | Update::MessageEdited(message) => {
+                let mut chats = vec![message.chat()];
+                chats.extend(message.sender());
+                chats
+            }
+            Update::CallbackQuery(query) => vec![query.chat().clone(), query.sender().clone()],
+            Update::InlineQuery(query) => vec![Chat::User(query.sender().clone())],
+            Update::InlineSend(inline_send) => vec![Chat::User(inline_send.sender().clone())],
+            _ => return,
+        };
+
+        let mut peers = self.peers.lock().expect("Failed to lock the cache");
+        for chat in chats {
+            let usernames = chat
+                .usernames()
+                .into_iter()
+                .map(str::to_lowercase)
+                .collect();
+
+            peers.insert(chat.id(), usernames, chat, self.capacity);
+        }
+    }
+
+    /// Counts a lookup.
+    fn count(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// The group migrations to supergroup seen by the dispatcher.
+///
+/// Always recorded, from the migration service messages, so [`crate::filter::id`]
+/// follows the migrated chats. Inject it to look them up.
+///
+/// Kept in memory by default, use [`ChatMigrations::load`] and
+/// [`crate::Dispatcher::chat_migrations`] to keep them across restarts.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// # let migrations: ferogram::ChatMigrations = unimplemented!();
+/// # let old_id = 0;
+/// let chat_id = migrations.resolve(old_id);
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct ChatMigrations {
+    /// The IDs of the supergroups by the IDs of the groups they were migrated from.
+    chats: Arc<Mutex<HashMap<i64, i64>>>,
+    /// The storage of the migrations, if persisted.
+    storage: Option<Arc<dyn Storage>>,
+    /// Serializes the saves, so an older map doesn't overwrite a newer one.
+    save_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl ChatMigrations {
+    /// Loads the migrations saved in `storage`, where the new ones are saved.
+    ///
+    /// Only one process should record the migrations in the storage.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// use ferogram::{ChatMigrations, MemoryStorage};
+    ///
+    /// let migrations = ChatMigrations::load(MemoryStorage::default()).await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the migrations could not be read or are not valid.
+    pub async fn load<S: Storage>(storage: S) -> Result<Self> {
+        let storage: Arc<dyn Storage> = Arc::new(storage);
+        let chats = match storage.get(MIGRATIONS_KEY).await? {
+            Some(value) => decode_migrations(&value)?,
+            None => HashMap::new(),
+        };
+
+        Ok(Self {
+            chats: Arc::new(Mutex::new(chats)),
+            storage: Some(storage),
+            ..Default::default()
+        })
+    }
+
+    /// Gets the ID of the supergroup which the group was migrated to.
+    pub fn get(&self, chat_id: i64) -> Option<i64> {
+        self.chats
+            .lock()
+            .expect("Failed to lock the migrations")
+            .get(&chat_id)
+            .copied()
+    }
+
+    /// Returns the current ID of the chat, following its migrations.
+    ///
+    /// Returns the ID itself if the chat was never migrated.
+    pub fn resolve(&self, chat_id: i64) -> i64 {
+        let chats = self.chats.lock().expect("Failed to lock the migrations");
+
+        let mut current_id = chat_id;
+        // Bounded by the migrations, in case of a cycle.
+        for _ in 0..=chats.len() {
+            match chats.get(&current_id) {
+                Some(&new_id) => current_id = new_id,
+                None => break,
+            }
+        }
+
+        current_id
+    }
+
+    /// Records the migration if the update is a migration service message.
+    ///
+    /// Returns an error if the migration could not be saved.
+    pub(crate) async fn record(&self, update: &Update) -> Result<()> {
+        if let Update::NewMessage(message) = update {
+            if let Some(tl::enums::MessageAction::ChatMigrateTo(action)) = message.action() {
+                self.insert(message.chat().id(), action.channel_id).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Inserts the migration and saves the migrations, if persisted.
+    async fn insert(&self, chat_id: i64, new_id: i64) -> Result<()> {
+        let _save = self.save_lock.lock().await;
+        let value = {
+            let mut chats = self.chats.lock().expect("Failed to lock the migrations");
+            chats.insert(chat_id, new_id);
+
+            encode_migrations(&chats)
+        };
+
+        match self.storage.as_ref() {
+            Some(storage) => storage.set(MIGRATIONS_KEY, value).await,
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::fmt::Debug for ChatMigrations {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChatMigrations")
+            .field("chats", &self.chats)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Encodes the migrations as `old_id:new_id` pairs, separated by commas.
+fn encode_migrations(chats: &HashMap<i64, i64>) -> String {
+    chats
+        .iter()
+        .map(|(chat_id, new_id)| format!("{}:{}", chat_id, new_id))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Decodes the migrations encoded by [`encode_migrations`].
+fn decode_migrations(value: &str) -> Result<HashMap<i64, i64>> {
+    value
+        .split(',')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            pair.split_once(':')
+                .and_then(|(chat_id, new_id)| Some((chat_id.parse().ok()?, new_id.parse().ok()?)))
+                .ok_or_else(|| {
+                    crate::Error::invalid_input(format!("Invalid chat migration: {}", pair)).into()
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryStorage;
+
+    #[tokio::test]
+    async fn test_migrations_round_trip() {
+        let storage = MemoryStorage::default();
+
+        let migrations = ChatMigrations::load(storage.clone()).await.unwrap();
+        migrations.insert(-1, -1001).await.unwrap();
+        migrations.insert(-1001, -1002).await.unwrap();
+        migrations.insert(-2, -1003).await.unwrap();
+
+        let loaded = ChatMigrations::load(storage.clone()).await.unwrap();
+        assert_eq!(loaded.get(-1), Some(-1001));
+        assert_eq!(loaded.resolve(-1), -1002);
+        assert_eq!(loaded.resolve(-2), -1003);
+        assert_eq!(loaded.resolve(-3), -3);
+
+        storage
+            .set(MIGRATIONS_KEY, String::from("-1:abc"))
+            .await
+            .unwrap();
+        assert!(ChatMigrations::load(storage).await.is_err());
+    }
+
+    #[test]
+    fn test_peers_eviction() {
+        let mut peers = Peers::default();
+        peers.insert(1, vec![String::from("one")], "one", 2);
+        peers.insert(2, vec![String::from("two")], "two", 2);
+        peers.insert(3, vec![String::from("three")], "three", 2);
+
+        assert_eq!(peers.chats.len(), 2);
+        assert_eq!(peers.seen.len(), 2);
+        assert_eq!(peers.get(1), None);
+        assert_eq!(peers.get_by_username("one"), None);
+        assert_eq!(peers.get(2), Some("two"));
+        assert_eq!(peers.get_by_username("three"), Some("three"));
+    }
+
+    #[test]
+    fn test_peers_get_refreshes() {
+        let mut peers = Peers::default();
+        peers.insert(1, Vec::new(), "one", 2);
+        peers.insert(2, Vec::new(), "two", 2);
+
+        assert_eq!(peers.get(1), Some("one"));
+        peers.insert(3, Vec::new(), "three", 2);
+        assert_eq!(peers.get(1), Some("one"));
+        assert_eq!(peers.get(2), None);
+
+        peers.insert(4, vec![String::from("four")], "four", 2);
+        assert_eq!(peers.get_by_username("four"), Some("four"));
+        peers.insert(5, Vec::new(), "five", 2);
+        assert_eq!(peers.get(4), Some("four"));
+        assert_eq!(peers.get(1), None);
+    }
+
+    #[test]
+    fn test_peers_reinsert() {
+        let mut peers = Peers::default();
+        peers.insert(1, vec![String::from("old")], "one", 2);
+        peers.insert(1, vec![String::from("new")], "one", 2);
+
+        assert_eq!(peers.chats.len(), 1);
+        assert_eq!(peers.seen.len(), 1);
+        assert_eq!(peers.get_by_username("old"), None);
+        assert_eq!(peers.get_by_username("new"), Some("one"));
+
+        assert_eq!(peers.remove(1), Some("one"));
+        assert!(peers.usernames.is_empty());
+        assert!(peers.seen.is_empty());
+    }
+}
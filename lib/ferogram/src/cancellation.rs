@@ -0,0 +1,88 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Cancellation module.
+
+use std::{
+    pin::pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use tokio::sync::Notify;
+
+/// The shared state of a [`CancellationToken`].
+#[derive(Debug, Default)]
+struct State {
+    /// Whether the token was cancelled.
+    cancelled: AtomicBool,
+    /// Wakes the tasks waiting for the cancellation.
+    notify: Notify,
+}
+
+/// A token to cancel the waits of the [`crate::Context`], like
+/// [`crate::Context::wait_for_message`].
+///
+/// The clones share the same state, so cancelling one cancels all of them.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// # let ctx: ferogram::Context = unimplemented!();
+/// use ferogram::CancellationToken;
+///
+/// let token = CancellationToken::new();
+///
+/// let waiter = token.clone();
+/// tokio::spawn(async move {
+///     let message = ctx.wait_for_message(None, Some(&waiter)).await;
+/// });
+///
+/// token.cancel();
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    /// The shared state.
+    state: Arc<State>,
+}
+
+impl CancellationToken {
+    /// Creates a new token, not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the token, waking the tasks waiting for it.
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Release);
+        self.state.notify.notify_waiters();
+    }
+
+    /// Checks if the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Waits until the token is cancelled.
+    pub async fn cancelled(&self) {
+        loop {
+            let mut notified = pin!(self.state.notify.notified());
+            notified.as_mut().enable();
+
+            if self.is_cancelled() {
+                return;
+            }
+
+            notified.await;
+        }
+    }
+}
@@ -0,0 +1,293 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Captcha module.
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::{Duration, Instant},
+};
+
+use grammers_client::{
+    button, reply_markup,
+    types::{Chat, InputMessage},
+    InvocationError, Update,
+};
+
+use crate::{
+    utils::{banned_rights, edit_banned},
+    Context, Result,
+};
+
+/// The emojis used by [`Challenge::Emoji`].
+const EMOJIS: [&str; 12] = [
+    "🍎", "🚗", "🐶", "🎈", "🌙", "⚽", "🎸", "🍕", "🌵", "🐟", "🔑", "📚",
+];
+
+/// The challenge sent to the new members.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Challenge {
+    /// A single button to press.
+    #[default]
+    Button,
+    /// Pick the emoji written in the message, between `options` emojis.
+    Emoji {
+        /// The number of emojis to pick from.
+        options: usize,
+    },
+}
+
+/// The result of a verification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// The member answered correctly and was unmuted.
+    Passed,
+    /// The member answered wrong and was removed.
+    Failed,
+    /// The member did not answer in time and was removed.
+    TimedOut,
+}
+
+/// Verifies the new members of a group.
+///
+/// Mutes the member, sends the challenge and waits for the answer. The member is
+/// unmuted when answering correctly and removed otherwise.
+///
+/// The members can only be muted in supergroups.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use std::time::Duration;
+///
+/// use ferogram::captcha::{Captcha, Challenge, Verdict};
+///
+/// # let ctx: ferogram::Context = unimplemented!();
+/// let captcha = Captcha::default()
+///     .challenge(Challenge::Emoji { options: 6 })
+///     .timeout(Duration::from_secs(120));
+///
+/// let member = ctx.sender().unwrap();
+/// if captcha.verify(&ctx, &member).await? == Verdict::Passed {
+///     ctx.send("Welcome!").await?;
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Captcha {
+    /// The challenge.
+    challenge: Challenge,
+    /// The time to answer.
+    timeout: Duration,
+    /// The text of the challenge message.
+    text: String,
+    /// The text of the button of [`Challenge::Button`].
+    button_text: String,
+    /// The text answered to the other users pressing the buttons.
+    not_for_you_text: String,
+    /// Whether to ban the member instead of kicking.
+    ban: bool,
+}
+
+impl Captcha {
+    /// Sets the challenge.
+    ///
+    /// Default is [`Challenge::Button`].
+    pub fn challenge(mut self, challenge: Challenge) -> Self {
+        self.challenge = challenge;
+        self
+    }
+
+    /// Sets the time the member has to answer.
+    ///
+    /// Default is 60 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the text of the challenge message.
+    ///
+    /// `{user}` is replaced by the name of the member and `{emoji}` by the emoji
+    /// to pick.
+    pub fn text<T: ToString>(mut self, text: T) -> Self {
+        self.text = text.to_string();
+        self
+    }
+
+    /// Sets the text of the button of [`Challenge::Button`].
+    pub fn button_text<T: ToString>(mut self, text: T) -> Self {
+        self.button_text = text.to_string();
+        self
+    }
+
+    /// Sets the text answered to the other users pressing the buttons.
+    pub fn not_for_you_text<T: ToString>(mut self, text: T) -> Self {
+        self.not_for_you_text = text.to_string();
+        self
+    }
+
+    /// Whether to ban the members who fail, instead of kicking.
+    pub fn ban(mut self, value: bool) -> Self {
+        self.ban = value;
+        self
+    }
+
+    /// Verifies the member in the chat of the context.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the member could not be restricted or removed, or the
+    /// challenge could not be sent.
+    pub async fn verify(&self, ctx: &Context, member: &Chat) -> Result<Verdict> {
+        let chat = ctx.chat().expect("No chat");
+        let prefix = format!("captcha:{}:", member.id());
+
+        restrict(ctx, &chat, member, true).await?;
+
+        let (text, buttons, answer) = match self.challenge {
+            Challenge::Button => (
+                self.text.replace("{emoji}", ""),
+                vec![vec![button::inline(
+                    self.button_text.clone(),
+                    format!("{}ok", prefix),
+                )]],
+                String::from("ok"),
+            ),
+            Challenge::Emoji { options } => {
+                let options = options.clamp(2, EMOJIS.len());
+
+                let start = random(EMOJIS.len());
+                let emojis = (0..options)
+                    .map(|i| EMOJIS[(start + i) % EMOJIS.len()])
+                    .collect::<Vec<_>>();
+                let answer = emojis[random(options)];
+
+                let buttons = emojis
+                    .chunks(3)
+                    .map(|row| {
+                        row.iter()
+                            .map(|emoji| button::inline(*emoji, format!("{}{}", prefix, emoji)))
+                            .collect()
+                    })
+                    .collect();
+
+                (
+                    self.text.replace("{emoji}", answer),
+                    buttons,
+                    answer.to_string(),
+                )
+            }
+        };
+
+        let text = text.replace("{user}", member.name().unwrap_or_default());
+        let mut waiter = ctx.waiter(None);
+        let sent = ctx
+            .send(InputMessage::text(text).reply_markup(reply_markup::inline(buttons)))
+            .await?;
+
+        let deadline = Instant::now() + self.timeout;
+        let verdict = loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break Verdict::TimedOut;
+            }
+
+            let Ok(update) = waiter.next(remaining).await else {
+                break Verdict::TimedOut;
+            };
+
+            if let Update::CallbackQuery(query) = update {
+                let Some(value) = std::str::from_utf8(query.data())
+                    .ok()
+                    .and_then(|data| data.strip_prefix(&prefix))
+                else {
+                    continue;
+                };
+
+                if query.sender().id() != member.id() {
+                    query
+                        .answer()
+                        .alert(self.not_for_you_text.clone())
+                        .send()
+                        .await?;
+                    continue;
+                }
+
+                query.answer().send().await?;
+                if value == answer {
+                    break Verdict::Passed;
+                } else {
+                    break Verdict::Failed;
+                }
+            }
+        };
+
+        sent.delete().await?;
+
+        match verdict {
+            Verdict::Passed => restrict(ctx, &chat, member, false).await?,
+            Verdict::Failed | Verdict::TimedOut => {
+                if self.ban {
+                    ban_member(ctx, &chat, member).await?;
+                } else {
+                    ctx.client().kick_participant(&chat, member).await?;
+                }
+            }
+        }
+
+        Ok(verdict)
+    }
+}
+
+impl Default for Captcha {
+    fn default() -> Self {
+        Self {
+            challenge: Challenge::default(),
+            timeout: Duration::from_secs(60),
+            text: String::from("Hello, {user}! Press the button to prove you are human. {emoji}"),
+            button_text: String::from("I'm not a robot"),
+            not_for_you_text: String::from("This challenge is not for you."),
+            ban: false,
+        }
+    }
+}
+
+/// Returns a random number below `bound`.
+fn random(bound: usize) -> usize {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+
+    hasher.finish() as usize % bound
+}
+
+/// Mutes or unmutes the member, if the chat is a supergroup.
+async fn restrict(
+    ctx: &Context,
+    chat: &Chat,
+    member: &Chat,
+    muted: bool,
+) -> std::result::Result<(), InvocationError> {
+    edit_banned(ctx.client(), chat, member, banned_rights(muted, false, 0)).await
+}
+
+/// Bans the member, if the chat is a supergroup.
+async fn ban_member(
+    ctx: &Context,
+    chat: &Chat,
+    member: &Chat,
+) -> std::result::Result<(), InvocationError> {
+    edit_banned(ctx.client(), chat, member, banned_rights(true, true, 0)).await
+}
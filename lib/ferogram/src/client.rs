@@ -8,14 +8,32 @@
 
 //! Client module.
 
-use std::path::Path;
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
 
+use futures_util::{future::join_all, Future};
 use grammers_client::{
-    grammers_tl_types as tl, session::Session, Config, InitParams, ReconnectionPolicy, SignInError,
+    grammers_tl_types as tl, session::Session, types::PackedChat, Config, InitParams,
+    InvocationError, ReconnectionPolicy, SignInError, Update,
 };
 use grammers_mtsender::ServerAddr;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    di, handler, logging,
+    reconnect::{PolicySlot, ReconnectHandler, ReconnectPolicy},
+    utils::prompt,
+    Context, CorrelationId, Dispatcher, DispatcherHandle, ErrorHandler, Reconnect, ReconnectEvent,
+    Result, Supervisor, UpdateFormatter, UpdateSource, UpdateSummary,
+};
 
-use crate::{di, utils::prompt, Context, Dispatcher, ErrorHandler, Result};
+/// How many requests [`Client::invoke_batched`] sends at once, keeping the
+/// containers within the limits of Telegram.
+const BATCH_SIZE: usize = 100;
 
 /// Wrapper about grammers' `Client` instance.
 pub struct Client {
@@ -25,16 +43,28 @@ pub struct Client {
     client_type: ClientType,
     /// The inner grammers' `Client` instance.
     inner_client: grammers_client::Client,
+    /// The source of the updates, the inner client if not set.
+    update_source: Option<Box<dyn UpdateSource>>,
 
     /// The session file path.
     session_file: Option<String>,
+    /// The interval between the session saves, if any.
+    autosave_interval: Option<Duration>,
 
     /// Whether the client is connected.
     is_connected: bool,
     /// Whether is to update Telegram's bot commands.
     set_bot_commands: bool,
+    /// Whether is to clear Telegram's bot commands before updating them.
+    clear_bot_commands: bool,
     /// Wheter is to wait for a `Ctrl + C` signal to close the connection and exit the app.
     wait_for_ctrl_c: bool,
+    /// Whether is to print the identity of the client once connected.
+    print_identity: bool,
+    /// The background task supervisor.
+    supervisor: Supervisor,
+    /// The formatter of the updates in the error logs.
+    update_formatter: UpdateFormatter,
 
     /// The global error handler.
     pub(crate) err_handler: Option<Box<dyn ErrorHandler>>,
@@ -42,6 +72,8 @@ pub struct Client {
     pub(crate) exit_handler: Option<di::Endpoint>,
     /// The ready handler.
     pub(crate) ready_handler: Option<di::Endpoint>,
+    /// The reconnection policy, kept alive while the client lives.
+    _reconnect_policy: Option<Arc<ReconnectPolicy>>,
 }
 
 impl Client {
@@ -139,7 +171,11 @@ impl Client {
                         client.session().save_to_file(session_file)?;
                     }
                     Err(e) => {
-                        panic!("Failed to sign in: {:?}", e);
+                        return Err(format!(
+                            "Failed to sign in: {}. Check if the bot token was revoked in @BotFather",
+                            e
+                        )
+                        .into());
                     }
                 },
                 ClientType::User(ref phone_number) => {
@@ -161,7 +197,7 @@ impl Client {
                             }
                         }
                         Err(e) => {
-                            panic!("Failed to sign in: {:?}", e);
+                            return Err(format!("Failed to sign in: {}", e).into());
                         }
                     }
                 }
@@ -169,6 +205,20 @@ impl Client {
         }
         self.is_connected = true;
 
+        if self.print_identity {
+            let me = client.get_me().await?;
+            let dc = client.session().get_user().map(|user| user.dc);
+
+            println!(
+                "Logged in as {} ({}) on DC {}",
+                me.username()
+                    .map(|username| format!("@{}", username))
+                    .unwrap_or_else(|| me.full_name()),
+                me.id(),
+                dc.map_or("?".to_string(), |dc| dc.to_string())
+            );
+        }
+
         Ok(self)
     }
 
@@ -224,13 +274,283 @@ impl Client {
     /// # }
     /// ```
     pub fn new_ctx(&self) -> Context {
-        let upd_receiver = self.dispatcher.upd_sender.subscribe();
+        Context::new(&self.inner_client, self.dispatcher.upd_sender.clone())
+            .with_slow_mode(&self.dispatcher.slow_mode)
+            .with_debouncer(&self.dispatcher.debouncer)
+            .with_cache(self.dispatcher.cache.as_ref())
+            .with_outbox(self.dispatcher.outbox.as_ref())
+            .with_history(self.dispatcher.history.as_ref())
+            .with_idempotency(self.dispatcher.idempotency.as_ref())
+    }
+
+    /// Spawns a background task tied to the client lifecycle.
+    ///
+    /// The task is restarted with backoff when it panics or exits, and is aborted
+    /// when the client shuts down after a `Ctrl + C` signal.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// client.spawn_supervised("poller", || async {
+    ///     loop {
+    ///         // Poll something...
+    ///     }
+    /// });
+    /// # }
+    /// ```
+    pub fn spawn_supervised<T, F>(&self, name: &str, task: T)
+    where
+        T: Fn() -> F + Send + Sync + 'static,
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.supervisor.spawn(name, task);
+    }
+
+    /// Returns the background task supervisor.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// let supervisor = client.supervisor();
+    /// # }
+    /// ```
+    pub fn supervisor(&self) -> &Supervisor {
+        &self.supervisor
+    }
+
+    /// Sets the log level of the target and its children, at runtime.
+    ///
+    /// Only applies to the logger installed with [`logging::init`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// use ferogram::logging;
+    ///
+    /// client.set_log_level(logging::FILTERS, log::LevelFilter::Off);
+    /// # }
+    /// ```
+    pub fn set_log_level(&self, target: &str, level: log::LevelFilter) {
+        logging::set_level(target, level);
+    }
+
+    /// Returns a handle to feed synthetic updates to the dispatcher.
+    ///
+    /// The handle keeps the dispatcher as it was configured when it was created.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// # let update = unimplemented!();
+    /// let handle = client.dispatcher_handle();
+    /// handle.feed(update).await?;
+    /// # }
+    /// ```
+    pub fn dispatcher_handle(&self) -> DispatcherHandle {
+        DispatcherHandle::new(&self.inner_client, &self.dispatcher)
+    }
+
+    /// Gets the view counts of the messages in the chat, see [`Context::get_message_views`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// # let chat: grammers_client::types::Chat = unimplemented!();
+    /// let views = client.get_message_views(chat, &[1, 2, 3]).await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the view counts could not be fetched.
+    pub async fn get_message_views<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        message_ids: &[i32],
+    ) -> std::result::Result<Vec<Option<i32>>, InvocationError> {
+        self.new_ctx().get_message_views(chat, message_ids).await
+    }
+
+    /// Marks the messages of the channel as viewed, see [`Context::increment_views`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// # let chat: grammers_client::types::Chat = unimplemented!();
+    /// client.increment_views(chat, &[1, 2, 3]).await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the messages could not be marked as viewed.
+    pub async fn increment_views<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        message_ids: &[i32],
+    ) -> std::result::Result<Vec<Option<i32>>, InvocationError> {
+        self.new_ctx().increment_views(chat, message_ids).await
+    }
+
+    /// Moves the chat to the archive.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// # let chat: grammers_client::types::Chat = unimplemented!();
+    /// client.archive(chat).await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the chat could not be archived.
+    pub async fn archive<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> std::result::Result<(), InvocationError> {
+        self.new_ctx().archive(chat).await
+    }
+
+    /// Moves the chat out of the archive.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// # let chat: grammers_client::types::Chat = unimplemented!();
+    /// client.unarchive(chat).await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the chat could not be unarchived.
+    pub async fn unarchive<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> std::result::Result<(), InvocationError> {
+        self.new_ctx().unarchive(chat).await
+    }
+
+    /// Gets the chat folders of the account, see [`Context::get_folders`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// for (id, title) in client.get_folders().await? {
+    ///     println!("{}: {}", id, title);
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the folders could not be fetched.
+    pub async fn get_folders(&self) -> std::result::Result<Vec<(i32, String)>, InvocationError> {
+        self.new_ctx().get_folders().await
+    }
+
+    /// Adds the chat to the chat folder.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// # let chat: grammers_client::types::Chat = unimplemented!();
+    /// let (folder_id, _) = client.get_folders().await?[0];
+    /// client.add_chat_to_folder(chat, folder_id).await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the folder was not found or could not be updated.
+    pub async fn add_chat_to_folder<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        folder_id: i32,
+    ) -> std::result::Result<(), crate::Error> {
+        self.new_ctx().add_chat_to_folder(chat, folder_id).await
+    }
+
+    /// Clears the drafts of every chat.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// client.clear_drafts().await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the drafts could not be cleared.
+    pub async fn clear_drafts(&self) -> std::result::Result<(), InvocationError> {
+        self.new_ctx().clear_drafts().await
+    }
 
-        Context::new(&self.inner_client, upd_receiver)
+    /// Invokes many requests at once, returning the result of each one in order.
+    ///
+    /// The requests are sent in chunks of 100 concurrent requests, which
+    /// the sender packs into containers, so it is faster than invoking one by one.
+    /// A failed request doesn't stop the others.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// # let users: Vec<grammers_client::types::User> = unimplemented!();
+    /// use grammers_client::grammers_tl_types as tl;
+    ///
+    /// let requests = users
+    ///     .iter()
+    ///     .map(|user| tl::functions::users::GetFullUser {
+    ///         id: user.pack().to_input_user_lossy(),
+    ///     })
+    ///     .collect::<Vec<_>>();
+    /// for result in client.invoke_batched(&requests).await {
+    ///     println!("{:?}", result);
+    /// }
+    /// # }
+    /// ```
+    pub async fn invoke_batched<R: tl::RemoteCall>(
+        &self,
+        requests: &[R],
+    ) -> Vec<std::result::Result<R::Return, InvocationError>> {
+        invoke_batched(&self.inner_client, requests).await
+    }
+
+    /// Sets the source of the updates sent to the dispatcher.
+    ///
+    /// By default, the updates come from the inner grammers' `Client`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// let (sender, receiver) = tokio::sync::mpsc::channel(100);
+    /// let client = client.update_source(receiver);
+    /// # }
+    /// ```
+    pub fn update_source<S: UpdateSource>(mut self, source: S) -> Self {
+        self.update_source = Some(Box::new(source));
+        self
     }
 
     /// Listen to Telegram's updates and send them to the dispatcher's routers.
     ///
+    /// The updates are pulled from the [`UpdateSource`], if set.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -239,69 +559,136 @@ impl Client {
     /// # }
     /// ```
     pub async fn run(self) -> Result<()> {
+        self.spawn_autosave(&self.inner_client);
+
         let handle = self.inner_client;
         let dispatcher = self.dispatcher;
         let err_handler = self.err_handler;
         let ready_handler = self.ready_handler;
+        let update_formatter = self.update_formatter;
+        let mut update_source = self
+            .update_source
+            .unwrap_or_else(|| Box::new(handle.clone()));
 
-        if self.set_bot_commands {
+        if self.set_bot_commands || self.clear_bot_commands {
             let mut commands = Vec::new();
 
-            let command_filters = dispatcher.get_commands();
-            for command_filter in command_filters.into_iter() {
-                let patterns = command_filter
-                    .command
-                    .split("|")
-                    .filter(|pattern| pattern.len() > 1)
-                    .collect::<Vec<_>>();
-                let description = command_filter.description;
-
-                for pattern in patterns.iter() {
+            if self.set_bot_commands {
+                for (command, description) in dispatcher.bot_commands() {
                     commands.push(tl::enums::BotCommand::Command(tl::types::BotCommand {
-                        command: pattern.to_string(),
-                        description: description.to_string(),
+                        command,
+                        description,
                     }));
                 }
             }
 
-            handle
-                .invoke(&tl::functions::bots::SetBotCommands {
-                    scope: tl::enums::BotCommandScope::Default,
-                    lang_code: "en".to_string(),
-                    commands,
-                })
-                .await?;
+            // Only the default scope holds the commands, the others are diffed
+            // against an empty list, so only the stale ones are reset.
+            for scope in BOT_COMMANDS_SCOPES {
+                for lang_code in ["", BOT_COMMANDS_LANG] {
+                    let commands = if matches!(scope, tl::enums::BotCommandScope::Default)
+                        && lang_code == BOT_COMMANDS_LANG
+                    {
+                        commands.clone()
+                    } else if self.clear_bot_commands {
+                        Vec::new()
+                    } else {
+                        continue;
+                    };
+
+                    sync_bot_commands(&handle, scope.clone(), lang_code, commands).await?;
+                }
+            }
         }
 
         let client = handle.clone();
 
+        if let Some(queue) = dispatcher.job_queue.clone() {
+            let client = handle.clone();
+            let dispatcher = dispatcher.clone();
+
+            self.supervisor.spawn("job-queue", move || {
+                queue.clone().run(client.clone(), dispatcher.clone())
+            });
+        }
+
+        if let Some(outbox) = dispatcher.outbox.clone() {
+            let client = handle.clone();
+
+            self.supervisor
+                .spawn("outbox", move || outbox.clone().run(client.clone()));
+        }
+
+        let mut event_receiver = dispatcher.event_bus.subscribe();
+        let event_client = handle.clone();
+        let event_dispatcher = dispatcher.clone();
+        tokio::task::spawn(async move {
+            loop {
+                match event_receiver.recv().await {
+                    Ok(event) => {
+                        let client = event_client.clone();
+                        let mut dp = event_dispatcher.clone();
+
+                        tokio::task::spawn(handler::trace_failure(async move {
+                            if let Err(e) = dp.handle_event(&client, &event).await {
+                                log::error!(
+                                    target: logging::DISPATCHER,
+                                    "Error handling event{}: {:?}",
+                                    failed_in(),
+                                    e
+                                );
+                            }
+                        }));
+                    }
+                    Err(RecvError::Lagged(count)) => {
+                        log::warn!(target: logging::DISPATCHER, "Skipped {} events", count);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
         tokio::task::spawn(async move {
             if let Some(mut handler) = ready_handler {
                 let mut injector = di::Injector::default();
                 injector.insert(handle.clone());
+                injector.insert(dispatcher.event_bus.clone());
 
                 handler.handle(&mut injector).await.unwrap();
             }
 
             loop {
-                match handle.next_update().await {
-                    Ok(update) => {
+                match update_source.next_update().await {
+                    Ok(Some(update)) => {
                         let client = handle.clone();
                         let mut dp = dispatcher.clone();
                         let err_handler = err_handler.clone();
-
-                        tokio::task::spawn(async move {
-                            if let Err(e) = dp.handle_update(&client, &update).await {
-                                if let Some(err_handler) = err_handler.as_ref() {
-                                    err_handler.run(client, update, e).await;
-                                } else {
-                                    log::error!("Error handling update: {:?}", e);
+                        let update_formatter = update_formatter.clone();
+
+                        let correlation_id = CorrelationId::new();
+
+                        tokio::task::spawn(correlation_id.scope(handler::trace_failure(
+                            async move {
+                                if let Err(e) = dp.handle_update(&client, &update).await {
+                                    if let Some(err_handler) = err_handler.as_ref() {
+                                        err_handler.run(client, update, e).await;
+                                    } else {
+                                        log::error!(
+                                            target: logging::DISPATCHER,
+                                            "Error handling update {}{} ({}): {:?}",
+                                            correlation_id,
+                                            failed_in(),
+                                            update_formatter(&update),
+                                            e
+                                        );
+                                    }
                                 }
-                            }
-                        });
+                            },
+                        )));
                     }
+                    Ok(None) => break,
                     Err(e) => {
-                        log::error!("Error getting updates: {:?}", e);
+                        log::error!(target: logging::DISPATCHER, "Error getting updates: {:?}", e);
                     }
                 }
             }
@@ -317,10 +704,12 @@ impl Client {
                 handler.handle(&mut injector).await.unwrap();
             }
 
-            let session_file = self.session_file.as_deref().unwrap_or("./ferogram.session");
-            client.session().save_to_file(session_file)?;
+            self.supervisor.shutdown();
         }
 
+        let session_file = self.session_file.as_deref().unwrap_or("./ferogram.session");
+        client.session().save_to_file(session_file)?;
+
         Ok(())
     }
 
@@ -334,7 +723,8 @@ impl Client {
     /// # }
     /// ```
     pub async fn keep_alive(self) -> Result<()> {
-        let handle = self.inner_client;
+        let handle = self.inner_client.clone();
+        self.spawn_autosave(&handle);
 
         tokio::task::spawn(async move {
             loop {
@@ -344,10 +734,167 @@ impl Client {
 
         if self.wait_for_ctrl_c {
             tokio::signal::ctrl_c().await?;
+
+            self.supervisor.shutdown();
         }
 
+        let session_file = self.session_file.as_deref().unwrap_or("./ferogram.session");
+        self.inner_client.session().save_to_file(session_file)?;
+
         Ok(())
     }
+
+    /// Spawns the task which saves the session periodically, if enabled.
+    fn spawn_autosave(&self, client: &grammers_client::Client) {
+        if let Some(interval) = self.autosave_interval {
+            let client = client.clone();
+            let session_file = self
+                .session_file
+                .clone()
+                .unwrap_or("./ferogram.session".to_string());
+
+            self.supervisor.spawn("session-autosave", move || {
+                let client = client.clone();
+                let session_file = session_file.clone();
+
+                async move {
+                    loop {
+                        tokio::time::sleep(interval).await;
+
+                        if let Err(e) = client.session().save_to_file(&session_file) {
+                            log::error!("Failed to save the session: {:?}", e);
+                        }
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// Invokes the requests in chunks of [`BATCH_SIZE`] concurrent requests.
+pub(crate) async fn invoke_batched<R: tl::RemoteCall>(
+    client: &grammers_client::Client,
+    requests: &[R],
+) -> Vec<std::result::Result<R::Return, InvocationError>> {
+    let mut results = Vec::with_capacity(requests.len());
+
+    for chunk in requests.chunks(BATCH_SIZE) {
+        results.extend(join_all(chunk.iter().map(|request| client.invoke(request))).await);
+    }
+
+    results
+}
+
+/// Checks if the file can be written, without leaving it behind if it doesn't exist.
+fn check_writable(path: &Path) -> std::io::Result<()> {
+    if path.exists() {
+        std::fs::OpenOptions::new().write(true).open(path)?;
+    } else {
+        // An empty file fails to load as a session, so the probe is removed.
+        std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)?;
+        std::fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+/// Returns ` in <handler>` with the handler which failed in the task, if any.
+fn failed_in() -> String {
+    handler::failed_handler()
+        .map(|name| format!(" in {}", name))
+        .unwrap_or_default()
+}
+
+/// The language of the bot commands set by the client.
+const BOT_COMMANDS_LANG: &str = "en";
+
+/// The scopes of the bot commands synced by the client, the default one holds the
+/// commands of the dispatcher.
+const BOT_COMMANDS_SCOPES: [tl::enums::BotCommandScope; 4] = [
+    tl::enums::BotCommandScope::Default,
+    tl::enums::BotCommandScope::Users,
+    tl::enums::BotCommandScope::Chats,
+    tl::enums::BotCommandScope::ChatAdmins,
+];
+
+/// Replaces the bot commands of the scope, only if they changed.
+///
+/// Resets the scope if there are no commands, so the stale ones are removed.
+async fn sync_bot_commands(
+    client: &grammers_client::Client,
+    scope: tl::enums::BotCommandScope,
+    lang_code: &str,
+    commands: Vec<tl::enums::BotCommand>,
+) -> Result<()> {
+    let current = client
+        .invoke(&tl::functions::bots::GetBotCommands {
+            scope: scope.clone(),
+            lang_code: lang_code.to_string(),
+        })
+        .await?;
+
+    if current == commands {
+        log::debug!("Bot commands of {:?} are up to date", scope);
+    } else if commands.is_empty() {
+        client
+            .invoke(&tl::functions::bots::ResetBotCommands {
+                scope,
+                lang_code: lang_code.to_string(),
+            })
+            .await?;
+    } else {
+        client
+            .invoke(&tl::functions::bots::SetBotCommands {
+                scope,
+                lang_code: lang_code.to_string(),
+                commands,
+            })
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// The datacenter of new sessions.
+const DEFAULT_DC: i32 = 2;
+
+/// Returns the address of the datacenter, if known.
+fn dc_address(dc: i32, test: bool, ipv6: bool) -> Option<SocketAddr> {
+    let ip = match (test, ipv6) {
+        (false, false) => IpAddr::V4(match dc {
+            1 => Ipv4Addr::new(149, 154, 175, 53),
+            2 => Ipv4Addr::new(149, 154, 167, 51),
+            3 => Ipv4Addr::new(149, 154, 175, 100),
+            4 => Ipv4Addr::new(149, 154, 167, 91),
+            5 => Ipv4Addr::new(91, 108, 56, 130),
+            _ => return None,
+        }),
+        (false, true) => IpAddr::V6(match dc {
+            1 => Ipv6Addr::new(0x2001, 0xb28, 0xf23d, 0xf001, 0, 0, 0, 0xa),
+            2 => Ipv6Addr::new(0x2001, 0x67c, 0x4e8, 0xf002, 0, 0, 0, 0xa),
+            3 => Ipv6Addr::new(0x2001, 0xb28, 0xf23d, 0xf003, 0, 0, 0, 0xa),
+            4 => Ipv6Addr::new(0x2001, 0x67c, 0x4e8, 0xf004, 0, 0, 0, 0xa),
+            5 => Ipv6Addr::new(0x2001, 0xb28, 0xf23f, 0xf005, 0, 0, 0, 0xa),
+            _ => return None,
+        }),
+        (true, false) => IpAddr::V4(match dc {
+            1 => Ipv4Addr::new(149, 154, 175, 10),
+            2 => Ipv4Addr::new(149, 154, 167, 40),
+            3 => Ipv4Addr::new(149, 154, 175, 117),
+            _ => return None,
+        }),
+        (true, true) => IpAddr::V6(match dc {
+            1 => Ipv6Addr::new(0x2001, 0xb28, 0xf23d, 0xf001, 0, 0, 0, 0xe),
+            2 => Ipv6Addr::new(0x2001, 0x67c, 0x4e8, 0xf002, 0, 0, 0, 0xe),
+            3 => Ipv6Addr::new(0x2001, 0xb28, 0xf23d, 0xf003, 0, 0, 0, 0xe),
+            _ => return None,
+        }),
+    };
+
+    Some(SocketAddr::new(ip, 443))
 }
 
 /// `Client` instance builder.
@@ -362,13 +909,29 @@ pub struct ClientBuilder {
     api_hash: String,
     /// The session file path.
     session_file: Option<String>,
+    /// The interval between the session saves, if any.
+    autosave_interval: Option<Duration>,
     /// The initial parameters.
     init_params: InitParams,
+    /// Whether is to connect to the IPv6 address of the datacenter.
+    use_ipv6: bool,
+    /// The test datacenter to connect to, if any.
+    test_dc: Option<i32>,
+    /// The reconnection policy, if set.
+    reconnect: Option<Reconnect>,
+    /// The reconnect hook.
+    reconnect_handler: Option<ReconnectHandler>,
 
     /// Whether is to update Telegram's bot commands.
     set_bot_commands: bool,
+    /// Whether is to clear Telegram's bot commands before updating them.
+    clear_bot_commands: bool,
     /// Whether is to wait for a `Ctrl + C` signal to close the connection and exit the app.
     wait_for_ctrl_c: bool,
+    /// Whether is to validate the configuration and print the identity of the client.
+    validate: bool,
+    /// The formatter of the updates in the error logs.
+    update_formatter: Option<UpdateFormatter>,
 
     /// The global error handler.
     pub(crate) err_handler: Option<Box<dyn ErrorHandler>>,
@@ -425,9 +988,31 @@ impl ClientBuilder {
     /// let client = client.build().await?;
     /// # }
     /// ```
-    pub async fn build(self) -> Result<Client> {
+    pub async fn build(mut self) -> Result<Client> {
+        let mut reconnect_policy = None;
+        if self.reconnect.is_some() || self.reconnect_handler.is_some() {
+            let policy = Arc::new(ReconnectPolicy {
+                reconnect: self.reconnect.unwrap_or_default(),
+                handler: self.reconnect_handler.take(),
+            });
+            self.init_params.reconnection_policy = PolicySlot::attach(&policy);
+            reconnect_policy = Some(policy);
+        }
+
+        if self.init_params.server_addr.is_none() && (self.use_ipv6 || self.test_dc.is_some()) {
+            let dc = self.test_dc.unwrap_or(DEFAULT_DC);
+            let address = dc_address(dc, self.test_dc.is_some(), self.use_ipv6)
+                .ok_or_else(|| format!("Unknown datacenter: {}", dc))?;
+
+            self.init_params.server_addr = Some(ServerAddr::Tcp { address });
+        }
+
         let session_file = self.session_file.as_deref().unwrap_or("./ferogram.session");
 
+        if self.validate {
+            self.validate_config(session_file)?;
+        }
+
         let inner_client = grammers_client::Client::connect(Config {
             session: Session::load_file_or_create(session_file)?,
             api_id: self.api_id,
@@ -440,16 +1025,25 @@ impl ClientBuilder {
             dispatcher: Dispatcher::default(),
             client_type: self.client_type,
             inner_client,
+            update_source: None,
 
             session_file: Some(session_file.to_string()),
+            autosave_interval: self.autosave_interval,
 
             is_connected: false,
             set_bot_commands: self.set_bot_commands,
+            clear_bot_commands: self.clear_bot_commands,
             wait_for_ctrl_c: self.wait_for_ctrl_c,
+            print_identity: self.validate,
+            supervisor: Supervisor::default(),
+            update_formatter: self
+                .update_formatter
+                .unwrap_or_else(UpdateSummary::formatter),
 
             err_handler: self.err_handler,
             exit_handler: self.exit_handler,
             ready_handler: self.ready_handler,
+            _reconnect_policy: reconnect_policy,
         })
     }
 
@@ -516,6 +1110,24 @@ impl ClientBuilder {
         self
     }
 
+    /// Saves the session periodically, so the changes are not lost on a crash.
+    ///
+    /// By default, the session is only saved after the login and when the client stops.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// use std::time::Duration;
+    ///
+    /// let client = client.autosave_session(Duration::from_secs(300));
+    /// # }
+    /// ```
+    pub fn autosave_session(mut self, interval: Duration) -> Self {
+        self.autosave_interval = Some(interval);
+        self
+    }
+
     /// User's device model.
     ///
     /// Telegram uses to know your device in devices settings.
@@ -613,6 +1225,40 @@ impl ClientBuilder {
         self
     }
 
+    /// Whether to connect to the IPv6 address of the datacenter, for IPv6-only networks.
+    ///
+    /// Ignored if [`ClientBuilder::server_address`] is set.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// let client = client.use_ipv6(true);
+    /// # }
+    /// ```
+    pub fn use_ipv6(mut self, value: bool) -> Self {
+        self.use_ipv6 = value;
+        self
+    }
+
+    /// Connects to the test datacenter `dc`, from 1 to 3.
+    ///
+    /// The test servers have their own accounts, so use a separate session file.
+    /// Ignored if [`ClientBuilder::server_address`] is set, and the build fails for an
+    /// unknown datacenter.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// let client = client.test_dc(2).session_file("test.session");
+    /// # }
+    /// ```
+    pub fn test_dc(mut self, dc: i32) -> Self {
+        self.test_dc = Some(dc);
+        self
+    }
+
     /// The threshold below which the library should automatically sleep on flood-wait and slow
     /// mode wait errors (inclusive). For instance, if an
     /// `RpcError { name: "FLOOD_WAIT", value: Some(17) }` (flood, must wait 17 seconds) occurs
@@ -691,11 +1337,136 @@ impl ClientBuilder {
     ///
     /// Only commands that has more than `1` char will be registered.
     /// Ex: `start`, `help`...
+    ///
+    /// Uses the description set with [`Command::description`], or the command name if empty.
+    ///
+    /// [`Command::description`]: crate::filter::Command::description
     pub fn set_bot_commands(mut self) -> Self {
         self.set_bot_commands = true;
         self
     }
 
+    /// Clears the Telegram-side bot's command lists of the default, users, chats and
+    /// chat admins scopes, except the one updated by [`ClientBuilder::set_bot_commands`].
+    ///
+    /// Removes the stale commands left by old versions of the bot or set via BotFather,
+    /// also the ones of the default language. Each list is fetched first and only reset
+    /// if it holds commands.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// let client = client.clear_bot_commands().set_bot_commands();
+    /// # }
+    /// ```
+    pub fn clear_bot_commands(mut self) -> Self {
+        self.clear_bot_commands = true;
+        self
+    }
+
+    /// Validates the configuration before connecting, and prints the identity
+    /// of the client once connected.
+    ///
+    /// Checks the formats of the API ID, API hash, bot token and phone number, and
+    /// whether the session file is writable, failing with an actionable message
+    /// instead of in the middle of the login.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// let client = client.validate();
+    /// # }
+    /// ```
+    pub fn validate(mut self) -> Self {
+        self.validate = true;
+        self
+    }
+
+    /// Checks the configuration, returning the first problem found.
+    fn validate_config(&self, session_file: &str) -> Result<()> {
+        if self.api_id <= 0 {
+            return Err(
+                "API ID must be a positive number, get yours in https://my.telegram.org/apps"
+                    .into(),
+            );
+        }
+
+        if self.api_hash.len() != 32 || !self.api_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err("API hash must have 32 hexadecimal characters, get yours in https://my.telegram.org/apps".into());
+        }
+
+        match self.client_type {
+            ClientType::Bot(ref token) => {
+                let re = regex::Regex::new(r"^\d+:[\w-]{35}$").unwrap();
+                if !re.is_match(token) {
+                    return Err(
+                        "Bot token must look like `123456:ABC-DEF...`, get yours from @BotFather"
+                            .into(),
+                    );
+                }
+            }
+            ClientType::User(ref phone_number) => {
+                let re = regex::Regex::new(r"^\+?\d{5,15}$").unwrap();
+                if !re.is_match(&phone_number.replace([' ', '-'], "")) {
+                    return Err(
+                        "Phone number must be in the international format, like `+5511999999999`"
+                            .into(),
+                    );
+                }
+            }
+        }
+
+        if let Err(e) = check_writable(Path::new(session_file)) {
+            return Err(format!("Session file {:?} is not writable: {}", session_file, e).into());
+        }
+
+        Ok(())
+    }
+
+    /// Sets the reconnection policy, one of the built-in [`Reconnect`] policies.
+    ///
+    /// Executed when the client loses the connection or the Telegram server closes it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// use ferogram::Reconnect;
+    ///
+    /// let client = client.reconnect(Reconnect::forever());
+    /// # }
+    /// ```
+    pub fn reconnect(mut self, reconnect: Reconnect) -> Self {
+        self.reconnect = Some(reconnect);
+        self
+    }
+
+    /// Sets the reconnect handler.
+    ///
+    /// Executed before each attempt to reconnect, and when the client gives up.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// let client = client.on_reconnect(|event| {
+    ///     match event.delay {
+    ///         Some(delay) => println!("Reconnecting in {:?}", delay),
+    ///         None => println!("Giving up after {} attempts", event.attempt),
+    ///     }
+    /// });
+    /// # }
+    /// ```
+    pub fn on_reconnect<F: Fn(ReconnectEvent) + Send + Sync + 'static>(
+        mut self,
+        handler: F,
+    ) -> Self {
+        self.reconnect_handler = Some(Arc::new(handler));
+        self
+    }
+
     /// Sets the reconnection policy.
     ///
     /// Executed when the client loses the connection or the Telegram server closes it.
@@ -725,7 +1496,35 @@ impl ClientBuilder {
     /// # }
     /// ```
     pub fn reconnection_policy<P: ReconnectionPolicy>(mut self, policy: &'static P) -> Self {
-        self.init_params.reconnection_policy = policy;
+        self.reconnect = Some(Reconnect::custom(policy));
+        self
+    }
+
+    /// Sets the formatter of the updates in the error logs.
+    ///
+    /// By default, the updates are logged as an [`UpdateSummary`], including their
+    /// trimmed text, next to the name of the failing handler, see [`crate::Handler::name`].
+    /// Use it to redact the sensitive data.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: ferogram::Client) {
+    /// use ferogram::UpdateSummary;
+    ///
+    /// let client = client.update_formatter(|update| {
+    ///     let mut summary = UpdateSummary::new(update);
+    ///     summary.text = None;
+    ///
+    ///     summary.to_string()
+    /// });
+    /// # }
+    /// ```
+    pub fn update_formatter<F: Fn(&Update) -> String + Send + Sync + 'static>(
+        mut self,
+        formatter: F,
+    ) -> Self {
+        self.update_formatter = Some(Arc::new(formatter));
         self
     }
 
@@ -848,4 +1647,20 @@ mod tests {
 
         assert!(client.is_ok());
     }
+
+    #[test]
+    fn test_validate_new_session() {
+        let path =
+            std::env::temp_dir().join(format!("ferogram-{}.session", crate::utils::random_u64()));
+        let session_file = path.to_str().unwrap();
+
+        let builder = Client::bot(format!("123456:{}", "A".repeat(35)))
+            .api_id(1)
+            .api_hash("0".repeat(32));
+        assert!(builder.validate_config(session_file).is_ok());
+        assert!(!path.exists());
+
+        assert!(Session::load_file_or_create(session_file).is_ok());
+        std::fs::remove_file(&path).ok();
+    }
 }
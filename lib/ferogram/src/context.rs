@@ -8,10 +8,18 @@
 
 //! Context module.
 
-use std::{io, path::Path, pin::pin, sync::Arc, time::Duration};
+use std::{
+    future::Future,
+    io,
+    path::Path,
+    pin::pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use futures_util::future::{select, Either};
 use grammers_client::{
+    button, grammers_tl_types as tl, reply_markup,
     types::{
         media::Uploaded, ActionSender, CallbackQuery, Chat, InlineQuery, InlineSend, InputMessage,
         Media, Message, PackedChat, Photo, User,
@@ -20,10 +28,24 @@ use grammers_client::{
 };
 use tokio::{
     io::AsyncRead,
-    sync::{broadcast::Receiver, Mutex},
+    sync::broadcast::{error::RecvError, Receiver, Sender},
+};
+
+use crate::{
+    export::{self, ExportOptions},
+    history::{CachedMessage, MessageHistory},
+    idempotency::Idempotency,
+    outbox::Outbox,
+    slow_mode::SlowMode,
+    upload::{self, UploadOptions},
+    utils::{bytes_to_string, random_u64, Debouncer, ProgressReporter},
+    Cache, CancellationToken, CorrelationId, Entity, Filter, MediaBuilder, MessageBuilder,
+    PollBuilder,
 };
 
-use crate::{utils::bytes_to_string, Filter};
+/// How long [`Context::send_paginated`] answers the navigation buttons after the
+/// last page was requested.
+pub const PAGINATION_TIMEOUT: Duration = Duration::from_secs(300);
 
 /// The context of an update.
 #[derive(Debug)]
@@ -32,17 +54,38 @@ pub struct Context {
     client: grammers_client::Client,
     /// The update itself.
     update: Option<Update>,
-    /// The update receiver.
-    upd_receiver: Arc<Mutex<Receiver<Update>>>,
+    /// The update sender, subscribed only while waiting for updates.
+    upd_sender: Sender<Update>,
+    /// The slow mode send queue.
+    slow_mode: SlowMode,
+    /// The debouncer of the edits.
+    debouncer: Debouncer,
+    /// The cache of the peers.
+    cache: Option<Cache>,
+    /// The outbox of the reliable messages.
+    outbox: Option<Outbox>,
+    /// The cache of the last messages.
+    history: Option<MessageHistory>,
+    /// The record of the messages sent once.
+    idempotency: Option<Idempotency>,
+    /// The correlation ID of the update.
+    correlation_id: Option<CorrelationId>,
 }
 
 impl Context {
     /// Creates a new context.
-    pub fn new(client: &grammers_client::Client, upd_receiver: Receiver<Update>) -> Self {
+    pub fn new(client: &grammers_client::Client, upd_sender: Sender<Update>) -> Self {
         Self {
             client: client.clone(),
             update: None,
-            upd_receiver: Arc::new(Mutex::new(upd_receiver)),
+            upd_sender,
+            slow_mode: SlowMode::default(),
+            debouncer: Debouncer::default(),
+            cache: None,
+            outbox: None,
+            history: None,
+            idempotency: None,
+            correlation_id: None,
         }
     }
 
@@ -50,12 +93,19 @@ impl Context {
     pub fn with(
         client: &grammers_client::Client,
         update: &Update,
-        upd_receiver: Receiver<Update>,
+        upd_sender: Sender<Update>,
     ) -> Self {
         Self {
             client: client.clone(),
             update: Some(update.clone()),
-            upd_receiver: Arc::new(Mutex::new(upd_receiver)),
+            upd_sender,
+            slow_mode: SlowMode::default(),
+            debouncer: Debouncer::default(),
+            cache: None,
+            outbox: None,
+            history: None,
+            idempotency: None,
+            correlation_id: None,
         }
     }
 
@@ -71,18 +121,102 @@ impl Context {
     /// # }
     /// ```
     pub fn clone_with(&self, update: &Update) -> Self {
-        let upd_receiver = self
-            .upd_receiver
-            .try_lock()
-            .expect("Failed to lock receiver");
-
         Self {
             client: self.client.clone(),
             update: Some(update.clone()),
-            upd_receiver: Arc::new(Mutex::new(upd_receiver.resubscribe())),
+            upd_sender: self.upd_sender.clone(),
+            slow_mode: self.slow_mode.clone(),
+            debouncer: self.debouncer.clone(),
+            cache: self.cache.clone(),
+            outbox: self.outbox.clone(),
+            history: self.history.clone(),
+            idempotency: self.idempotency.clone(),
+            correlation_id: self.correlation_id,
         }
     }
 
+    /// Shares the slow mode send queue with other contexts.
+    pub(crate) fn with_slow_mode(mut self, slow_mode: &SlowMode) -> Self {
+        self.slow_mode = slow_mode.clone();
+        self
+    }
+
+    /// Shares the debouncer of the edits with other contexts.
+    pub(crate) fn with_debouncer(mut self, debouncer: &Debouncer) -> Self {
+        self.debouncer = debouncer.clone();
+        self
+    }
+
+    /// Shares the cache of the peers with other contexts.
+    pub(crate) fn with_cache(mut self, cache: Option<&Cache>) -> Self {
+        self.cache = cache.cloned();
+        self
+    }
+
+    /// Returns the cache of the peers seen by the dispatcher.
+    ///
+    /// Returns `None` if no cache was attached with [`crate::Dispatcher::peer_cache`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx: ferogram::Context = unimplemented!();
+    /// if let Some(cache) = ctx.cache() {
+    ///     let chat = cache.get_chat(ctx.chat().unwrap().id());
+    /// }
+    /// # }
+    /// ```
+    pub fn cache(&self) -> Option<&Cache> {
+        self.cache.as_ref()
+    }
+
+    /// Shares the outbox of the reliable messages with other contexts.
+    pub(crate) fn with_outbox(mut self, outbox: Option<&Outbox>) -> Self {
+        self.outbox = outbox.cloned();
+        self
+    }
+
+    /// Shares the cache of the last messages with other contexts.
+    pub(crate) fn with_history(mut self, history: Option<&MessageHistory>) -> Self {
+        self.history = history.cloned();
+        self
+    }
+
+    /// Shares the record of the messages sent once with other contexts.
+    pub(crate) fn with_idempotency(mut self, idempotency: Option<&Idempotency>) -> Self {
+        self.idempotency = idempotency.cloned();
+        self
+    }
+
+    /// Sets the correlation ID of the update.
+    pub(crate) fn with_correlation_id(mut self, correlation_id: Option<CorrelationId>) -> Self {
+        self.correlation_id = correlation_id;
+        self
+    }
+
+    /// Returns the correlation ID of the update being handled.
+    ///
+    /// The ID is kept by the contexts cloned from this one, and shown in the logs
+    /// of the dispatcher and of the messages of [`Context::send_reliable`].
+    ///
+    /// Returns `None` if the context was not created by the dispatcher for an
+    /// update, like the contexts of the jobs.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx: ferogram::Context = unimplemented!();
+    /// if let Some(id) = ctx.correlation_id() {
+    ///     log::info!("[{}] Starting the checkout", id);
+    /// }
+    /// # }
+    /// ```
+    pub fn correlation_id(&self) -> Option<CorrelationId> {
+        self.correlation_id
+    }
+
     /// Returns the client.
     ///
     /// # Example
@@ -152,6 +286,58 @@ impl Context {
         }
     }
 
+    /// Returns the text of the edited message before the edit.
+    ///
+    /// Returns `None` if the update is not an edited message, if the dispatcher has
+    /// no [`MessageHistory`] or if the message was not cached.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// if let Some(previous) = ctx.previous_text() {
+    ///     println!("Edited from {:?} to {:?}", previous, ctx.text().unwrap());
+    /// }
+    /// # }
+    /// ```
+    pub fn previous_text(&self) -> Option<String> {
+        match self.update.as_ref().expect("No update") {
+            Update::MessageEdited(message) => {
+                self.history
+                    .as_ref()?
+                    .get(message.chat().id(), message.id())?
+                    .previous_text
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the cached content of the deleted messages.
+    ///
+    /// Returns an empty list if the update is not a message deletion, if the
+    /// dispatcher has no [`MessageHistory`] or if the messages were not cached.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// for message in ctx.deleted_messages() {
+    ///     println!("Deleted from {}: {:?}", message.chat_id, message.text);
+    /// }
+    /// # }
+    /// ```
+    pub fn deleted_messages(&self) -> Vec<CachedMessage> {
+        match (
+            self.update.as_ref().expect("No update"),
+            self.history.as_ref(),
+        ) {
+            (Update::MessageDeleted(deletion), Some(history)) => history.deleted(deletion),
+            _ => Vec::new(),
+        }
+    }
+
     /// Returns the sender.
     ///
     /// Returns `None` if the update not has a sender.
@@ -223,6 +409,33 @@ impl Context {
         }
     }
 
+    /// Returns the entities of the message held by the update, with their texts resolved.
+    ///
+    /// Returns an empty list if the update is not/not from a message.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// let urls = ctx
+    ///     .entities()
+    ///     .await
+    ///     .iter()
+    ///     .filter_map(|entity| entity.url().map(ToString::to_string))
+    ///     .collect::<Vec<_>>();
+    /// # }
+    /// ```
+    pub async fn entities(&self) -> Vec<Entity> {
+        match self.message().await {
+            Some(message) => message
+                .fmt_entities()
+                .map(|entities| Entity::parse(message.text(), entities))
+                .unwrap_or_default(),
+            None => Vec::new(),
+        }
+    }
+
     /// Returns the callback query.
     ///
     /// Returns `None` if the update is not a callback query.
@@ -308,10 +521,106 @@ impl Context {
         }
     }
 
+    /// Edits the message held by the update, at most once per `interval`.
+    ///
+    /// The edits made during the interval replace each other and only the last one
+    /// is sent when the interval ends, so it is safe to call for every progress update.
+    /// The edits sharing the same `key` are coalesced.
+    ///
+    /// Errors are logged, since the edit may be sent after the method returns.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// use std::time::Duration;
+    ///
+    /// for progress in 0..=100 {
+    ///     ctx.edit_debounced("progress", format!("{}%", progress), Duration::from_secs(2))
+    ///         .await;
+    /// }
+    /// # }
+    /// ```
+    pub async fn edit_debounced<K: ToString, M: Into<InputMessage>>(
+        &self,
+        key: K,
+        message: M,
+        interval: Duration,
+    ) {
+        let message = message.into();
+
+        if let Some(query) = self.callback_query() {
+            self.debouncer.call(key, interval, async move {
+                if let Err(e) = query.answer().edit(message).await {
+                    log::error!("Failed to edit the message: {:?}", e);
+                }
+            });
+        } else if let Some(msg) = self.message().await {
+            self.debouncer.call(key, interval, async move {
+                if let Err(e) = msg.edit(message).await {
+                    log::error!("Failed to edit the message: {:?}", e);
+                }
+            });
+        } else {
+            panic!("Cannot edit this message")
+        }
+    }
+
+    /// Creates a progress reporter which edits the message held by the update.
+    ///
+    /// The edits are debounced with the other edits of the message.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// let reporter = ctx.progress_reporter().await.title("Downloading...");
+    /// reporter.report(512, 1024);
+    /// # }
+    /// ```
+    pub async fn progress_reporter(&self) -> ProgressReporter {
+        let (reporter, key) = if let Some(query) = self.callback_query() {
+            let key = format!("progress:{}:{}", query.chat().id(), query.raw.msg_id);
+            let reporter = ProgressReporter::new(move |text| {
+                let query = query.clone();
+
+                async move {
+                    if let Err(e) = query.answer().edit(text).await {
+                        log::error!("Failed to edit the progress: {:?}", e);
+                    }
+                }
+            });
+
+            (reporter, key)
+        } else if let Some(message) = self.message().await {
+            let key = format!("progress:{}:{}", message.chat().id(), message.id());
+            let reporter = ProgressReporter::new(move |text| {
+                let message = message.clone();
+
+                async move {
+                    if let Err(e) = message.edit(text).await {
+                        log::error!("Failed to edit the progress: {:?}", e);
+                    }
+                }
+            });
+
+            (reporter, key)
+        } else {
+            panic!("Cannot edit this message")
+        };
+
+        reporter.debouncer(&self.debouncer, key)
+    }
+
     /// Tries to send a message to the chat.
     ///
     /// If the chat is not found, it will panic.
     ///
+    /// If the chat is in slow mode, waits until the message can be sent instead of
+    /// returning a `SLOWMODE_WAIT` error.
+    ///
     /// Returns the sent message.
     ///
     /// # Example
@@ -329,6 +638,36 @@ impl Context {
     pub async fn send<M: Into<InputMessage>>(
         &self,
         message: M,
+    ) -> Result<Message, InvocationError> {
+        let chat_id = self.chat().expect("No chat").id();
+        let message = message.into();
+
+        self.slow_mode
+            .send(chat_id, || self.send_now(message.clone()))
+            .await
+    }
+
+    /// Tries to send a message to the chat, ignoring the slow mode.
+    ///
+    /// If the chat is not found, it will panic.
+    ///
+    /// Returns the sent message.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// ctx.send_now("Hello, world!").await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message could not be sent.
+    pub async fn send_now<M: Into<InputMessage>>(
+        &self,
+        message: M,
     ) -> Result<Message, InvocationError> {
         if let Some(msg) = self.message().await {
             msg.respond(message).await
@@ -348,6 +687,8 @@ impl Context {
 
     /// Tries to reply to the message held by the update.
     ///
+    /// If the chat is in slow mode, waits until the message can be sent.
+    ///
     /// Returns the replied message.
     ///
     /// # Example
@@ -367,12 +708,252 @@ impl Context {
         message: M,
     ) -> Result<Message, InvocationError> {
         if let Some(msg) = self.message().await {
-            msg.reply(message).await
+            let message = message.into();
+
+            self.slow_mode
+                .send(msg.chat().id(), || msg.reply(message.clone()))
+                .await
         } else {
             panic!("Cannot reply to this message")
         }
     }
 
+    /// Sends a message to the chat through the outbox, so it is not lost.
+    ///
+    /// The message is written to the storage before this returns, and is sent by
+    /// the outbox worker with retries, even after a restart. Use it for the
+    /// critical notifications, like payment confirmations.
+    ///
+    /// Returns the idempotency key of the message.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx: ferogram::Context = unimplemented!();
+    /// ctx.send_reliable("Your payment was confirmed.").await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message could not be written to the storage.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dispatcher has no outbox.
+    pub async fn send_reliable<T: ToString>(&self, text: T) -> crate::Result<i64> {
+        let chat = self.chat().expect("No chat");
+
+        self.outbox
+            .as_ref()
+            .expect("No outbox")
+            .push_with(chat.pack(), text, self.correlation_id)
+            .await
+    }
+
+    /// Sends a message to the chat, unless a message was already sent for the key.
+    ///
+    /// The IDs of the sent messages are recorded for the key, so running the
+    /// handler again, after a retry or a crash, does not send a duplicate.
+    ///
+    /// Returns the sent message, or `None` if the key was already sent.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx: ferogram::Context = unimplemented!();
+    /// # let order_id = 0;
+    /// ctx.send_once(&format!("order-shipped:{}", order_id), "Your order was shipped!")
+    ///     .await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the dispatcher has no idempotency record, the message
+    /// could not be sent, or the record could not be read or written.
+    pub async fn send_once<M: Into<InputMessage>>(
+        &self,
+        key: &str,
+        message: M,
+    ) -> crate::Result<Option<Message>> {
+        let idempotency = self
+            .idempotency
+            .as_ref()
+            .ok_or_else(crate::Error::missing_dependency::<Idempotency>)?;
+        let _guard = idempotency.lock(key).await;
+
+        if idempotency.sent(key).await?.is_some() {
+            return Ok(None);
+        }
+
+        let message = self.send(message).await?;
+        idempotency.record(key, &[message.id()]).await?;
+
+        Ok(Some(message))
+    }
+
+    /// Sends a list split in pages, with buttons to navigate between them.
+    ///
+    /// The pages are answered in the background until nobody navigates for
+    /// [`PAGINATION_TIMEOUT`], then the buttons are removed. Only the sender of the
+    /// update can navigate.
+    ///
+    /// See [`Context::send_paginated`] to fetch the items lazily.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx: ferogram::Context = unimplemented!();
+    /// let subscriptions = (1..=500).map(|i| format!("Channel {}", i)).collect();
+    /// ctx.send_paginated_list(subscriptions, 10).await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the first page could not be sent.
+    pub async fn send_paginated_list<T: ToString>(
+        &self,
+        items: Vec<T>,
+        page_size: usize,
+    ) -> Result<Message, InvocationError> {
+        let items = Arc::new(items.iter().map(T::to_string).collect::<Vec<_>>());
+
+        self.send_paginated(page_size, move |offset, limit| {
+            let items = items.clone();
+
+            async move { items.iter().skip(offset).take(limit).cloned().collect() }
+        })
+        .await
+    }
+
+    /// Sends a list split in pages, fetching each page when navigated to.
+    ///
+    /// `fetch` receives the offset and the limit of the items, like a database
+    /// query, and a page has a next one if the limit is filled.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx: ferogram::Context = unimplemented!();
+    /// # let db = unimplemented!();
+    /// ctx.send_paginated(10, move |offset, limit| {
+    ///     let db = db.clone();
+    ///
+    ///     async move { db.subscriptions(offset, limit).await }
+    /// })
+    /// .await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the first page could not be sent.
+    pub async fn send_paginated<F, Fut>(
+        &self,
+        page_size: usize,
+        fetch: F,
+    ) -> Result<Message, InvocationError>
+    where
+        F: Fn(usize, usize) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Vec<String>> + Send,
+    {
+        let page_size = page_size.max(1);
+        let token = random_u64() as u32;
+        let render = move |page: usize, mut items: Vec<String>| {
+            let has_next = items.len() > page_size;
+            items.truncate(page_size);
+
+            let text = if items.is_empty() {
+                String::from("Nothing here.")
+            } else {
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| format!("{}. {}", page * page_size + i + 1, item))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+
+            let mut buttons = Vec::new();
+            if page > 0 {
+                buttons.push(button::inline(
+                    "« Previous",
+                    format!("page:{}:{}", token, page - 1),
+                ));
+            }
+            if has_next {
+                buttons.push(button::inline(
+                    "Next »",
+                    format!("page:{}:{}", token, page + 1),
+                ));
+            }
+
+            let message = InputMessage::text(&text);
+            if buttons.is_empty() {
+                message
+            } else {
+                message.reply_markup(reply_markup::inline(vec![buttons]))
+            }
+        };
+
+        let sent = self.send(render(0, fetch(0, page_size + 1).await)).await?;
+
+        let ctx = self.clone();
+        let sender_id = self.sender().map(|sender| sender.id());
+        let prefix = format!("page:{}:", token);
+        let message = sent.clone();
+        tokio::spawn(async move {
+            let mut waiter = ctx.waiter(None);
+            let mut deadline = Instant::now() + PAGINATION_TIMEOUT;
+
+            while let Ok(update) = waiter
+                .next(deadline.saturating_duration_since(Instant::now()))
+                .await
+            {
+                let Update::CallbackQuery(query) = update else {
+                    continue;
+                };
+                let Some(page) = std::str::from_utf8(query.data())
+                    .ok()
+                    .and_then(|data| data.strip_prefix(&prefix))
+                    .and_then(|page| page.parse::<usize>().ok())
+                else {
+                    continue;
+                };
+
+                let result = if sender_id.is_some_and(|id| id != query.sender().id()) {
+                    query
+                        .answer()
+                        .alert("This list is not for you.")
+                        .send()
+                        .await
+                } else {
+                    // Only the navigation keeps the buttons alive.
+                    deadline = Instant::now() + PAGINATION_TIMEOUT;
+
+                    let items = fetch(page * page_size, page_size + 1).await;
+                    query.answer().edit(render(page, items)).await
+                };
+
+                if let Err(e) = result {
+                    log::error!("Error answering the page: {:?}", e);
+                }
+            }
+
+            if let Err(e) = message.edit(InputMessage::text(message.text())).await {
+                log::error!("Error removing the page buttons: {:?}", e);
+            }
+        });
+
+        Ok(sent)
+    }
+
     /// Tries to delete the message held by the update.
     ///
     /// If the message is from the client, it will be deleted.
@@ -447,29 +1028,78 @@ impl Context {
         }
     }
 
-    /// Tries to forward the message held by the update to a chat.
+    /// Returns the story that the message held by the update is replying to.
     ///
-    /// Returns the forwarded message.
+    /// [`Context::get_reply`] returns `None` for these replies.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # async fn example() {
     /// # let ctx = unimplemented!();
-    /// let chat = ctx.chat().unwrap();
-    /// ctx.forward_to(chat).await?;
+    /// if let Some(story) = ctx.replied_story().await {
+    ///     println!("Replying to the story {}", story.story_id);
+    /// }
     /// # }
     /// ```
+    pub async fn replied_story(&self) -> Option<tl::types::MessageReplyStoryHeader> {
+        match self.message().await?.reply_header()? {
+            tl::enums::MessageReplyHeader::MessageReplyStoryHeader(header) => Some(header),
+            tl::enums::MessageReplyHeader::Header(_) => None,
+        }
+    }
+
+    /// Returns the reply header if the message held by the update is replying to
+    /// a message from another chat.
     ///
-    /// # Errors
+    /// The header holds the replied chat, the forward info and the media of the
+    /// replied message, and the quoted text. [`Context::get_reply`] returns `None`
+    /// for these replies.
     ///
-    /// Returns an error if the message could not be forwarded.
-    pub async fn forward_to<C: Into<PackedChat>>(
-        &self,
-        chat: C,
-    ) -> Result<Message, InvocationError> {
-        if let Some(msg) = self.message().await {
-            msg.forward_to(chat).await
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// if let Some(reply) = ctx.external_reply().await {
+    ///     println!("Quoting: {:?}", reply.quote_text);
+    /// }
+    /// # }
+    /// ```
+    pub async fn external_reply(&self) -> Option<tl::types::MessageReplyHeader> {
+        match self.message().await?.reply_header()? {
+            tl::enums::MessageReplyHeader::Header(header)
+                if header.reply_to_peer_id.is_some() || header.reply_from.is_some() =>
+            {
+                Some(header)
+            }
+            _ => None,
+        }
+    }
+
+    /// Tries to forward the message held by the update to a chat.
+    ///
+    /// Returns the forwarded message.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// let chat = ctx.chat().unwrap();
+    /// ctx.forward_to(chat).await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message could not be forwarded.
+    pub async fn forward_to<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> Result<Message, InvocationError> {
+        if let Some(msg) = self.message().await {
+            msg.forward_to(chat).await
         } else {
             panic!("Cannot forward this message")
         }
@@ -521,6 +1151,145 @@ impl Context {
         self.client.upload_stream(stream, size, name).await
     }
 
+    /// Tries to upload a local file to the telegram with the given options.
+    ///
+    /// Uploads the parts in parallel, retrying the failed ones and reporting the progress.
+    ///
+    /// Returns the uploaded file.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// let options = UploadOptions::default().progress(|uploaded, total| {
+    ///     println!("Uploaded {} of {} bytes", uploaded, total);
+    /// });
+    /// let file = ctx.upload_file_with("path/to/file", options).await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be uploaded.
+    pub async fn upload_file_with<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: UploadOptions,
+    ) -> Result<Uploaded, InvocationError> {
+        let path = path.as_ref();
+
+        let mut file = tokio::fs::File::open(path).await?;
+        let size = file.metadata().await?.len() as usize;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        upload::upload_stream(&self.client, &mut file, size, name, options).await
+    }
+
+    /// Tries to upload a stream to the telegram with the given options.
+    ///
+    /// Uploads the parts in parallel, retrying the failed ones and reporting the progress.
+    ///
+    /// Returns the uploaded file.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// let stream = tokio::fs::File::open("path/to/file").await?;
+    /// let options = UploadOptions::default().workers(8);
+    /// let file = ctx.upload_stream_with(&mut stream, 1024, "file.txt", options).await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream could not be uploaded.
+    pub async fn upload_stream_with<S: AsyncRead + Unpin>(
+        &self,
+        stream: &mut S,
+        size: usize,
+        name: String,
+        options: UploadOptions,
+    ) -> Result<Uploaded, InvocationError> {
+        upload::upload_stream(&self.client, stream, size, name, options).await
+    }
+
+    /// Tries to download the media of the message held by the update and upload it again to a chat.
+    ///
+    /// The media is streamed from Telegram to Telegram in chunks, without buffering
+    /// the entire file. Only photos and documents can be reuploaded.
+    ///
+    /// Returns the sent message.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// let chat = ctx.chat().unwrap();
+    /// ctx.reupload_media_to(chat).await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the update holds no message or media, if the media is not
+    /// a photo or document, or if it could not be downloaded, uploaded or sent.
+    pub async fn reupload_media_to<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> Result<Message, crate::Error> {
+        let message = self
+            .message()
+            .await
+            .ok_or_else(|| crate::Error::telegram("The update has no message"))?;
+        let media = message
+            .media()
+            .ok_or_else(|| crate::Error::telegram("The message has no media"))?;
+
+        let mut input = InputMessage::text(message.text())
+            .fmt_entities(message.fmt_entities().cloned().unwrap_or_default());
+
+        match &media {
+            Media::Photo(photo) => {
+                let size = photo.size() as usize;
+                let name = "photo.jpg".to_string();
+                let uploaded =
+                    upload::reupload(&self.client, photo, size, name, UploadOptions::default())
+                        .await?;
+
+                input = input.photo(uploaded);
+            }
+            Media::Document(document) => {
+                let size = document.size() as usize;
+                let name = match document.name() {
+                    "" => "file".to_string(),
+                    name => name.to_string(),
+                };
+                let uploaded =
+                    upload::reupload(&self.client, document, size, name, UploadOptions::default())
+                        .await?;
+
+                input = input.document(uploaded);
+                if let Some(mime_type) = document.mime_type() {
+                    input = input.mime_type(mime_type);
+                }
+            }
+            _ => {
+                return Err(crate::Error::telegram(
+                    "Only photos and documents can be reuploaded",
+                ))
+            }
+        }
+
+        Ok(self.client.send_message(chat, input).await?)
+    }
+
     /// Tries to forward the message held by the update to the client's saved messages.
     ///
     /// Returns the forwarded message.
@@ -749,6 +1518,35 @@ impl Context {
         Ok(messages)
     }
 
+    /// Exports the history of the chat to a JSON or NDJSON file.
+    ///
+    /// The export can be interrupted and continued later from its checkpoint, see
+    /// [`ExportOptions`]. Not works with bot clients.
+    ///
+    /// Returns how many messages were exported.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx: ferogram::Context = unimplemented!();
+    /// use ferogram::ExportOptions;
+    ///
+    /// let count = ctx
+    ///     .export_history(ExportOptions::new("history.ndjson").media_dir("media"))
+    ///     .await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the messages could not be retrieved or written.
+    pub async fn export_history(&self, options: ExportOptions) -> crate::Result<usize> {
+        let chat = self.chat().expect("No chat");
+
+        export::export_history(&self.client, chat.pack(), &options).await
+    }
+
     /// Returns the messages in the chat from the client.
     ///
     /// If the limit is `None`, it will be set to `100`.
@@ -788,31 +1586,34 @@ impl Context {
         Ok(messages)
     }
 
+    /// Subscribes to the updates until the waiter is dropped.
+    pub(crate) fn waiter(&self, cancel: Option<&CancellationToken>) -> UpdateWaiter {
+        UpdateWaiter {
+            receiver: self.upd_sender.subscribe(),
+            cancel: cancel.cloned(),
+        }
+    }
+
     /// Waits for an update.
     ///
     /// If the timeout is `None`, it will be set to 30 seconds.
     ///
-    /// Returns `None` if the timeout is reached.
+    /// Returns `None` if the timeout is reached or the token is cancelled.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # async fn example() {
     /// # let ctx = unimplemented!();
-    /// let update = ctx.wait_for_update(None).await?;
+    /// let update = ctx.wait_for_update(None, None).await?;
     /// # }
     /// ```
-    pub async fn wait_for_update(&self, timeout: Option<u64>) -> Option<Update> {
-        let mut rx = self.upd_receiver.lock().await;
-
-        let stop =
-            pin!(async { tokio::time::sleep(Duration::from_secs(timeout.unwrap_or(30))).await });
-        let upd = pin!(async { rx.recv().await });
-
-        match select(stop, upd).await {
-            Either::Left(_) => None,
-            Either::Right((update, _)) => update.ok(),
-        }
+    pub async fn wait_for_update(
+        &self,
+        timeout: Option<u64>,
+        cancel: Option<&CancellationToken>,
+    ) -> Option<Update> {
+        self.waiter(cancel).next(wait_timeout(timeout)).await.ok()
     }
 
     /// Waits for an update that matches the filter.
@@ -835,25 +1636,25 @@ impl Context {
     ///     }
     ///
     ///     flow::break_now()
-    /// }, None).await?;
+    /// }, None, None).await?;
     /// # }
     /// ```
     ///
     /// # Errors
     ///
-    /// Returns an error if the update could not be received.
+    /// Returns an error if the timeout is reached or the token is cancelled.
     pub async fn wait_for<F: Filter>(
         &self,
         mut filter: F,
         timeout: Option<u64>,
+        cancel: Option<&CancellationToken>,
     ) -> Result<Update, crate::Error> {
+        let mut waiter = self.waiter(cancel);
+
         loop {
-            if let Some(update) = self.wait_for_update(timeout).await {
-                if filter.check(&self.client, &update).await.is_continue() {
-                    return Ok(update);
-                }
-            } else {
-                return Err(crate::Error::timeout(timeout.unwrap()));
+            let update = waiter.next(wait_timeout(timeout)).await?;
+            if filter.check(&self.client, &update).await.is_continue() {
+                return Ok(update);
             }
         }
     }
@@ -867,31 +1668,30 @@ impl Context {
     /// ```no_run
     /// # async fn example() {
     /// # let ctx = unimplemented!();
-    /// let message = ctx.wait_for_reply("Hello, world!", None).await?;
+    /// let message = ctx.wait_for_reply("Hello, world!", None, None).await?;
     /// # }
     /// ```
     ///
     /// # Errors
     ///
-    /// Returns an error if the message could not be sent or the reply could not be received.
+    /// Returns an error if the message could not be sent, the timeout is reached or
+    /// the token is cancelled.
     pub async fn wait_for_reply<M: Into<InputMessage>>(
         &self,
         message: M,
         timeout: Option<u64>,
+        cancel: Option<&CancellationToken>,
     ) -> Result<Message, crate::Error> {
+        let mut waiter = self.waiter(cancel);
         let sent = self.reply(message).await?;
 
         loop {
-            if let Some(update) = self.wait_for_update(timeout).await {
-                if let Update::NewMessage(msg) | Update::MessageEdited(msg) = update {
-                    if let Some(msg_id) = msg.reply_to_message_id() {
-                        if msg_id == sent.id() {
-                            return Ok(msg);
-                        }
-                    }
+            if let Update::NewMessage(msg) | Update::MessageEdited(msg) =
+                waiter.next(wait_timeout(timeout)).await?
+            {
+                if msg.reply_to_message_id() == Some(sent.id()) {
+                    return Ok(msg);
                 }
-            } else {
-                return Err(crate::Error::timeout(timeout.unwrap()));
             }
         }
     }
@@ -905,21 +1705,23 @@ impl Context {
     /// ```no_run
     /// # async fn example() {
     /// # let ctx = unimplemented!();
-    /// let message = ctx.wait_for_message(None).await?;
+    /// let message = ctx.wait_for_message(None, None).await?;
     /// # }
     /// ```
     ///
     /// # Errors
     ///
-    /// Returns an error if the message could not be received.
-    pub async fn wait_for_message(&self, timeout: Option<u64>) -> Result<Message, crate::Error> {
+    /// Returns an error if the timeout is reached or the token is cancelled.
+    pub async fn wait_for_message(
+        &self,
+        timeout: Option<u64>,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Message, crate::Error> {
+        let mut waiter = self.waiter(cancel);
+
         loop {
-            if let Some(update) = self.wait_for_update(timeout).await {
-                if let Update::NewMessage(message) = update {
-                    return Ok(message);
-                }
-            } else {
-                return Err(crate::Error::timeout(timeout.unwrap()));
+            if let Update::NewMessage(message) = waiter.next(wait_timeout(timeout)).await? {
+                return Ok(message);
             }
         }
     }
@@ -933,24 +1735,23 @@ impl Context {
     /// ```no_run
     /// # async fn example() {
     /// # let ctx = unimplemented!();
-    /// let query = ctx.wait_for_callback_query(None).await?;
+    /// let query = ctx.wait_for_callback_query(None, None).await?;
     /// # }
     /// ```
     ///
     /// # Errors
     ///
-    /// Returns an error if the callback query could not be received.
+    /// Returns an error if the timeout is reached or the token is cancelled.
     pub async fn wait_for_callback_query(
         &self,
         timeout: Option<u64>,
+        cancel: Option<&CancellationToken>,
     ) -> Result<CallbackQuery, crate::Error> {
+        let mut waiter = self.waiter(cancel);
+
         loop {
-            if let Some(update) = self.wait_for_update(timeout).await {
-                if let Update::CallbackQuery(query) = update {
-                    return Ok(query);
-                }
-            } else {
-                return Err(crate::Error::timeout(timeout.unwrap()));
+            if let Update::CallbackQuery(query) = waiter.next(wait_timeout(timeout)).await? {
+                return Ok(query);
             }
         }
     }
@@ -964,24 +1765,23 @@ impl Context {
     /// ```no_run
     /// # async fn example() {
     /// # let ctx = unimplemented!();
-    /// let query = ctx.wait_for_inline_query(None).await?;
+    /// let query = ctx.wait_for_inline_query(None, None).await?;
     /// # }
     /// ```
     ///
     /// # Errors
     ///
-    /// Returns an error if the inline query could not be received.
+    /// Returns an error if the timeout is reached or the token is cancelled.
     pub async fn wait_for_inline_query(
         &self,
         timeout: Option<u64>,
+        cancel: Option<&CancellationToken>,
     ) -> Result<InlineQuery, crate::Error> {
+        let mut waiter = self.waiter(cancel);
+
         loop {
-            if let Some(update) = self.wait_for_update(timeout).await {
-                if let Update::InlineQuery(query) = update {
-                    return Ok(query);
-                }
-            } else {
-                return Err(crate::Error::timeout(timeout.unwrap()));
+            if let Update::InlineQuery(query) = waiter.next(wait_timeout(timeout)).await? {
+                return Ok(query);
             }
         }
     }
@@ -995,149 +1795,963 @@ impl Context {
     /// ```no_run
     /// # async fn example() {
     /// # let ctx = unimplemented!();
-    /// let inline_send = ctx.wait_for_inline_send(None).await?;
+    /// let inline_send = ctx.wait_for_inline_send(None, None).await?;
     /// # }
     /// ```
     ///
     /// # Errors
     ///
-    /// Returns an error if the inline send could not be received.
+    /// Returns an error if the timeout is reached or the token is cancelled.
     pub async fn wait_for_inline_send(
         &self,
         timeout: Option<u64>,
+        cancel: Option<&CancellationToken>,
     ) -> Result<InlineSend, crate::Error> {
+        let mut waiter = self.waiter(cancel);
+
         loop {
-            if let Some(update) = self.wait_for_update(timeout).await {
-                if let Update::InlineSend(inline_send) = update {
-                    return Ok(inline_send);
-                }
-            } else {
-                return Err(crate::Error::timeout(timeout.unwrap()));
+            if let Update::InlineSend(inline_send) = waiter.next(wait_timeout(timeout)).await? {
+                return Ok(inline_send);
             }
         }
     }
 
-    /// Returns the media of the message.
+    /// Transcribes the voice note held by the update.
+    ///
+    /// Requires a premium account, or a trial transcription. While the transcription
+    /// is pending, polls every second until it's done.
+    ///
+    /// If the timeout is `None`, it will be set to 30 seconds.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # async fn example() {
-    /// # let ctx = unimplemented!();
-    /// let media = ctx.media().await?;
+    /// # let router: ferogram::Router = unimplemented!();
+    /// use ferogram::{filter, handler, Context, Filter};
+    ///
+    /// let router = router.register(
+    ///     handler::new_message(filter::has_voice.and(filter::private)).then(
+    ///         |ctx: Context| async move {
+    ///             let text = ctx.transcribe_voice(None).await?;
+    ///             ctx.reply(text).await?;
+    ///
+    ///             Ok(())
+    ///         },
+    ///     ),
+    /// );
     /// # }
     /// ```
-    pub async fn media(&self) -> Option<Media> {
-        if let Some(message) = self.message().await {
-            return message.media();
-        }
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the voice note could not be transcribed in time.
+    pub async fn transcribe_voice(&self, timeout: Option<u64>) -> Result<String, crate::Error> {
+        let message = self.message().await.expect("No message");
+        let peer = message.chat().pack().to_input_peer();
 
-        None
+        let timeout = timeout.unwrap_or(30);
+        let started_at = Instant::now();
+
+        loop {
+            let tl::enums::messages::TranscribedAudio::Audio(transcription) = self
+                .client
+                .invoke(&tl::functions::messages::TranscribeAudio {
+                    peer: peer.clone(),
+                    msg_id: message.id(),
+                })
+                .await?;
+
+            if !transcription.pending {
+                return Ok(transcription.text);
+            } else if started_at.elapsed() >= Duration::from_secs(timeout) {
+                return Err(crate::Error::timeout(timeout));
+            }
+
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
     }
 
-    /// Returns the photo of the message.
+    /// Returns the view count of the message, for channel posts.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # async fn example() {
     /// # let ctx = unimplemented!();
-    /// let photo = ctx.photo().await?;
+    /// if let Some(views) = ctx.view_count().await {
+    ///     println!("Viewed {} times", views);
+    /// }
     /// # }
     /// ```
-    pub async fn photo(&self) -> Option<Photo> {
-        if let Some(message) = self.message().await {
-            return message.photo();
-        }
-
-        None
+    pub async fn view_count(&self) -> Option<i32> {
+        self.message()
+            .await
+            .and_then(|message| message.view_count())
     }
 
-    /// Returns if the message has a media.
+    /// Gets the view counts of the messages in the chat, in the same order as `message_ids`.
+    ///
+    /// Messages without a view count, like the ones outside channels, return `None`.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # async fn example() {
     /// # let ctx = unimplemented!();
-    /// if ctx.has_media().await {
-    ///     ctx.send("Hello, world!").await?;
-    /// }
+    /// let chat = ctx.chat().unwrap();
+    /// let views = ctx.get_message_views(chat, &[1, 2, 3]).await?;
     /// # }
     /// ```
-    pub async fn has_media(&self) -> bool {
-        self.media().await.is_some()
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the view counts could not be fetched.
+    pub async fn get_message_views<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        message_ids: &[i32],
+    ) -> Result<Vec<Option<i32>>, InvocationError> {
+        self.invoke_message_views(chat, message_ids, false).await
     }
 
-    /// Returns if the message has a photo.
+    /// Marks the messages of the channel as viewed, incrementing their view counts.
+    ///
+    /// Returns the new view counts, in the same order as `message_ids`.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # async fn example() {
     /// # let ctx = unimplemented!();
-    /// if ctx.has_photo().await {
-    ///     ctx.send("Hello, world!").await?;
-    /// }
+    /// let chat = ctx.chat().unwrap();
+    /// ctx.increment_views(chat, &[1, 2, 3]).await?;
     /// # }
     /// ```
-    pub async fn has_photo(&self) -> bool {
-        self.photo().await.is_some()
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the messages could not be marked as viewed.
+    pub async fn increment_views<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        message_ids: &[i32],
+    ) -> Result<Vec<Option<i32>>, InvocationError> {
+        self.invoke_message_views(chat, message_ids, true).await
     }
 
-    /// Returns if the chat is private (an user).
+    /// Invokes `messages.getMessagesViews`, returning the view counts.
+    async fn invoke_message_views<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        message_ids: &[i32],
+        increment: bool,
+    ) -> Result<Vec<Option<i32>>, InvocationError> {
+        let tl::enums::messages::MessageViews::Views(views) = self
+            .client
+            .invoke(&tl::functions::messages::GetMessagesViews {
+                peer: chat.into().to_input_peer(),
+                id: message_ids.to_vec(),
+                increment,
+            })
+            .await?;
+
+        Ok(views
+            .views
+            .into_iter()
+            .map(|tl::enums::MessageViews::Views(views)| views.views)
+            .collect())
+    }
+
+    /// Sends a built message to the chat, with its effect.
+    ///
+    /// If the chat is in slow mode, waits until the message can be sent.
+    ///
+    /// Returns the sent message.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # async fn example() {
     /// # let ctx = unimplemented!();
-    /// if ctx.is_private() {
-    ///     ctx.send("Hello, world!").await?;
-    /// }
+    /// use ferogram::MessageBuilder;
+    ///
+    /// let message = MessageBuilder::new()
+    ///     .text("You won! ")
+    ///     .spoiler("The prize is a cake.")
+    ///     .effect(5046509860389126442);
+    /// ctx.send_message(message).await?;
     /// # }
     /// ```
-    pub fn is_private(&self) -> bool {
-        self.chat()
-            .map(|chat| matches!(chat, Chat::User(_)))
-            .unwrap_or(false)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message could not be sent.
+    pub async fn send_message(&self, message: MessageBuilder) -> Result<Message, crate::Error> {
+        self.send_raw(|peer, random_id| message.request(peer, random_id))
+            .await
     }
 
-    /// Returns if the chat is a group.
+    /// Sends paid media to the chat, unlocked for `stars` Telegram Stars.
+    ///
+    /// Only photos and videos can be paid. The `payload` is not shown to the users,
+    /// and is sent back in the purchases, see [`crate::filter::paid_media_purchased`].
+    ///
+    /// Returns the sent message.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # async fn example() {
     /// # let ctx = unimplemented!();
-    /// if ctx.is_group() {
-    ///     ctx.send("Hello, world!").await?;
-    /// }
+    /// use ferogram::MediaBuilder;
+    ///
+    /// let photo = ctx.upload_file("exclusive.jpg").await?;
+    /// ctx.send_paid_media(vec![MediaBuilder::photo(photo)], 50, Some("post-42"))
+    ///     .await?;
     /// # }
     /// ```
-    pub fn is_group(&self) -> bool {
-        self.chat()
-            .map(|chat| matches!(chat, Chat::Group(_)))
-            .unwrap_or(false)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the media could not be sent.
+    pub async fn send_paid_media(
+        &self,
+        media: Vec<MediaBuilder>,
+        stars: i64,
+        payload: Option<&str>,
+    ) -> Result<Message, crate::Error> {
+        let media = tl::enums::InputMedia::PaidMedia(tl::types::InputMediaPaidMedia {
+            stars_amount: stars,
+            extended_media: media.into_iter().map(MediaBuilder::input_media).collect(),
+            payload: payload.map(str::to_string),
+        });
+
+        self.send_media_raw(media, String::new(), None, None).await
     }
 
-    /// Returns if the chat is a channel.
+    /// Sends a poll to the chat.
+    ///
+    /// If the chat is in slow mode, waits until the poll can be sent.
+    ///
+    /// Returns the sent message.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # async fn example() {
     /// # let ctx = unimplemented!();
-    /// if ctx.is_channel() {
-    ///     ctx.send("Hello, world!").await?;
-    /// }
+    /// use ferogram::PollBuilder;
+    ///
+    /// let poll = PollBuilder::new("Pizza or burger?", &["Pizza", "Burger"]).anonymous(false);
+    /// ctx.send_poll(poll).await?;
     /// # }
     /// ```
-    pub fn is_channel(&self) -> bool {
-        self.chat()
-            .map(|chat| matches!(chat, Chat::Channel(_)))
-            .unwrap_or(false)
-    }
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the poll could not be sent.
+    pub async fn send_poll(&self, poll: PollBuilder) -> Result<Message, crate::Error> {
+        self.send_media_raw(poll.build(), String::new(), None, None)
+            .await
+    }
+
+    /// Sends the media to the chat, in its send queue.
+    ///
+    /// Returns the sent message.
+    async fn send_media_raw(
+        &self,
+        media: tl::enums::InputMedia,
+        message: String,
+        entities: Option<Vec<tl::enums::MessageEntity>>,
+        reply_markup: Option<tl::enums::ReplyMarkup>,
+    ) -> Result<Message, crate::Error> {
+        self.send_raw(|peer, random_id| tl::functions::messages::SendMedia {
+            silent: false,
+            background: false,
+            clear_draft: false,
+            noforwards: false,
+            update_stickersets_order: false,
+            invert_media: false,
+            allow_paid_floodskip: false,
+            peer,
+            reply_to: None,
+            media: media.clone(),
+            message: message.clone(),
+            random_id,
+            reply_markup: reply_markup.clone(),
+            entities: entities.clone(),
+            schedule_date: None,
+            send_as: None,
+            quick_reply_shortcut: None,
+            effect: None,
+        })
+        .await
+    }
+
+    /// Sends the request built with the peer of the chat and a new random ID,
+    /// in the chat's send queue, so it waits for the slow mode.
+    ///
+    /// Returns the sent message.
+    async fn send_raw<R, B>(&self, build: B) -> Result<Message, crate::Error>
+    where
+        R: tl::RemoteCall<Return = tl::enums::Updates>,
+        B: Fn(tl::enums::InputPeer, i64) -> R,
+    {
+        let chat = self.chat().expect("No chat");
+
+        let (updates, random_id) = self
+            .slow_mode
+            .send(chat.id(), || {
+                let random_id = random_u64() as i64;
+                let request = build(chat.pack().to_input_peer(), random_id);
+
+                async move {
+                    self.client
+                        .invoke(&request)
+                        .await
+                        .map(|updates| (updates, random_id))
+                }
+            })
+            .await?;
+
+        let message_id = sent_message_id(&updates, random_id)
+            .ok_or_else(|| crate::Error::telegram("The sent message was not found"))?;
+
+        self.client
+            .get_messages_by_id(chat.pack(), &[message_id])
+            .await?
+            .pop()
+            .flatten()
+            .ok_or_else(|| crate::Error::telegram("The sent message was not found"))
+    }
+
+    /// Closes the poll held by the update, so no more votes are accepted.
+    ///
+    /// If the message has no poll, it will panic.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// ctx.stop_poll().await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the poll could not be closed.
+    pub async fn stop_poll(&self) -> Result<(), InvocationError> {
+        let message = self.message().await.expect("No message");
+        let Some(Media::Poll(poll)) = message.media() else {
+            panic!("Cannot stop this poll")
+        };
+
+        let mut raw = poll.raw;
+        raw.closed = true;
+
+        self.client
+            .invoke(&tl::functions::messages::EditMessage {
+                no_webpage: false,
+                invert_media: false,
+                peer: message.chat().pack().to_input_peer(),
+                id: message.id(),
+                message: None,
+                media: Some(tl::enums::InputMedia::Poll(tl::types::InputMediaPoll {
+                    poll: tl::enums::Poll::Poll(raw),
+                    correct_answers: None,
+                    solution: None,
+                    solution_entities: None,
+                })),
+                reply_markup: None,
+                entities: None,
+                schedule_date: None,
+                quick_reply_shortcut_id: None,
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Returns the results of the poll held by the update.
+    ///
+    /// Each option paired with its vote count, in the same order as the options.
+    /// Returns `None` if the message has no poll or the results are not visible yet.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// if let Some(results) = ctx.poll_results().await {
+    ///     for (option, votes) in results {
+    ///         println!("{}: {}", option, votes);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub async fn poll_results(&self) -> Option<Vec<(String, i32)>> {
+        let Some(Media::Poll(poll)) = self.media().await else {
+            return None;
+        };
+        let voters = poll.raw_results?.results?;
+
+        Some(
+            poll.raw
+                .answers
+                .into_iter()
+                .map(|tl::enums::PollAnswer::Answer(answer)| {
+                    let tl::enums::TextWithEntities::Entities(text) = answer.text;
+                    let votes = voters
+                        .iter()
+                        .find_map(|tl::enums::PollAnswerVoters::Voters(voters)| {
+                            (voters.option == answer.option).then_some(voters.voters)
+                        })
+                        .unwrap_or(0);
+
+                    (text.text, votes)
+                })
+                .collect(),
+        )
+    }
+
+    /// Sets the score of the user in the game held by the update.
+    ///
+    /// The game message is edited to show the new high scores.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// let user = ctx.sender().unwrap();
+    /// ctx.set_game_score(user, 100).await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the score could not be set, like when it is lower than the current one.
+    pub async fn set_game_score<C: Into<PackedChat>>(
+        &self,
+        user: C,
+        score: i32,
+    ) -> Result<(), InvocationError> {
+        let message = self.message().await.expect("No message");
+
+        self.client
+            .invoke(&tl::functions::messages::SetGameScore {
+                edit_message: true,
+                force: false,
+                peer: message.chat().pack().to_input_peer(),
+                id: message.id(),
+                user_id: user.into().to_input_user_lossy(),
+                score,
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Gets the high scores of the game held by the update, around the user's score.
+    ///
+    /// Returns the user IDs paired with their scores, from the highest score.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// let user = ctx.sender().unwrap();
+    /// for (user_id, score) in ctx.get_game_high_scores(user).await? {
+    ///     println!("{}: {}", user_id, score);
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the high scores could not be fetched.
+    pub async fn get_game_high_scores<C: Into<PackedChat>>(
+        &self,
+        user: C,
+    ) -> Result<Vec<(i64, i32)>, InvocationError> {
+        let message = self.message().await.expect("No message");
+
+        let tl::enums::messages::HighScores::Scores(high_scores) = self
+            .client
+            .invoke(&tl::functions::messages::GetGameHighScores {
+                peer: message.chat().pack().to_input_peer(),
+                id: message.id(),
+                user_id: user.into().to_input_user_lossy(),
+            })
+            .await?;
+
+        Ok(high_scores
+            .scores
+            .into_iter()
+            .map(|tl::enums::HighScore::Score(high_score)| (high_score.user_id, high_score.score))
+            .collect())
+    }
+
+    /// Moves the chat to the archive.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// let chat = ctx.chat().unwrap();
+    /// ctx.archive(chat).await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the chat could not be archived.
+    pub async fn archive<C: Into<PackedChat>>(&self, chat: C) -> Result<(), InvocationError> {
+        self.edit_peer_folder(chat, 1).await
+    }
+
+    /// Moves the chat out of the archive.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// let chat = ctx.chat().unwrap();
+    /// ctx.unarchive(chat).await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the chat could not be unarchived.
+    pub async fn unarchive<C: Into<PackedChat>>(&self, chat: C) -> Result<(), InvocationError> {
+        self.edit_peer_folder(chat, 0).await
+    }
+
+    /// Moves the chat to the peer folder, `0` is the main list and `1` the archive.
+    async fn edit_peer_folder<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        folder_id: i32,
+    ) -> Result<(), InvocationError> {
+        self.client
+            .invoke(&tl::functions::folders::EditPeerFolders {
+                folder_peers: vec![tl::enums::InputFolderPeer::Peer(
+                    tl::types::InputFolderPeer {
+                        peer: chat.into().to_input_peer(),
+                        folder_id,
+                    },
+                )],
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Gets the chat folders of the account.
+    ///
+    /// Returns the IDs of the folders paired with their titles.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// for (id, title) in ctx.get_folders().await? {
+    ///     println!("{}: {}", id, title);
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the folders could not be fetched.
+    pub async fn get_folders(&self) -> Result<Vec<(i32, String)>, InvocationError> {
+        Ok(self
+            .get_dialog_filters()
+            .await?
+            .into_iter()
+            .filter_map(|filter| match filter {
+                tl::enums::DialogFilter::Filter(filter) => Some((filter.id, filter.title)),
+                tl::enums::DialogFilter::Chatlist(chatlist) => Some((chatlist.id, chatlist.title)),
+                tl::enums::DialogFilter::Default => None,
+            })
+            .collect())
+    }
+
+    /// Adds the chat to the chat folder.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// let chat = ctx.chat().unwrap();
+    /// let (folder_id, _) = ctx.get_folders().await?[0];
+    /// ctx.add_chat_to_folder(chat, folder_id).await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the folder was not found or could not be updated.
+    pub async fn add_chat_to_folder<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        folder_id: i32,
+    ) -> Result<(), crate::Error> {
+        let peer = chat.into().to_input_peer();
+
+        let mut filter = self
+            .get_dialog_filters()
+            .await?
+            .into_iter()
+            .find(|filter| match filter {
+                tl::enums::DialogFilter::Filter(filter) => filter.id == folder_id,
+                tl::enums::DialogFilter::Chatlist(chatlist) => chatlist.id == folder_id,
+                tl::enums::DialogFilter::Default => false,
+            })
+            .ok_or_else(|| crate::Error::telegram("The folder was not found"))?;
+
+        let include_peers = match &mut filter {
+            tl::enums::DialogFilter::Filter(filter) => &mut filter.include_peers,
+            tl::enums::DialogFilter::Chatlist(chatlist) => &mut chatlist.include_peers,
+            tl::enums::DialogFilter::Default => unreachable!(),
+        };
+        if include_peers.contains(&peer) {
+            return Ok(());
+        }
+        include_peers.push(peer);
+
+        self.client
+            .invoke(&tl::functions::messages::UpdateDialogFilter {
+                id: folder_id,
+                filter: Some(filter),
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Gets the raw dialog filters of the account.
+    async fn get_dialog_filters(&self) -> Result<Vec<tl::enums::DialogFilter>, InvocationError> {
+        let tl::enums::messages::DialogFilters::Filters(filters) = self
+            .client
+            .invoke(&tl::functions::messages::GetDialogFilters {})
+            .await?;
+
+        Ok(filters.filters)
+    }
+
+    /// Saves a draft in the chat of the update.
+    ///
+    /// An empty text clears the draft.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// ctx.save_draft("I'll answer it later").await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the draft could not be saved.
+    pub async fn save_draft<T: ToString>(&self, text: T) -> Result<(), InvocationError> {
+        let chat = self.chat().expect("No chat");
+
+        self.client
+            .invoke(&tl::functions::messages::SaveDraft {
+                no_webpage: false,
+                invert_media: false,
+                reply_to: None,
+                peer: chat.pack().to_input_peer(),
+                message: text.to_string(),
+                entities: None,
+                media: None,
+                effect: None,
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Clears the drafts of every chat.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// ctx.clear_drafts().await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the drafts could not be cleared.
+    pub async fn clear_drafts(&self) -> Result<(), InvocationError> {
+        self.client
+            .invoke(&tl::functions::messages::ClearAllDrafts {})
+            .await?;
+
+        Ok(())
+    }
+
+    /// Invokes a raw request, with the same resilience as the other methods.
+    ///
+    /// Flood waits below the client's `flood_sleep_threshold` are slept and retried by
+    /// the client, and when a send-type request fails with `SLOWMODE_WAIT` in the chat
+    /// of the update, it is retried in the chat's send queue like [`Context::send`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// use grammers_client::grammers_tl_types as tl;
+    ///
+    /// let config = ctx.invoke(&tl::functions::help::GetConfig {}).await?;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request failed, mapped into [`crate::Error`].
+    pub async fn invoke<R: tl::RemoteCall>(&self, request: &R) -> Result<R::Return, crate::Error> {
+        let result = match self.client.invoke(request).await {
+            Err(InvocationError::Rpc(e)) if e.name == "SLOWMODE_WAIT" => {
+                match self.update.as_ref().and_then(|_| self.chat()) {
+                    Some(chat) => {
+                        // The queue receives the error first, so it waits before retrying.
+                        let mut error = Some(InvocationError::Rpc(e));
+                        self.slow_mode
+                            .send(chat.id(), || {
+                                let error = error.take();
+
+                                async move {
+                                    match error {
+                                        Some(error) => Err(error),
+                                        None => self.client.invoke(request).await,
+                                    }
+                                }
+                            })
+                            .await
+                    }
+                    None => Err(InvocationError::Rpc(e)),
+                }
+            }
+            result => result,
+        };
+
+        Ok(result?)
+    }
+
+    /// Invokes many requests at once, returning the result of each one in order.
+    ///
+    /// See [`crate::Client::invoke_batched`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let (ctx, users): (ferogram::Context, Vec<grammers_client::types::User>) = unimplemented!();
+    /// use grammers_client::grammers_tl_types as tl;
+    ///
+    /// let requests = users
+    ///     .iter()
+    ///     .map(|user| tl::functions::users::GetFullUser {
+    ///         id: user.pack().to_input_user_lossy(),
+    ///     })
+    ///     .collect::<Vec<_>>();
+    /// for result in ctx.invoke_batched(&requests).await {
+    ///     println!("{:?}", result);
+    /// }
+    /// # }
+    /// ```
+    pub async fn invoke_batched<R: tl::RemoteCall>(
+        &self,
+        requests: &[R],
+    ) -> Vec<Result<R::Return, InvocationError>> {
+        crate::client::invoke_batched(&self.client, requests).await
+    }
+
+    /// Returns the media of the message.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// let media = ctx.media().await?;
+    /// # }
+    /// ```
+    pub async fn media(&self) -> Option<Media> {
+        if let Some(message) = self.message().await {
+            return message.media();
+        }
+
+        None
+    }
+
+    /// Returns the photo of the message.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// let photo = ctx.photo().await?;
+    /// # }
+    /// ```
+    pub async fn photo(&self) -> Option<Photo> {
+        if let Some(message) = self.message().await {
+            return message.photo();
+        }
+
+        None
+    }
+
+    /// Returns if the message has a media.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// if ctx.has_media().await {
+    ///     ctx.send("Hello, world!").await?;
+    /// }
+    /// # }
+    /// ```
+    pub async fn has_media(&self) -> bool {
+        self.media().await.is_some()
+    }
+
+    /// Returns if the message has a photo.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// if ctx.has_photo().await {
+    ///     ctx.send("Hello, world!").await?;
+    /// }
+    /// # }
+    /// ```
+    pub async fn has_photo(&self) -> bool {
+        self.photo().await.is_some()
+    }
+
+    /// Returns if the chat is private (an user).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// if ctx.is_private() {
+    ///     ctx.send("Hello, world!").await?;
+    /// }
+    /// # }
+    /// ```
+    pub fn is_private(&self) -> bool {
+        self.chat()
+            .map(|chat| matches!(chat, Chat::User(_)))
+            .unwrap_or(false)
+    }
+
+    /// Returns the id of the group which the chat was migrated from.
+    ///
+    /// Returns `None` if the update is not a migration service message sent in the new supergroup.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// if let Some(old_id) = ctx.migrated_from() {
+    ///     println!("Migrated from {}", old_id);
+    /// }
+    /// # }
+    /// ```
+    pub fn migrated_from(&self) -> Option<i64> {
+        match self.update.as_ref().expect("No update") {
+            Update::NewMessage(message) => match message.action() {
+                Some(tl::enums::MessageAction::ChannelMigrateFrom(action)) => Some(action.chat_id),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the id of the supergroup which the chat was migrated to.
+    ///
+    /// Returns `None` if the update is not a migration service message sent in the old group.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// if let Some(new_id) = ctx.migrated_to() {
+    ///     println!("Migrated to {}", new_id);
+    /// }
+    /// # }
+    /// ```
+    pub fn migrated_to(&self) -> Option<i64> {
+        match self.update.as_ref().expect("No update") {
+            Update::NewMessage(message) => match message.action() {
+                Some(tl::enums::MessageAction::ChatMigrateTo(action)) => Some(action.channel_id),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns if the chat is a group.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// if ctx.is_group() {
+    ///     ctx.send("Hello, world!").await?;
+    /// }
+    /// # }
+    /// ```
+    pub fn is_group(&self) -> bool {
+        self.chat()
+            .map(|chat| matches!(chat, Chat::Group(_)))
+            .unwrap_or(false)
+    }
+
+    /// Returns if the chat is a channel.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let ctx = unimplemented!();
+    /// if ctx.is_channel() {
+    ///     ctx.send("Hello, world!").await?;
+    /// }
+    /// # }
+    /// ```
+    pub fn is_channel(&self) -> bool {
+        self.chat()
+            .map(|chat| matches!(chat, Chat::Channel(_)))
+            .unwrap_or(false)
+    }
 
     /// Returns if the update is a message.
     ///
@@ -1253,15 +2867,85 @@ impl Context {
 
 impl Clone for Context {
     fn clone(&self) -> Self {
-        let upd_receiver = self
-            .upd_receiver
-            .try_lock()
-            .expect("Failed to lock receiver");
-
         Self {
             client: self.client.clone(),
             update: self.update.clone(),
-            upd_receiver: Arc::new(Mutex::new(upd_receiver.resubscribe())),
+            upd_sender: self.upd_sender.clone(),
+            slow_mode: self.slow_mode.clone(),
+            debouncer: self.debouncer.clone(),
+            cache: self.cache.clone(),
+            outbox: self.outbox.clone(),
+            history: self.history.clone(),
+            idempotency: self.idempotency.clone(),
+            correlation_id: self.correlation_id,
+        }
+    }
+}
+
+/// A subscription to the updates, alive only while waiting for them.
+///
+/// Dropping it, after a timeout, a cancellation or with the context, unsubscribes,
+/// so no updates are buffered for abandoned waits.
+pub(crate) struct UpdateWaiter {
+    /// The update receiver.
+    receiver: Receiver<Update>,
+    /// The token cancelling the wait, if any.
+    cancel: Option<CancellationToken>,
+}
+
+impl UpdateWaiter {
+    /// Waits for the next update, up to `timeout`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the timeout is reached, the token is cancelled or the
+    /// dispatcher was dropped.
+    pub(crate) async fn next(&mut self, timeout: Duration) -> Result<Update, crate::Error> {
+        let Self { receiver, cancel } = self;
+
+        let recv = pin!(tokio::time::timeout(timeout, async {
+            loop {
+                match receiver.recv().await {
+                    Ok(update) => return Some(update),
+                    Err(RecvError::Lagged(count)) => {
+                        log::warn!("Skipped {} updates while waiting", count);
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        }));
+        let cancelled = pin!(async {
+            match cancel.as_ref() {
+                Some(token) => token.cancelled().await,
+                None => std::future::pending().await,
+            }
+        });
+
+        match select(recv, cancelled).await {
+            Either::Left((Ok(Some(update)), _)) => Ok(update),
+            Either::Left((Ok(None), _)) => Err(crate::Error::unknown()),
+            Either::Left((Err(_), _)) => Err(crate::Error::timeout(timeout.as_secs())),
+            Either::Right(_) => Err(crate::Error::cancelled()),
         }
     }
 }
+
+/// Returns the timeout of the waits, 30 seconds by default.
+fn wait_timeout(timeout: Option<u64>) -> Duration {
+    Duration::from_secs(timeout.unwrap_or(30))
+}
+
+/// Returns the ID of the message sent with `random_id`.
+fn sent_message_id(updates: &tl::enums::Updates, random_id: i64) -> Option<i32> {
+    let updates = match updates {
+        tl::enums::Updates::Updates(updates) => &updates.updates,
+        tl::enums::Updates::Combined(updates) => &updates.updates,
+        tl::enums::Updates::UpdateShortSentMessage(sent) => return Some(sent.id),
+        _ => return None,
+    };
+
+    updates.iter().find_map(|update| match update {
+        tl::enums::Update::MessageId(sent) if sent.random_id == random_id => Some(sent.id),
+        _ => None,
+    })
+}
@@ -100,7 +100,7 @@ impl Conversation {
                 }
                 Action::WaitMessage => {
                     let message = context
-                        .wait_for_message(Some(self.timeout))
+                        .wait_for_message(Some(self.timeout), None)
                         .await
                         .expect("Failed to get message");
 
@@ -108,7 +108,7 @@ impl Conversation {
                 }
                 Action::WaitReply(message) => {
                     let message = context
-                        .wait_for_reply(message, Some(self.timeout))
+                        .wait_for_reply(message, Some(self.timeout), None)
                         .await
                         .expect("Failed to get reply message");
 
@@ -116,7 +116,7 @@ impl Conversation {
                 }
                 Action::WaitCallback => {
                     let callback_query = context
-                        .wait_for_callback_query(Some(self.timeout))
+                        .wait_for_callback_query(Some(self.timeout), None)
                         .await
                         .expect("Failed to get callback query");
 
@@ -124,7 +124,7 @@ impl Conversation {
                 }
                 Action::WaitInline => {
                     let inline_query = context
-                        .wait_for_inline_query(Some(self.timeout))
+                        .wait_for_inline_query(Some(self.timeout), None)
                         .await
                         .expect("Failed to get inline query");
 
@@ -0,0 +1,72 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Correlation module.
+
+use std::{future::Future, str::FromStr};
+
+use crate::utils::random_u64;
+
+tokio::task_local! {
+    /// The correlation ID of the update being handled by the task.
+    static CURRENT: CorrelationId;
+}
+
+/// An ID generated for each update, to trace it through the pipeline.
+///
+/// The ID is shown in the logs of the dispatcher and the outbox, and can be
+/// obtained in the handlers with [`crate::Context::correlation_id`], injected
+/// directly, or with [`CorrelationId::current`] in the task handling the update,
+/// including the error handlers. Send it to other services to trace the action of
+/// the user end to end.
+///
+/// Displayed as 16 hexadecimal digits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CorrelationId(u64);
+
+impl CorrelationId {
+    /// Generates a random ID.
+    pub fn new() -> Self {
+        Self(random_u64())
+    }
+
+    /// Returns the ID of the update being handled by the current task, if any.
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(|id| *id).ok()
+    }
+
+    /// Runs the future with the ID as the current one.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT.scope(self, future).await
+    }
+
+    /// Returns the ID as a number.
+    pub fn get(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for CorrelationId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for CorrelationId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl FromStr for CorrelationId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u64::from_str_radix(s, 16).map(Self)
+    }
+}
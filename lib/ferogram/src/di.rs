@@ -80,6 +80,14 @@ impl Injector {
             .push_back(Resource::new(value));
     }
 
+    /// Inserts an already wrapped resource of the given type.
+    pub(crate) fn insert_resource(&mut self, type_id: TypeId, resource: Resource) {
+        self.resources
+            .entry(type_id)
+            .or_default()
+            .push_back(resource);
+    }
+
     /// Inserts a new resource.
     ///
     /// # Example
@@ -111,6 +119,17 @@ impl Injector {
         }
     }
 
+    /// Inserts the resources of another injector in front of the existing ones,
+    /// so they are taken first.
+    pub(crate) fn shadow(&mut self, other: &Self) {
+        for (type_id, values) in other.resources.iter() {
+            let resources = self.resources.entry(*type_id).or_default();
+            for value in values.iter().rev() {
+                resources.push_front(value.clone());
+            }
+        }
+    }
+
     /// Removes a resource.
     ///
     /// # Example
@@ -145,6 +164,51 @@ impl Injector {
             .and_then(|resource| resource.to_ref())
     }
 
+    /// Returns the names of the types stored.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let injector = unimplemented!();
+    /// for type_name in injector.available_types() {
+    ///     println!("{}", type_name);
+    /// }
+    /// # }
+    /// ```
+    pub fn available_types(&self) -> Vec<&'static str> {
+        let mut type_names = self
+            .resources
+            .values()
+            .filter_map(|values| values.front())
+            .map(|resource| resource.type_name)
+            .collect::<Vec<_>>();
+        type_names.sort_unstable();
+
+        type_names
+    }
+
+    /// Creates a missing dependency error, listing the types stored.
+    ///
+    /// Ex: `Missing dependency: "Db", have: [Client, Context, Update]`.
+    pub(crate) fn missing_dependency<D>(&self) -> crate::Error {
+        let mut type_names = self
+            .available_types()
+            .into_iter()
+            .map(short_type_name)
+            .collect::<Vec<_>>();
+        type_names.sort_unstable();
+
+        let mut error = crate::Error::missing_dependency::<D>();
+        error.message = format!(
+            "Missing dependency: {:?}, have: [{}]",
+            short_type_name(std::any::type_name::<D>()),
+            type_names.join(", ")
+        );
+
+        error
+    }
+
     /// Updates a resource.
     pub fn update<R: Clone + Send + Sync + 'static>(
         &mut self,
@@ -164,13 +228,36 @@ impl Injector {
 
                 Ok(())
             }
-            Entry::Vacant(_) => Err(crate::Error::missing_dependency::<R>()),
+            Entry::Vacant(_) => Err(self.missing_dependency::<R>()),
         }
     }
 }
 
+/// Removes the module paths from a type name.
+///
+/// Ex: `alloc::vec::Vec<alloc::string::String>` becomes `Vec<String>`.
+fn short_type_name(type_name: &str) -> String {
+    let mut short_name = String::with_capacity(type_name.len());
+    let mut segment_start = 0;
+
+    for (i, char) in type_name.char_indices() {
+        if matches!(
+            char,
+            '<' | '>' | ',' | ' ' | '(' | ')' | '[' | ']' | '&' | ';'
+        ) {
+            let segment = &type_name[segment_start..i];
+            short_name.push_str(segment.rsplit("::").next().unwrap_or(segment));
+            short_name.push(char);
+            segment_start = i + 1;
+        }
+    }
+    let segment = &type_name[segment_start..];
+    short_name.push_str(segment.rsplit("::").next().unwrap_or(segment));
+
+    short_name
+}
+
 /// A resource.
-#[allow(dead_code)]
 #[derive(Clone, Debug)]
 pub struct Resource {
     type_name: &'static str,
@@ -224,7 +311,7 @@ macro_rules! impl_handler {
                 $(
                     let $params = std::borrow::Borrow::<$params>::borrow(match injector.take() {
                         Some(ref value) => value,
-                        None => return Err(format!("Missing dependency: {:?}", stringify!($params)).into()),
+                        None => return Err(injector.missing_dependency::<$params>().into()),
                     })
                     .clone();
                 )*
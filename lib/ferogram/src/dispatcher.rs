@@ -8,10 +8,34 @@
 
 //! Dispatcher module.
 
+use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
 use grammers_client::{types::Chat, Client, Update};
 use tokio::sync::broadcast::Sender;
 
-use crate::{di, filters::Command, middleware::MiddlewareStack, Context, Plugin, Result, Router};
+use crate::{
+    di,
+    error::Error,
+    event::{Event, EventBus},
+    filters::Command,
+    flags::FeatureFlags,
+    history::MessageHistory,
+    idempotency::Idempotency,
+    jobs::JobQueue,
+    logging,
+    middleware::MiddlewareStack,
+    outbox::Outbox,
+    shedding::LoadShedder,
+    slow_mode::SlowMode,
+    utils::{levenshtein, ChatLock, Debouncer},
+    Cache, ChatMigrations, ChatSettings, CommandAliases, Context, CorrelationId, Plugin,
+    PrefixProvider, Result, Router,
+};
 
 /// A dispatcher.
 ///
@@ -28,9 +52,43 @@ pub struct Dispatcher {
     middlewares: MiddlewareStack,
     /// The update sender.
     pub(crate) upd_sender: Sender<Update>,
+    /// The event bus.
+    pub(crate) event_bus: EventBus,
+    /// The slow mode send queue, shared by the contexts.
+    pub(crate) slow_mode: SlowMode,
+    /// The per-chat locks, shared by the handlers.
+    chat_lock: ChatLock,
+    /// The debouncer of the edits, shared by the contexts.
+    pub(crate) debouncer: Debouncer,
+    /// The cache of the peers, shared by the contexts.
+    pub(crate) cache: Option<Cache>,
+    /// The group migrations to supergroup.
+    migrations: ChatMigrations,
+    /// The per-chat settings.
+    chat_settings: Option<ChatSettings>,
+    /// The provider of the per-chat command prefixes.
+    prefix_provider: Option<Arc<dyn PrefixProvider>>,
+    /// The feature flags.
+    feature_flags: Option<FeatureFlags>,
+    /// The load shedder of the handlers.
+    load_shedder: Option<LoadShedder>,
+    /// The command aliases.
+    aliases: CommandAliases,
+    /// The queue of delayed jobs.
+    pub(crate) job_queue: Option<JobQueue>,
+    /// The outbox of the reliable messages.
+    pub(crate) outbox: Option<Outbox>,
+    /// The cache of the last messages.
+    pub(crate) history: Option<MessageHistory>,
+    /// The record of the messages sent once.
+    pub(crate) idempotency: Option<Idempotency>,
+    /// The dispatch statistics.
+    stats: Arc<Mutex<DispatchStats>>,
 
     /// Whether allow the client to handle updates from itself.
     allow_from_self: bool,
+    /// Whether suggest similar commands for the unknown ones.
+    suggest_commands: bool,
 }
 
 impl Dispatcher {
@@ -47,7 +105,39 @@ impl Dispatcher {
     /// # }
     /// ```
     pub fn router<R: FnOnce(Router) -> Router + 'static>(mut self, router: R) -> Self {
-        let router = router(Router::default());
+        let mut router = router(Router::default());
+        router.attach(&self.services());
+
+        self.routers.push(router);
+
+        self
+    }
+
+    /// Attachs a new router with the given priority.
+    ///
+    /// Routers and plugins with higher priority run first, regardless of the
+    /// registration order. The ones with the same priority keep the registration
+    /// order, with routers before plugins. The default priority is `0`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let dispatcher = unimplemented!();
+    /// let dispatcher = dispatcher.router_with_priority(100, |router| {
+    ///     router
+    /// });
+    /// # }
+    /// ```
+    pub fn router_with_priority<R: FnOnce(Router) -> Router + 'static>(
+        mut self,
+        priority: i32,
+        router: R,
+    ) -> Self {
+        let mut router = router(Router::default());
+        router.priority = priority;
+        router.attach(&self.services());
+
         self.routers.push(router);
 
         self
@@ -129,6 +219,26 @@ impl Dispatcher {
         self
     }
 
+    /// Whether reply to the messages which look like a command, but are not
+    /// handled, with the most similar registered command.
+    ///
+    /// Ex: `/strat` is replied with "Did you mean /start?".
+    ///
+    /// By default, the unknown commands are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+    /// let dispatcher = dispatcher.suggest_commands(true);
+    /// # }
+    /// ```
+    pub fn suggest_commands(mut self, value: bool) -> Self {
+        self.suggest_commands = value;
+        self
+    }
+
     /// Attachs a new plugin.
     ///
     /// A plugin is a collection of routers.
@@ -141,11 +251,272 @@ impl Dispatcher {
     /// let dispatcher = dispatcher.plugin(Plugin::default());
     /// # }
     /// ```
-    pub fn plugin(mut self, plugin: Plugin) -> Self {
+    pub fn plugin(mut self, mut plugin: Plugin) -> Self {
+        plugin.router.attach(&self.services());
+
         self.plugins.push(plugin);
         self
     }
 
+    /// Attachs the per-chat settings.
+    ///
+    /// The settings are injected in the handlers and the commands use the prefix
+    /// of the chat, when set, unless another [`PrefixProvider`] is attached.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let dispatcher = unimplemented!();
+    /// use ferogram::{ChatSettings, MemoryStorage};
+    ///
+    /// let dispatcher = dispatcher.chat_settings(ChatSettings::new(MemoryStorage::default()));
+    /// # }
+    /// ```
+    pub fn chat_settings(mut self, settings: ChatSettings) -> Self {
+        if self.prefix_provider.is_none() {
+            self = self.prefix_provider(settings.clone());
+        }
+
+        self.chat_settings = Some(settings);
+        self
+    }
+
+    /// Attachs the provider of the per-chat command prefixes.
+    ///
+    /// The commands consult it when checking the updates, falling back to their
+    /// static prefixes when it returns `None`. It is also injected in the handlers,
+    /// as `Arc<dyn PrefixProvider>`. Applies to the routers and plugins added
+    /// before and after it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+    /// # let provider = unimplemented!();
+    /// let dispatcher = dispatcher.prefix_provider(provider);
+    /// # }
+    /// ```
+    pub fn prefix_provider<P: PrefixProvider>(mut self, provider: P) -> Self {
+        self.prefix_provider = Some(Arc::new(provider));
+        self.attach_services();
+
+        self
+    }
+
+    /// Attachs the feature flags.
+    ///
+    /// The flags are consulted by [`crate::filter::feature`] and injected in the
+    /// handlers. Applies to the routers and plugins added before and after it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+    /// use ferogram::{flags::FeatureFlags, MemoryStorage};
+    ///
+    /// let dispatcher = dispatcher.feature_flags(FeatureFlags::new(MemoryStorage::default()));
+    /// # }
+    /// ```
+    pub fn feature_flags(mut self, flags: FeatureFlags) -> Self {
+        self.feature_flags = Some(flags);
+        self.attach_services();
+
+        self
+    }
+
+    /// Attachs the chat migrations, like the ones loaded with [`ChatMigrations::load`].
+    ///
+    /// The migrations are consulted by [`crate::filter::id`] and injected in the
+    /// handlers. Applies to the routers and plugins added before and after it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+    /// use ferogram::{ChatMigrations, MemoryStorage};
+    ///
+    /// let migrations = ChatMigrations::load(MemoryStorage::default()).await?;
+    /// let dispatcher = dispatcher.chat_migrations(migrations);
+    /// # }
+    /// ```
+    pub fn chat_migrations(mut self, migrations: ChatMigrations) -> Self {
+        self.migrations = migrations;
+        self.attach_services();
+
+        self
+    }
+
+    /// Returns the services consulted by the filters, see [`crate::Filter::attach`].
+    fn services(&self) -> di::Injector {
+        let mut services = di::Injector::default();
+        services.insert(self.migrations.clone());
+        if let Some(provider) = self.prefix_provider.as_ref() {
+            services.insert(provider.clone());
+        }
+        if let Some(flags) = self.feature_flags.as_ref() {
+            services.insert(flags.clone());
+        }
+
+        services
+    }
+
+    /// Attachs the services to the filters of the routers and plugins.
+    fn attach_services(&mut self) {
+        let services = self.services();
+
+        self.routers
+            .iter_mut()
+            .for_each(|router| router.attach(&services));
+        self.plugins
+            .iter_mut()
+            .for_each(|plugin| plugin.router.attach(&services));
+    }
+
+    /// Attachs a load shedder, which rejects the low priority handlers while
+    /// overloaded.
+    ///
+    /// The shedder is also injected in the handlers.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+    /// use ferogram::shedding::LoadShedder;
+    ///
+    /// let dispatcher = dispatcher.load_shedder(LoadShedder::new().max_in_flight(500));
+    /// # }
+    /// ```
+    pub fn load_shedder(mut self, shedder: LoadShedder) -> Self {
+        self.load_shedder = Some(shedder);
+        self
+    }
+
+    /// Adds an alias of the command, like `s` for `start`.
+    ///
+    /// The aliases are rewritten to the command before the filters run, so
+    /// `/s` is handled as `/start`. See [`Dispatcher::aliases`] to change them
+    /// while running.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+    /// let dispatcher = dispatcher.alias("s", "start").alias("iniciar", "start");
+    /// # }
+    /// ```
+    pub fn alias<A: ToString, C: ToString>(self, alias: A, command: C) -> Self {
+        self.aliases.add(alias, command);
+        self
+    }
+
+    /// Returns the command aliases, which can be changed while running.
+    ///
+    /// The aliases are also injected in the handlers.
+    pub fn aliases(&self) -> CommandAliases {
+        self.aliases.clone()
+    }
+
+    /// Attachs a queue of delayed jobs.
+    ///
+    /// The due jobs are executed while the client runs, and the queue is injected
+    /// in the handlers to push new ones.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+    /// # #[derive(Clone)]
+    /// # struct RemindJob;
+    /// use ferogram::{jobs::JobQueue, MemoryStorage};
+    ///
+    /// let dispatcher = dispatcher.job_queue(JobQueue::new(MemoryStorage::default()).job::<RemindJob>());
+    /// # }
+    /// ```
+    pub fn job_queue(mut self, queue: JobQueue) -> Self {
+        self.job_queue = Some(queue);
+        self
+    }
+
+    /// Attachs an outbox, which sends the messages of [`Context::send_reliable`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+    /// use ferogram::{outbox::Outbox, MemoryStorage};
+    ///
+    /// let dispatcher = dispatcher.outbox(Outbox::new(MemoryStorage::default()));
+    /// # }
+    /// ```
+    pub fn outbox(mut self, outbox: Outbox) -> Self {
+        self.outbox = Some(outbox);
+        self
+    }
+
+    /// Attachs a cache of the last messages, which keeps the previous text of the
+    /// edited messages for [`Context::previous_text`].
+    ///
+    /// The cache is also injected in the handlers.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+    /// use ferogram::history::MessageHistory;
+    ///
+    /// let dispatcher = dispatcher.message_history(MessageHistory::new(500));
+    /// # }
+    /// ```
+    pub fn message_history(mut self, history: MessageHistory) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Attachs a cache of the peers seen in the updates, shared by the contexts
+    /// and injected in the handlers.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+    /// use ferogram::Cache;
+    ///
+    /// let dispatcher = dispatcher.peer_cache(Cache::new(10_000));
+    /// # }
+    /// ```
+    pub fn peer_cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Attachs a record of the sent messages, which skips the messages of
+    /// [`Context::send_once`] already sent.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+    /// use ferogram::{idempotency::Idempotency, MemoryStorage};
+    ///
+    /// let dispatcher = dispatcher.idempotency(Idempotency::new(MemoryStorage::default()));
+    /// # }
+    /// ```
+    pub fn idempotency(mut self, idempotency: Idempotency) -> Self {
+        self.idempotency = Some(idempotency);
+        self
+    }
+
     /// Returns the commands from the routers and plugins.
     pub(crate) fn get_commands(&self) -> Vec<Command> {
         let mut commands = Vec::new();
@@ -160,6 +531,120 @@ impl Dispatcher {
         commands
     }
 
+    /// Returns the bot commands from the routers and plugins, paired with their descriptions.
+    ///
+    /// Commands registered more than once are only returned the first time.
+    pub(crate) fn bot_commands(&self) -> Vec<(String, String)> {
+        let mut commands = Vec::<(String, String)>::new();
+
+        for (name, description) in self
+            .get_commands()
+            .iter()
+            .flat_map(|command| command.bot_commands())
+        {
+            if !commands.iter().any(|(other, _)| *other == name) {
+                commands.push((name, description));
+            }
+        }
+
+        commands
+    }
+
+    /// Builds a help text listing the commands and their descriptions.
+    ///
+    /// One command per line, in the `/command - description` format.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+    /// let help = dispatcher.help_text();
+    /// # }
+    /// ```
+    pub fn help_text(&self) -> String {
+        self.bot_commands()
+            .into_iter()
+            .map(|(name, description)| format!("/{} - {}", name, description))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Returns the dispatch statistics, shared by the dispatcher clones.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+    /// let stats = dispatcher.stats();
+    /// println!("{} updates, {:?} on average", stats.updates, stats.average_time());
+    /// # }
+    /// ```
+    pub fn stats(&self) -> DispatchStats {
+        self.stats.lock().expect("Failed to lock the stats").clone()
+    }
+
+    /// Takes a snapshot of the runtime state, to be restored after a restart.
+    ///
+    /// Holds the failures and the disabled flag of each plugin. The feature flags,
+    /// the chat settings and the jobs already persist in their [`crate::Storage`],
+    /// and the conversations hold closures, which can't be saved.
+    ///
+    /// The dispatcher has no maintenance mode, throttling or state machine yet,
+    /// so their state is left for when they are added.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+    /// std::fs::write("dispatcher.snapshot", dispatcher.snapshot().to_string())?;
+    /// # }
+    /// ```
+    pub fn snapshot(&self) -> DispatcherSnapshot {
+        DispatcherSnapshot {
+            plugins: self
+                .plugins
+                .iter()
+                .map(|plugin| {
+                    let (failures, disabled) = plugin.health();
+
+                    PluginState {
+                        name: plugin.name().to_string(),
+                        failures,
+                        disabled,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Restores a snapshot taken with [`Dispatcher::snapshot`].
+    ///
+    /// Plugins are matched by name, the ones missing in the snapshot are kept as they are.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+    /// let snapshot = std::fs::read_to_string("dispatcher.snapshot")?.parse()?;
+    /// dispatcher.restore(&snapshot);
+    /// # }
+    /// ```
+    pub fn restore(&self, snapshot: &DispatcherSnapshot) {
+        for state in snapshot.plugins.iter() {
+            if let Some(plugin) = self
+                .plugins
+                .iter()
+                .find(|plugin| plugin.name() == state.name)
+            {
+                plugin.restore_health(state.failures, state.disabled);
+            }
+        }
+    }
+
     /// Handle the update sent by Telegram.
     ///
     /// Returns `Ok(())` if the update was handled.
@@ -173,20 +658,99 @@ impl Dispatcher {
     /// # }
     /// ```
     pub(crate) async fn handle_update(&mut self, client: &Client, update: &Update) -> Result<()> {
-        let mut injector = di::Injector::default();
+        let started_at = Instant::now();
+        let correlation_id = CorrelationId::current().unwrap_or_default();
+        log::debug!(target: logging::DISPATCHER, "Handling update {}", correlation_id);
 
-        let upd_receiver = self.upd_sender.subscribe();
-        let context = Context::with(client, update, upd_receiver);
-        injector.insert(context);
+        let result = correlation_id
+            .scope(self.dispatch_update(client, update))
+            .await;
 
-        self.upd_sender
-            .send(update.clone())
-            .expect("Failed to send update");
+        self.stats
+            .lock()
+            .expect("Failed to lock the stats")
+            .record(started_at.elapsed(), result.is_err());
+
+        result
+    }
+
+    /// Builds the injector of the handlers, with the context, the client and
+    /// the services of the dispatcher.
+    fn build_injector(&self, client: &Client, update: Option<&Update>) -> di::Injector {
+        let mut injector = di::Injector::default();
+
+        let context = match update {
+            Some(update) => Context::with(client, update, self.upd_sender.clone()),
+            None => Context::new(client, self.upd_sender.clone()),
+        };
+        injector.insert(
+            context
+                .with_slow_mode(&self.slow_mode)
+                .with_debouncer(&self.debouncer)
+                .with_cache(self.cache.as_ref())
+                .with_outbox(self.outbox.as_ref())
+                .with_history(self.history.as_ref())
+                .with_idempotency(self.idempotency.as_ref())
+                .with_correlation_id(CorrelationId::current()),
+        );
 
         injector.insert(client.clone());
-        injector.insert(update.clone());
+        if let Some(update) = update {
+            injector.insert(update.clone());
+        }
+        if let Some(correlation_id) = CorrelationId::current() {
+            injector.insert(correlation_id);
+        }
+        injector.insert(self.event_bus.clone());
+        injector.insert(self.chat_lock.clone());
+        if let Some(cache) = self.cache.as_ref() {
+            injector.insert(cache.clone());
+        }
+        injector.insert(self.migrations.clone());
+        injector.insert(self.aliases.clone());
+        if let Some(settings) = self.chat_settings.as_ref() {
+            injector.insert(settings.clone());
+        }
+        if let Some(provider) = self.prefix_provider.as_ref() {
+            injector.insert(provider.clone());
+        }
+        if let Some(flags) = self.feature_flags.as_ref() {
+            injector.insert(flags.clone());
+        }
+        if let Some(shedder) = self.load_shedder.as_ref() {
+            injector.insert(shedder.clone());
+        }
+        if let Some(queue) = self.job_queue.as_ref() {
+            injector.insert(queue.clone());
+        }
+        if let Some(history) = self.history.as_ref() {
+            injector.insert(history.clone());
+        }
         injector.extend(&mut self.injector.clone());
 
+        injector
+    }
+
+    /// Sends the update to the middlewares, routers and plugins.
+    async fn dispatch_update(&mut self, client: &Client, update: &Update) -> Result<()> {
+        if let Some(history) = self.history.as_ref() {
+            history.record(update);
+        }
+
+        let update = match self.aliases.rewrite(update) {
+            Some(update) => Cow::Owned(update),
+            None => Cow::Borrowed(update),
+        };
+        let update = update.as_ref();
+        if let Err(e) = self.migrations.record(update).await {
+            log::error!(target: logging::DISPATCHER, "Error saving the chat migration: {:?}", e);
+        }
+
+        // Fails only when no context is waiting for updates.
+        let _ = self.upd_sender.send(update.clone());
+
+        let mut injector = self.build_injector(client, Some(update));
+
         if !self.allow_from_self {
             match update {
                 Update::NewMessage(message) | Update::MessageEdited(message) => {
@@ -221,31 +785,325 @@ impl Dispatcher {
             };
         }
 
-        for router in self.routers.iter_mut() {
-            match router
-                .handle_update(client, update, &mut injector, self.middlewares.clone())
-                .await
-            {
+        if let Some(cache) = self.cache.as_ref() {
+            cache.record(update);
+        }
+
+        let mut routes = self
+            .routers
+            .iter_mut()
+            .map(Route::Router)
+            .chain(
+                self.plugins
+                    .iter_mut()
+                    .filter(|plugin| !plugin.is_disabled())
+                    .map(Route::Plugin),
+            )
+            .collect::<Vec<_>>();
+        routes.sort_by_key(|route| Reverse(route.priority()));
+
+        for route in routes {
+            let handled = match route {
+                Route::Router(router) => {
+                    router
+                        .handle_update(client, update, &mut injector, self.middlewares.clone())
+                        .await
+                }
+                Route::Plugin(plugin) => {
+                    plugin
+                        .handle_update(
+                            client,
+                            update,
+                            &mut injector,
+                            self.middlewares.clone(),
+                            &self.event_bus,
+                        )
+                        .await
+                }
+            };
+
+            match handled {
                 Ok(false) => continue,
                 Ok(true) => return Ok(()),
                 Err(e) => return Err(e),
             }
         }
 
-        for plugin in self.plugins.iter_mut() {
-            match plugin
-                .router
-                .handle_update(client, update, &mut injector, self.middlewares.clone())
-                .await
+        if self.suggest_commands {
+            self.suggest_command(update).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Replies to an unhandled message which looks like a command with the most
+    /// similar registered command, if any is close enough.
+    async fn suggest_command(&self, update: &Update) -> Result<()> {
+        let Update::NewMessage(message) = update else {
+            return Ok(());
+        };
+        let text = message.text();
+
+        let mut best = None::<(usize, String)>;
+        for command in self.get_commands() {
+            let mut prefixes = command.prefixes.clone();
+            if let Some(provider) = command.prefix_provider.as_ref() {
+                if let Some(chat_prefixes) = provider.prefixes(message.chat().id()).await {
+                    if !chat_prefixes.is_empty() {
+                        prefixes = chat_prefixes.iter().map(|p| regex::escape(p)).collect();
+                    }
+                }
+            }
+
+            let Some(prefix) = regex::Regex::new(&format!("^(?:{})", prefixes.join("|")))
+                .ok()
+                .and_then(|pat| pat.find(text))
+            else {
+                continue;
+            };
+
+            let word = text[prefix.end()..]
+                .split_whitespace()
+                .next()
+                .unwrap_or_default();
+            let (name, mention) = match word.split_once('@') {
+                Some((name, mention)) => (name, Some(mention)),
+                None => (word, None),
+            };
+            if name.is_empty() {
+                continue;
+            }
+
+            // Skips the commands sent to other bots.
+            if let (Some(mention), Some(username)) =
+                (mention, command.username.lock().await.as_deref())
             {
-                Ok(false) => continue,
-                Ok(true) => return Ok(()),
-                Err(e) => return Err(e),
+                if !mention.eq_ignore_ascii_case(username) {
+                    continue;
+                }
+            }
+
+            let name = name.to_lowercase();
+            for (candidate, _) in command.bot_commands() {
+                let distance = levenshtein(&name, &candidate.to_lowercase());
+                let max_distance = (candidate.chars().count() / 3).max(1);
+
+                if distance > 0
+                    && distance <= max_distance
+                    && best.as_ref().is_none_or(|(best, _)| distance < *best)
+                {
+                    best = Some((distance, format!("{}{}", prefix.as_str(), candidate)));
+                }
             }
         }
 
+        if let Some((_, suggestion)) = best {
+            message
+                .reply(format!("Did you mean {}?", suggestion))
+                .await?;
+        }
+
         Ok(())
     }
+
+    /// Handle the event published in the event bus.
+    ///
+    /// Sends the event to every handler subscribed to it, in the routers and plugins.
+    pub(crate) async fn handle_event(&mut self, client: &Client, event: &Event) -> Result<()> {
+        let injector = self.build_injector(client, None);
+
+        let mut routers = self
+            .routers
+            .iter_mut()
+            .chain(
+                self.plugins
+                    .iter_mut()
+                    .filter(|plugin| !plugin.is_disabled())
+                    .map(|plugin| &mut plugin.router),
+            )
+            .collect::<Vec<_>>();
+        routers.sort_by_key(|router| Reverse(router.priority));
+
+        for router in routers {
+            router.handle_event(event, &injector).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A router or a plugin, sorted by priority when dispatching.
+enum Route<'a> {
+    Router(&'a mut Router),
+    Plugin(&'a mut Plugin),
+}
+
+impl Route<'_> {
+    /// Returns the priority of the router.
+    fn priority(&self) -> i32 {
+        match self {
+            Self::Router(router) => router.priority,
+            Self::Plugin(plugin) => plugin.router.priority,
+        }
+    }
+}
+
+/// A snapshot of the runtime state of a [`Dispatcher`], see [`Dispatcher::snapshot`].
+///
+/// Converted to and parsed from a text, one plugin per line.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DispatcherSnapshot {
+    /// The state of the plugins.
+    pub plugins: Vec<PluginState>,
+}
+
+/// The runtime state of a plugin.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PluginState {
+    /// The name of the plugin.
+    pub name: String,
+    /// How many times the plugin failed.
+    pub failures: usize,
+    /// Whether the plugin is disabled.
+    pub disabled: bool,
+}
+
+impl std::fmt::Display for DispatcherSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for state in self.plugins.iter() {
+            writeln!(f, "{} {} {}", state.failures, state.disabled, state.name)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for DispatcherSnapshot {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid =
+            |line: &str| Error::invalid_input(format!("Invalid snapshot line: {:?}", line));
+
+        let plugins = s
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let mut parts = line.splitn(3, ' ');
+
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some(failures), Some(disabled), Some(name)) => Ok(PluginState {
+                        name: name.to_string(),
+                        failures: failures.parse().map_err(|_| invalid(line))?,
+                        disabled: disabled.parse().map_err(|_| invalid(line))?,
+                    }),
+                    _ => Err(invalid(line)),
+                }
+            })
+            .collect::<std::result::Result<_, _>>()?;
+
+        Ok(Self { plugins })
+    }
+}
+
+/// Dispatch statistics.
+///
+/// Measures the time spent routing and handling the updates, including the
+/// middlewares, filters and endpoints.
+#[derive(Clone, Debug, Default)]
+pub struct DispatchStats {
+    /// How many updates were dispatched.
+    pub updates: u64,
+    /// How many updates returned an error.
+    pub errors: u64,
+    /// The total time spent dispatching the updates.
+    pub total_time: Duration,
+    /// The longest time spent dispatching an update.
+    pub max_time: Duration,
+}
+
+impl DispatchStats {
+    /// Returns the average time spent dispatching an update.
+    pub fn average_time(&self) -> Duration {
+        if self.updates == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_nanos((self.total_time.as_nanos() / self.updates as u128) as u64)
+        }
+    }
+
+    /// Records a dispatched update.
+    fn record(&mut self, time: Duration, is_err: bool) {
+        self.updates += 1;
+        if is_err {
+            self.errors += 1;
+        }
+
+        self.total_time += time;
+        self.max_time = self.max_time.max(time);
+    }
+}
+
+/// A cloneable handle to a [`Dispatcher`].
+///
+/// Feeds synthetic updates through the middlewares, routers and plugins, as if
+/// they were sent by Telegram. The clones share the dispatcher, so the updates
+/// fed by them are handled one at a time.
+#[derive(Clone)]
+pub struct DispatcherHandle {
+    /// The inner grammers' `Client` instance.
+    client: Client,
+    /// The dispatcher.
+    dispatcher: Arc<tokio::sync::Mutex<Dispatcher>>,
+}
+
+impl DispatcherHandle {
+    /// Creates a new handle.
+    pub(crate) fn new(client: &Client, dispatcher: &Dispatcher) -> Self {
+        Self {
+            client: client.clone(),
+            dispatcher: Arc::new(tokio::sync::Mutex::new(dispatcher.clone())),
+        }
+    }
+
+    /// Dispatches the update.
+    ///
+    /// Returns the error returned by the handler, if any, instead of sending it
+    /// to the global error handler.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let handle: ferogram::DispatcherHandle = unimplemented!();
+    /// # let update = unimplemented!();
+    /// handle.feed(update).await?;
+    /// # }
+    /// ```
+    pub async fn feed(&self, update: Update) -> Result<()> {
+        self.dispatcher
+            .lock()
+            .await
+            .handle_update(&self.client, &update)
+            .await
+    }
+
+    /// Returns the dispatch statistics.
+    ///
+    /// Includes the updates sent by Telegram after the client starts running.
+    pub async fn stats(&self) -> DispatchStats {
+        self.dispatcher.lock().await.stats()
+    }
+
+    /// Takes a snapshot of the runtime state, see [`Dispatcher::snapshot`].
+    pub async fn snapshot(&self) -> DispatcherSnapshot {
+        self.dispatcher.lock().await.snapshot()
+    }
+
+    /// Restores a snapshot, see [`Dispatcher::restore`].
+    pub async fn restore(&self, snapshot: &DispatcherSnapshot) {
+        self.dispatcher.lock().await.restore(snapshot);
+    }
 }
 
 impl Default for Dispatcher {
@@ -258,16 +1116,35 @@ impl Default for Dispatcher {
             injector: di::Injector::default(),
             middlewares: MiddlewareStack::new(),
             upd_sender,
+            event_bus: EventBus::default(),
+            slow_mode: SlowMode::default(),
+            chat_lock: ChatLock::default(),
+            debouncer: Debouncer::default(),
+            cache: None,
+            migrations: ChatMigrations::default(),
+            chat_settings: None,
+            prefix_provider: None,
+            feature_flags: None,
+            load_shedder: None,
+            aliases: CommandAliases::default(),
+            job_queue: None,
+            outbox: None,
+            history: None,
+            idempotency: None,
+            stats: Arc::new(Mutex::new(DispatchStats::default())),
 
             allow_from_self: false,
+            suggest_commands: false,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use grammers_client::grammers_tl_types as tl;
+
     use super::*;
-    use crate::handler;
+    use crate::{filters, handler, Filter};
 
     #[test]
     fn test_dispatcher() {
@@ -277,4 +1154,140 @@ mod tests {
                 router.register(handler::then(|_: Client, _: Update| async { Ok(()) }))
             });
     }
+
+    #[tokio::test]
+    async fn test_dispatch_without_waiter() {
+        let client = crate::Client::bot(std::env::var("BOT_TOKEN").unwrap_or_default())
+            .api_id(
+                std::env::var("API_ID")
+                    .unwrap_or("123456789".to_string())
+                    .parse::<i32>()
+                    .unwrap(),
+            )
+            .api_hash(std::env::var("API_HASH").unwrap_or_default())
+            .build()
+            .await
+            .unwrap();
+
+        let mut dispatcher = Dispatcher::default().router(|router| {
+            router.register(handler::then(|_: Client, _: Update| async { Ok(()) }))
+        });
+        let update = Update::Raw(tl::enums::Update::MessageId(tl::types::UpdateMessageId {
+            id: 1,
+            random_id: 1,
+        }));
+
+        assert!(dispatcher
+            .handle_update(client.inner(), &update)
+            .await
+            .is_ok());
+    }
+
+    #[test]
+    fn test_average_time() {
+        let stats = DispatchStats {
+            updates: u32::MAX as u64 + 1,
+            total_time: Duration::from_secs(u32::MAX as u64 + 1),
+            ..Default::default()
+        };
+
+        assert_eq!(stats.average_time(), Duration::from_secs(1));
+        assert_eq!(DispatchStats::default().average_time(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_help_text() {
+        let dispatcher = Dispatcher::default()
+            .router(|router| {
+                router
+                    .register(handler::new_message(
+                        filters::command("start").description("Start the bot."),
+                    ))
+                    .register(handler::new_message(
+                        filters::commands(&["help", "h"]).and(filters::private),
+                    ))
+                    .register(handler::new_message(filters::not(
+                        filters::command("ban").description("Ban a user."),
+                    )))
+            })
+            .plugin(Plugin::builder().build().handler(handler::new_message(
+                filters::command("start").description("Duplicated."),
+            )));
+
+        assert_eq!(
+            dispatcher.help_text(),
+            "/start - Start the bot.\n/help - help"
+        );
+    }
+
+    #[test]
+    fn test_aliases() {
+        let dispatcher = Dispatcher::default().alias("s", "start");
+
+        let aliases = dispatcher.aliases();
+        assert_eq!(aliases.resolve("S"), Some(String::from("start")));
+
+        aliases.add("iniciar", "start");
+        aliases.remove("s");
+        assert_eq!(dispatcher.aliases().resolve("s"), None);
+        assert_eq!(
+            dispatcher.aliases().resolve("iniciar"),
+            Some(String::from("start"))
+        );
+    }
+
+    #[test]
+    fn test_plugin_command_prefix() {
+        let stats = || handler::new_message(filters::command("stats").description("Stats."));
+
+        let dispatcher = Dispatcher::default()
+            .plugin(
+                Plugin::builder()
+                    .build()
+                    .command_prefix("a")
+                    .handler(stats())
+                    .handler(handler::new_message(filters::not(
+                        filters::command("reset").description("Reset."),
+                    ))),
+            )
+            .plugin(
+                Plugin::builder()
+                    .build()
+                    .handler(stats())
+                    .command_prefix("b"),
+            );
+
+        assert_eq!(
+            dispatcher.help_text(),
+            "/a_stats - Stats.\n/b_stats - Stats."
+        );
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let plugin = || Plugin::builder().name("Stats plugin").build();
+
+        let dispatcher = Dispatcher::default().plugin(plugin());
+        dispatcher.restore(&DispatcherSnapshot {
+            plugins: vec![PluginState {
+                name: "Stats plugin".to_string(),
+                failures: 3,
+                disabled: true,
+            }],
+        });
+
+        let snapshot = dispatcher.snapshot();
+        assert_eq!(snapshot.to_string(), "3 true Stats plugin\n");
+
+        let restored = Dispatcher::default().plugin(plugin());
+        restored.restore(&snapshot.to_string().parse().unwrap());
+        assert_eq!(restored.snapshot(), snapshot);
+        assert!(matches!(
+            "x true Stats plugin".parse::<DispatcherSnapshot>(),
+            Err(Error {
+                kind: crate::error::ErrorKind::InvalidInput,
+                ..
+            })
+        ));
+    }
 }
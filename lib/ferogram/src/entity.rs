@@ -0,0 +1,188 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Entity module.
+
+use grammers_client::grammers_tl_types as tl;
+
+use crate::utils::utf16_slice;
+
+/// A message entity, with its text resolved.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// # let ctx: ferogram::Context = unimplemented!();
+/// use ferogram::EntityKind;
+///
+/// for entity in ctx.entities().await {
+///     if let EntityKind::TextUrl { url } = entity.kind {
+///         println!("{} links to {}", entity.text, url);
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entity {
+    /// The kind of the entity.
+    pub kind: EntityKind,
+    /// The offset in UTF-16 code units.
+    pub offset: usize,
+    /// The length in UTF-16 code units.
+    pub length: usize,
+    /// The text covered by the entity.
+    pub text: String,
+}
+
+/// The kind of a message entity.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EntityKind {
+    /// A `@username` mention.
+    Mention,
+    /// A `#hashtag`.
+    Hashtag,
+    /// A `$USD` cashtag.
+    Cashtag,
+    /// A `/command`.
+    BotCommand,
+    /// An URL.
+    Url,
+    /// An email address.
+    Email,
+    /// A phone number.
+    Phone,
+    /// A bank card number.
+    BankCard,
+    /// A bold text.
+    Bold,
+    /// An italic text.
+    Italic,
+    /// An underlined text.
+    Underline,
+    /// A strikethrough text.
+    Strike,
+    /// A spoiler.
+    Spoiler,
+    /// An inline code.
+    Code,
+    /// A code block.
+    Pre {
+        /// The language of the code, may be empty.
+        language: String,
+    },
+    /// A text link.
+    TextUrl {
+        /// The URL of the link.
+        url: String,
+    },
+    /// A mention of an user without username.
+    MentionName {
+        /// The ID of the user.
+        user_id: i64,
+    },
+    /// A custom emoji.
+    CustomEmoji {
+        /// The ID of the emoji's document.
+        document_id: i64,
+    },
+    /// A block quotation.
+    Blockquote {
+        /// Whether the quotation is collapsed.
+        collapsed: bool,
+    },
+    /// An entity unknown to the library.
+    Unknown,
+}
+
+impl Entity {
+    /// Parses the raw entities of the text.
+    ///
+    /// Skips the entities whose range is not valid in the text.
+    pub fn parse(text: &str, entities: &[tl::enums::MessageEntity]) -> Vec<Self> {
+        entities
+            .iter()
+            .filter_map(|entity| Self::from_raw(text, entity))
+            .collect()
+    }
+
+    /// Resolves a raw entity in the text.
+    ///
+    /// Returns `None` if the range of the entity is not valid in the text.
+    pub fn from_raw(text: &str, entity: &tl::enums::MessageEntity) -> Option<Self> {
+        use tl::enums::MessageEntity as E;
+
+        let kind = match entity {
+            E::Mention(_) => EntityKind::Mention,
+            E::Hashtag(_) => EntityKind::Hashtag,
+            E::Cashtag(_) => EntityKind::Cashtag,
+            E::BotCommand(_) => EntityKind::BotCommand,
+            E::Url(_) => EntityKind::Url,
+            E::Email(_) => EntityKind::Email,
+            E::Phone(_) => EntityKind::Phone,
+            E::BankCard(_) => EntityKind::BankCard,
+            E::Bold(_) => EntityKind::Bold,
+            E::Italic(_) => EntityKind::Italic,
+            E::Underline(_) => EntityKind::Underline,
+            E::Strike(_) => EntityKind::Strike,
+            E::Spoiler(_) => EntityKind::Spoiler,
+            E::Code(_) => EntityKind::Code,
+            E::Pre(pre) => EntityKind::Pre {
+                language: pre.language.clone(),
+            },
+            E::TextUrl(text_url) => EntityKind::TextUrl {
+                url: text_url.url.clone(),
+            },
+            E::MentionName(mention) => EntityKind::MentionName {
+                user_id: mention.user_id,
+            },
+            E::CustomEmoji(emoji) => EntityKind::CustomEmoji {
+                document_id: emoji.document_id,
+            },
+            E::Blockquote(quote) => EntityKind::Blockquote {
+                collapsed: quote.collapsed,
+            },
+            E::Unknown(_) | E::InputMessageEntityMentionName(_) => EntityKind::Unknown,
+        };
+
+        let offset = entity.offset() as usize;
+        let length = entity.length() as usize;
+
+        Some(Self {
+            kind,
+            offset,
+            length,
+            text: utf16_slice(text, offset, length)?.to_string(),
+        })
+    }
+
+    /// Returns the URL of links, which is the text itself for plain URLs.
+    pub fn url(&self) -> Option<&str> {
+        match &self.kind {
+            EntityKind::Url => Some(&self.text),
+            EntityKind::TextUrl { url } => Some(url),
+            _ => None,
+        }
+    }
+
+    /// Returns the username of `@username` mentions, without the `@`.
+    pub fn username(&self) -> Option<&str> {
+        match self.kind {
+            EntityKind::Mention => Some(self.text.trim_start_matches('@')),
+            _ => None,
+        }
+    }
+
+    /// Returns the ID of the user of mentions without username.
+    pub fn user_id(&self) -> Option<i64> {
+        match self.kind {
+            EntityKind::MentionName { user_id } => Some(user_id),
+            _ => None,
+        }
+    }
+}
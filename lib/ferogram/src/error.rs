@@ -28,6 +28,14 @@ impl Error {
         }
     }
 
+    /// Creates a new cancelled error.
+    pub fn cancelled() -> Self {
+        Self {
+            kind: ErrorKind::Cancelled,
+            message: "The wait was cancelled".to_string(),
+        }
+    }
+
     /// Creates a new telegram error.
     pub fn telegram<E: ToString>(err: E) -> Self {
         Self {
@@ -44,6 +52,14 @@ impl Error {
         }
     }
 
+    /// Creates a new invalid input error.
+    pub fn invalid_input<E: ToString>(err: E) -> Self {
+        Self {
+            kind: ErrorKind::InvalidInput,
+            message: err.to_string(),
+        }
+    }
+
     /// Creates a new unknown error.
     pub fn unknown() -> Self {
         Self {
@@ -66,10 +82,14 @@ impl std::error::Error for Error {}
 pub enum ErrorKind {
     /// The time has run out.
     Timeout,
+    /// The wait was cancelled.
+    Cancelled,
     /// The error is from Telegram.
     Telegram,
     /// A dependency is missing.
     MissingDependency,
+    /// The input is not valid.
+    InvalidInput,
     /// The error is unknown.
     #[default]
     Unknown,
@@ -79,8 +99,10 @@ impl std::fmt::Display for ErrorKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Timeout => write!(f, "Timeout"),
+            Self::Cancelled => write!(f, "Cancelled"),
             Self::Telegram => write!(f, "Telegram"),
             Self::MissingDependency => write!(f, "Missing dependency"),
+            Self::InvalidInput => write!(f, "Invalid input"),
             Self::Unknown => write!(f, "Unknown"),
         }
     }
@@ -0,0 +1,80 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Event module.
+
+use std::any::TypeId;
+
+use tokio::sync::broadcast::{Receiver, Sender};
+
+use crate::di::Resource;
+
+/// An internal event bus.
+///
+/// Lets handlers and plugins communicate through typed events, which are sent
+/// to the handlers created with [`crate::handler::event`].
+///
+/// Injected in every handler.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// # let bus: ferogram::EventBus = unimplemented!();
+/// #[derive(Clone)]
+/// struct UserVerified(i64);
+///
+/// bus.publish(UserVerified(1234));
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct EventBus {
+    /// The event sender.
+    sender: Sender<Event>,
+}
+
+impl EventBus {
+    /// Publishes an event.
+    ///
+    /// Events published while the client isn't running are dropped.
+    pub fn publish<E: Clone + Send + Sync + 'static>(&self, event: E) {
+        let _ = self.sender.send(Event::new(event));
+    }
+
+    /// Subscribes to the events.
+    pub(crate) fn subscribe(&self) -> Receiver<Event> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        let (sender, _) = tokio::sync::broadcast::channel(100);
+
+        Self { sender }
+    }
+}
+
+/// A published event.
+#[derive(Clone, Debug)]
+pub(crate) struct Event {
+    /// The type of the event.
+    pub(crate) type_id: TypeId,
+    /// The event value.
+    pub(crate) resource: Resource,
+}
+
+impl Event {
+    /// Creates a new event.
+    pub(crate) fn new<E: Send + Sync + 'static>(event: E) -> Self {
+        Self {
+            type_id: TypeId::of::<E>(),
+            resource: Resource::new(event),
+        }
+    }
+}
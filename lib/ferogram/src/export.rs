@@ -0,0 +1,282 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Export module.
+
+use std::{
+    fmt::Write,
+    io::SeekFrom,
+    path::{Path, PathBuf},
+};
+
+use grammers_client::{
+    types::{Message, PackedChat},
+    Client,
+};
+use tokio::{
+    fs,
+    io::{AsyncSeekExt, AsyncWriteExt},
+};
+
+use crate::Result;
+
+/// The format of the exported history.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One JSON object per line.
+    #[default]
+    Ndjson,
+    /// A JSON array of objects.
+    Json,
+}
+
+/// History export options.
+///
+/// The messages are exported from the newest to the oldest. A checkpoint file,
+/// next to the output, keeps the last exported message, so an interrupted export
+/// continues from it when run again with the same options. The checkpoint is
+/// removed when the export completes.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// # let ctx: ferogram::Context = unimplemented!();
+/// use ferogram::{ExportFormat, ExportOptions};
+///
+/// let options = ExportOptions::new("history.json")
+///     .format(ExportFormat::Json)
+///     .media_dir("media")
+///     .limit(10_000);
+/// let count = ctx.export_history(options).await?;
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ExportOptions {
+    /// The output file.
+    path: PathBuf,
+    /// The format of the output.
+    format: ExportFormat,
+    /// The directory where the media is downloaded, if any.
+    media_dir: Option<PathBuf>,
+    /// The max messages exported.
+    limit: Option<usize>,
+    /// How many messages are exported between checkpoints.
+    checkpoint_every: usize,
+}
+
+impl ExportOptions {
+    /// Creates the options to export to the file.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            format: ExportFormat::default(),
+            media_dir: None,
+            limit: None,
+            checkpoint_every: 100,
+        }
+    }
+
+    /// Sets the format of the output, NDJSON by default.
+    pub fn format(mut self, format: ExportFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Downloads the media of the messages to the directory.
+    ///
+    /// The files are named by the message IDs.
+    pub fn media_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.media_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the max messages exported, counting the ones of previous runs.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Sets how many messages are exported between checkpoints, `100` by default.
+    pub fn checkpoint_every(mut self, count: usize) -> Self {
+        self.checkpoint_every = count.max(1);
+        self
+    }
+
+    /// Returns the path of the checkpoint file.
+    fn checkpoint_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".checkpoint");
+
+        path.into()
+    }
+}
+
+/// The progress of an export, saved as `last_id:count:len`.
+#[derive(Clone, Copy, Debug, Default)]
+struct Checkpoint {
+    /// The ID of the last exported message.
+    last_id: i32,
+    /// How many messages were exported.
+    count: usize,
+    /// The length of the output, which is truncated to it when resuming.
+    len: u64,
+}
+
+impl Checkpoint {
+    /// Loads the checkpoint of the options, if any.
+    async fn load(options: &ExportOptions) -> Option<Self> {
+        let value = fs::read_to_string(options.checkpoint_path()).await.ok()?;
+        let mut parts = value.trim().splitn(3, ':');
+
+        Some(Self {
+            last_id: parts.next()?.parse().ok()?,
+            count: parts.next()?.parse().ok()?,
+            len: parts.next()?.parse().ok()?,
+        })
+    }
+
+    /// Saves the checkpoint of the options.
+    async fn save(&self, options: &ExportOptions) -> Result<()> {
+        fs::write(
+            options.checkpoint_path(),
+            format!("{}:{}:{}", self.last_id, self.count, self.len),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Exports the history of the chat.
+///
+/// Returns how many messages were exported, counting the ones of previous runs.
+pub(crate) async fn export_history(
+    client: &Client,
+    chat: PackedChat,
+    options: &ExportOptions,
+) -> Result<usize> {
+    let checkpoint = Checkpoint::load(options).await;
+    let mut progress = checkpoint.unwrap_or_default();
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(checkpoint.is_none())
+        .open(&options.path)
+        .await?;
+
+    // Drops what was written after the checkpoint, which is exported again.
+    file.set_len(progress.len).await?;
+    file.seek(SeekFrom::End(0)).await?;
+
+    if checkpoint.is_none() && options.format == ExportFormat::Json {
+        file.write_all(b"[").await?;
+        progress.len += 1;
+    }
+    if let Some(dir) = options.media_dir.as_ref() {
+        fs::create_dir_all(dir).await?;
+    }
+
+    let mut iter = client.iter_messages(chat);
+    if progress.last_id > 0 {
+        iter = iter.offset_id(progress.last_id);
+    }
+
+    while options.limit.is_none_or(|limit| progress.count < limit) {
+        let Some(message) = iter.next().await? else {
+            break;
+        };
+
+        let media_path = match (options.media_dir.as_ref(), message.media()) {
+            (Some(dir), Some(media)) => {
+                let path = dir.join(message.id().to_string());
+                client.download_media(&media, &path).await?;
+
+                Some(path)
+            }
+            _ => None,
+        };
+
+        let object = to_json(&message, media_path.as_deref());
+        let line = match options.format {
+            ExportFormat::Ndjson => format!("{}\n", object),
+            ExportFormat::Json if progress.count == 0 => format!("\n{}", object),
+            ExportFormat::Json => format!(",\n{}", object),
+        };
+        file.write_all(line.as_bytes()).await?;
+
+        progress.len += line.len() as u64;
+        progress.last_id = message.id();
+        progress.count += 1;
+
+        if progress.count.is_multiple_of(options.checkpoint_every) {
+            file.flush().await?;
+            progress.save(options).await?;
+        }
+    }
+
+    if options.format == ExportFormat::Json {
+        file.write_all(b"\n]\n").await?;
+    }
+    file.flush().await?;
+
+    let _ = fs::remove_file(options.checkpoint_path()).await;
+
+    Ok(progress.count)
+}
+
+/// Serializes the message as a JSON object.
+fn to_json(message: &Message, media_path: Option<&Path>) -> String {
+    let mut object = format!(
+        "{{\"id\":{},\"date\":{}",
+        message.id(),
+        message.date().timestamp()
+    );
+
+    if let Some(sender) = message.sender() {
+        let _ = write!(object, ",\"sender_id\":{}", sender.id());
+    }
+    if let Some(reply_to) = message.reply_to_message_id() {
+        let _ = write!(object, ",\"reply_to\":{}", reply_to);
+    }
+
+    let _ = write!(object, ",\"text\":\"{}\"", escape_json(message.text()));
+
+    if let Some(path) = media_path {
+        let _ = write!(
+            object,
+            ",\"media\":\"{}\"",
+            escape_json(&path.to_string_lossy())
+        );
+    }
+
+    object.push('}');
+    object
+}
+
+/// Escapes the text to be inserted in a JSON string.
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for char in text.chars() {
+        match char {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            char if char.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", char as u32);
+            }
+            char => escaped.push(char),
+        }
+    }
+
+    escaped
+}
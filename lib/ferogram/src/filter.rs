@@ -15,7 +15,7 @@ use futures_util::Future;
 use grammers_client::{Client, Update};
 
 pub use crate::filters::*;
-use crate::Flow;
+use crate::{di::Injector, Flow};
 
 /// A filter.
 #[async_trait]
@@ -55,6 +55,25 @@ pub trait Filter: CloneFilter + Send + Sync + 'static {
         }
     }
 
+    /// Returns the commands held by the filter.
+    ///
+    /// Used to register the bot commands and to build the help text.
+    fn commands(&self) -> Vec<Command> {
+        Vec::new()
+    }
+
+    /// Prepends `prefix` to the names of the commands held by the filter.
+    ///
+    /// Used to namespace the commands of a plugin.
+    fn prefix_commands(&mut self, _prefix: &str) {}
+
+    /// Attachs the services of the dispatcher consulted by the filter.
+    ///
+    /// Holds the [`crate::ChatMigrations`], plus the `Arc<dyn PrefixProvider>` and the
+    /// [`crate::flags::FeatureFlags`] when attached to the dispatcher. Combinators must
+    /// forward it to their filters.
+    fn attach(&mut self, _services: &Injector) {}
+
     /// Returns the filter as a `Any` trait object.
     fn as_any(&self) -> &dyn Any
     where
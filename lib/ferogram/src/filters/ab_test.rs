@@ -0,0 +1,80 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::sync::Arc;
+
+use grammers_client::{types::Chat, Update};
+
+use crate::{flow, Filter};
+
+/// The variant assigned to an user by [`ab_test`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Variant {
+    /// The key of the test.
+    pub key: &'static str,
+    /// The bucket of the user, from `0` to `99`.
+    pub bucket: u8,
+}
+
+impl Variant {
+    /// Assigns the bucket of the user in the test.
+    ///
+    /// The same user always gets the same bucket for the same key.
+    pub fn assign(key: &'static str, user_id: i64) -> Self {
+        // FNV-1a, which is stable between builds, unlike the std hasher.
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in user_id.to_le_bytes().iter().chain(key.as_bytes()) {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+
+        Self {
+            key,
+            bucket: (hash % 100) as u8,
+        }
+    }
+
+    /// Whether the user is in the first `percent` percent of the users.
+    pub fn is_within(&self, percent: u8) -> bool {
+        self.bucket < percent
+    }
+}
+
+/// Pass if the sender is bucketed in the first `percent` percent of the users.
+///
+/// The users are bucketed by the hash of their id and the key, so the same
+/// user always gets the same variant. Use [`crate::filter::not`] to route the
+/// rest of the users.
+///
+/// Injects `Variant`: the assigned variant.
+pub fn ab_test(key: &'static str, percent: u8) -> impl Filter {
+    Arc::new(move |_, update| async move {
+        let user_id = match update {
+            Update::NewMessage(message) | Update::MessageEdited(message) => {
+                match message.sender() {
+                    Some(Chat::User(user)) => user.id(),
+                    _ => return flow::break_now(),
+                }
+            }
+            Update::CallbackQuery(query) => match query.sender() {
+                Chat::User(user) => user.id(),
+                _ => return flow::break_now(),
+            },
+            Update::InlineQuery(query) => query.sender().id(),
+            Update::InlineSend(inline_send) => inline_send.sender().id(),
+            _ => return flow::break_now(),
+        };
+
+        let variant = Variant::assign(key, user_id);
+        if variant.is_within(percent) {
+            flow::continue_with(variant)
+        } else {
+            flow::break_now()
+        }
+    })
+}
@@ -9,7 +9,7 @@
 use async_trait::async_trait;
 use grammers_client::{Client, Update};
 
-use crate::{flow, Filter, Flow};
+use crate::{di::Injector, filters::Command, Filter, Flow};
 
 #[derive(Clone)]
 pub struct And {
@@ -33,8 +33,28 @@ impl Filter for And {
 
                 return first_flow;
             }
+
+            // Keeps what the filter injected when breaking. Ex: `UsageError`.
+            return second_flow;
         }
 
-        flow::break_now()
+        first_flow
+    }
+
+    fn commands(&self) -> Vec<Command> {
+        let mut commands = self.first.commands();
+        commands.extend(self.second.commands());
+
+        commands
+    }
+
+    fn prefix_commands(&mut self, prefix: &str) {
+        self.first.prefix_commands(prefix);
+        self.second.prefix_commands(prefix);
+    }
+
+    fn attach(&mut self, services: &Injector) {
+        self.first.attach(services);
+        self.second.attach(services);
     }
 }
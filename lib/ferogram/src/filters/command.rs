@@ -6,19 +6,23 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use std::sync::Arc;
+use std::{collections::HashMap, str::FromStr, sync::Arc};
 
 use async_trait::async_trait;
-use grammers_client::{Client, Update};
+use grammers_client::{types::Chat, Client, Update};
 use tokio::sync::Mutex;
 
-use crate::{Filter, Flow};
+use crate::{di::Injector, flow, Filter, Flow, PrefixProvider};
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct Command {
     pub(crate) prefixes: Vec<String>,
     pub(crate) command: String,
     pub(crate) description: String,
+    pub(crate) allow_mention: bool,
+    pub(crate) prefix_provider: Option<Arc<dyn PrefixProvider>>,
+    pub(crate) args: Vec<Arg>,
+    pub(crate) usage_handler: Option<UsageHandler>,
 
     pub(crate) username: Arc<Mutex<Option<String>>>,
 }
@@ -41,6 +45,303 @@ impl Command {
         self.description = description.to_string();
         self
     }
+
+    /// Whether to also accept the command written after a bot mention, without prefix.
+    ///
+    /// Only applies to groups and channels. Ex: `@mybot start`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ferogram::filter::command;
+    ///
+    /// let command = command("start").allow_mention(true);
+    /// ```
+    pub fn allow_mention(mut self, value: bool) -> Self {
+        self.allow_mention = value;
+        self
+    }
+
+    /// Sets the arguments of the command.
+    ///
+    /// Each argument is a name, optionally followed by its kind, `:int` or `:float`.
+    /// A trailing `?` makes it optional and a trailing `...` makes it take the rest
+    /// of the text. When the arguments are missing or invalid, the filter doesn't
+    /// pass and the handler replies the usage of the command, or runs its error
+    /// handler with the [`UsageError`], if set.
+    ///
+    /// Injects `CommandArgs`: the parsed arguments.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ferogram::filter::command;
+    ///
+    /// let command = command("ban").args(&["user", "days:int?", "reason...?"])?;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an argument has an unknown kind.
+    pub fn args(mut self, schema: &[&str]) -> crate::Result<Self> {
+        self.args = schema
+            .iter()
+            .map(|spec| Arg::parse(spec))
+            .collect::<crate::Result<_>>()?;
+
+        Ok(self)
+    }
+
+    /// Sets the reply sent when the arguments are missing or invalid.
+    ///
+    /// Returning `None` doesn't reply. By default, replies the reason and the usage.
+    /// Not used when the handler has an error handler.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ferogram::filter::command;
+    ///
+    /// let command = command("ban")
+    ///     .args(&["user"])?
+    ///     .on_usage_error(|error| Some(format!("Try: {}", error.usage)));
+    /// ```
+    pub fn on_usage_error<F: Fn(&UsageError) -> Option<String> + Send + Sync + 'static>(
+        mut self,
+        handler: F,
+    ) -> Self {
+        self.usage_handler = Some(Arc::new(handler));
+        self
+    }
+
+    /// Returns the usage of the arguments. Ex: `<user> [reason...]`.
+    fn args_usage(&self) -> String {
+        self.args
+            .iter()
+            .map(Arg::usage)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Parses the arguments by the schema.
+    ///
+    /// Returns the reason if they are missing or invalid.
+    fn parse_args(&self, text: &str) -> Result<CommandArgs, String> {
+        let mut values = HashMap::new();
+        let mut rest = text.trim();
+
+        for arg in self.args.iter() {
+            if rest.is_empty() {
+                if arg.optional {
+                    continue;
+                }
+
+                return Err(format!("Missing argument <{}>.", arg.name));
+            }
+
+            let value = if arg.rest {
+                std::mem::take(&mut rest)
+            } else {
+                match rest.split_once(char::is_whitespace) {
+                    Some((value, remaining)) => {
+                        rest = remaining.trim_start();
+                        value
+                    }
+                    None => std::mem::take(&mut rest),
+                }
+            };
+
+            let is_valid = match arg.kind {
+                ArgKind::Text => true,
+                ArgKind::Int => value.parse::<i64>().is_ok(),
+                ArgKind::Float => value.parse::<f64>().is_ok(),
+            };
+            if !is_valid {
+                return Err(format!("Invalid argument <{}>: {}.", arg.name, value));
+            }
+
+            values.insert(arg.name.clone(), value.to_string());
+        }
+
+        if !rest.is_empty() {
+            return Err("Too many arguments.".to_string());
+        }
+
+        Ok(CommandArgs { values })
+    }
+
+    /// Prepends `prefix` and an underscore to the command names.
+    ///
+    /// Ex: `stats` becomes `prefix_stats`.
+    pub(crate) fn prefix(&mut self, prefix: &str) {
+        let (names, rest) = match self.command.split_once(' ') {
+            Some((names, rest)) => (names, Some(rest)),
+            None => (self.command.as_str(), None),
+        };
+
+        let names = names
+            .split("|")
+            .map(|name| format!("{}_{}", prefix, name))
+            .collect::<Vec<_>>()
+            .join("|");
+
+        self.command = match rest {
+            Some(rest) => format!("{} {}", names, rest),
+            None => names,
+        };
+    }
+
+    /// Returns the names to register as bot commands, paired with the description.
+    ///
+    /// Names with a single char are skipped and an empty description falls back
+    /// to the name, since Telegram rejects empty ones.
+    pub(crate) fn bot_commands(&self) -> Vec<(String, String)> {
+        self.command
+            .split("|")
+            .filter(|name| name.len() > 1)
+            .map(|name| {
+                let description = if self.description.is_empty() {
+                    name.to_string()
+                } else {
+                    self.description.clone()
+                };
+
+                (name.to_string(), description)
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Command")
+            .field("prefixes", &self.prefixes)
+            .field("command", &self.command)
+            .field("description", &self.description)
+            .field("allow_mention", &self.allow_mention)
+            .field("args", &self.args)
+            .field("username", &self.username)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The handler of the usage errors of a [`Command`].
+pub(crate) type UsageHandler = Arc<dyn Fn(&UsageError) -> Option<String> + Send + Sync>;
+
+/// The kind of a command argument.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ArgKind {
+    Text,
+    Int,
+    Float,
+}
+
+/// A command argument, from the schema of [`Command::args`].
+#[derive(Clone, Debug)]
+pub(crate) struct Arg {
+    name: String,
+    kind: ArgKind,
+    optional: bool,
+    rest: bool,
+}
+
+impl Arg {
+    /// Parses the spec of the argument. Ex: `days:int?`.
+    fn parse(spec: &str) -> crate::Result<Self> {
+        let (spec, optional) = match spec.strip_suffix('?') {
+            Some(spec) => (spec, true),
+            None => (spec, false),
+        };
+        let (spec, rest) = match spec.strip_suffix("...") {
+            Some(spec) => (spec, true),
+            None => (spec, false),
+        };
+        let (name, kind) = match spec.split_once(':') {
+            Some((name, "int")) => (name, ArgKind::Int),
+            Some((name, "float")) => (name, ArgKind::Float),
+            Some((_, kind)) => {
+                return Err(Box::new(crate::Error::invalid_input(format!(
+                    "Unknown argument kind: {}",
+                    kind
+                ))));
+            }
+            None => (spec, ArgKind::Text),
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            kind,
+            optional,
+            rest,
+        })
+    }
+
+    /// Returns the usage of the argument. Ex: `<user>`, `[reason...]`.
+    fn usage(&self) -> String {
+        let dots = if self.rest { "..." } else { "" };
+
+        if self.optional {
+            format!("[{}{}]", self.name, dots)
+        } else {
+            format!("<{}{}>", self.name, dots)
+        }
+    }
+}
+
+/// The arguments of a command, parsed by [`Command::args`].
+#[derive(Clone, Debug, Default)]
+pub struct CommandArgs {
+    /// The values by the argument names.
+    values: HashMap<String, String>,
+}
+
+impl CommandArgs {
+    /// Gets the argument, parsed.
+    ///
+    /// Returns `None` if the optional argument is missing or can't be parsed.
+    pub fn get<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.get_str(name).and_then(|value| value.parse().ok())
+    }
+
+    /// Gets the argument as sent.
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+}
+
+/// A command sent with missing or invalid arguments.
+///
+/// Injected when the [`Command`] filter doesn't pass because of the arguments.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UsageError {
+    /// Why the arguments are not valid.
+    pub reason: String,
+    /// The usage of the command. Ex: `/ban <user> [reason...]`.
+    pub usage: String,
+}
+
+impl std::fmt::Display for UsageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}\nUsage: {}", self.reason, self.usage)
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+/// The reply to a [`UsageError`], built by the usage handler of the [`Command`].
+#[derive(Clone, Debug)]
+pub(crate) struct UsageReply(pub(crate) String);
+
+/// The command matched by a [`Command`] filter.
+///
+/// Injected when the filter passes, so handlers registered with many commands
+/// can tell which one was sent.
+#[derive(Clone, Debug)]
+pub struct MatchedCommand {
+    /// The command name, lowercased, without prefix and mention.
+    pub name: String,
+    /// The text after the command, trimmed.
+    pub args: String,
 }
 
 #[async_trait]
@@ -56,24 +357,210 @@ impl Filter for Command {
             *username = me.username().map(|u| u.to_string());
         }
 
-        let mut pat = String::new();
-        if username.is_some() {
-            pat += &format!("{0}(@{1})?", splitted[0], username.as_deref().unwrap());
+        let mut pat = format!("(?P<name>{0})", splitted[0]);
+        if let Some(username) = username.as_deref() {
+            pat += &format!("(@{0})?", username);
         }
 
-        let pre_pat = format!("^({})(?i)", self.prefixes.join("|"));
-        if splitted.len() > 1 {
-            pat = format!(r"{0}({1} {2})($|\s)", pre_pat, pat, splitted[1..].join(" "));
+        let rest = if splitted.len() > 1 {
+            format!(" {}", splitted[1..].join(" "))
         } else {
-            pat = format!(r"{0}({1})($|\s)", pre_pat, pat);
+            String::new()
+        };
+
+        let mut prefixes = self.prefixes.clone();
+        if let (Some(provider), Update::NewMessage(message) | Update::MessageEdited(message)) =
+            (&self.prefix_provider, update)
+        {
+            if let Some(chat_prefixes) = provider.prefixes(message.chat().id()).await {
+                if !chat_prefixes.is_empty() {
+                    prefixes = chat_prefixes
+                        .iter()
+                        .map(|prefix| regex::escape(prefix))
+                        .collect();
+                }
+            }
         }
 
+        let pat = format!(r"^({0})(?i)({1}{2})($|\s)", prefixes.join("|"), pat, rest);
+
         match update {
             Update::NewMessage(message) | Update::MessageEdited(message) => {
-                regex::Regex::new(&pat).unwrap().is_match(message.text())
+                let text = message.text();
+
+                let mut captures = regex::Regex::new(&pat).unwrap().captures(text);
+                let mut prefix = captures
+                    .as_ref()
+                    .and_then(|captures| captures.get(1))
+                    .map(|prefix| prefix.as_str().to_string());
+                if captures.is_none()
+                    && self.allow_mention
+                    && !matches!(message.chat(), Chat::User(_))
+                {
+                    if let Some(username) = username.as_deref() {
+                        let mention_pat = format!(
+                            r"^(?i)@{0}\s+((?P<name>{1}){2})($|\s)",
+                            regex::escape(username),
+                            splitted[0],
+                            rest
+                        );
+
+                        captures = regex::Regex::new(&mention_pat).unwrap().captures(text);
+                        prefix = Some(format!("@{} ", username));
+                    }
+                }
+
+                match captures {
+                    Some(captures) => {
+                        let name = captures
+                            .name("name")
+                            .map_or("", |name| name.as_str())
+                            .to_lowercase();
+                        let end = captures.get(0).map_or(0, |all| all.end());
+                        let args = text[end..].trim().to_string();
+
+                        if self.args.is_empty() {
+                            return flow::continue_with(MatchedCommand { name, args });
+                        }
+
+                        match self.parse_args(&args) {
+                            Ok(command_args) => {
+                                let mut flow = flow::continue_with(MatchedCommand { name, args });
+                                flow.injector.insert(command_args);
+
+                                flow
+                            }
+                            Err(reason) => {
+                                let error = UsageError {
+                                    reason,
+                                    usage: format!(
+                                        "{}{} {}",
+                                        prefix.unwrap_or_default(),
+                                        name,
+                                        self.args_usage()
+                                    ),
+                                };
+                                let reply = match self.usage_handler.as_ref() {
+                                    Some(handler) => handler(&error),
+                                    None => Some(error.to_string()),
+                                };
+
+                                let mut flow = flow::break_with(error);
+                                if let Some(reply) = reply {
+                                    flow.inject(UsageReply(reply));
+                                }
+
+                                flow
+                            }
+                        }
+                    }
+                    None => flow::break_now(),
+                }
             }
-            _ => false,
+            _ => flow::break_now(),
         }
-        .into()
+    }
+
+    fn commands(&self) -> Vec<Command> {
+        vec![self.clone()]
+    }
+
+    fn prefix_commands(&mut self, prefix: &str) {
+        self.prefix(prefix);
+    }
+
+    fn attach(&mut self, services: &Injector) {
+        if let Some(provider) = services.get::<Arc<dyn PrefixProvider>>() {
+            self.prefix_provider = Some(provider.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::command;
+
+    #[test]
+    fn test_arg_parse() {
+        let arg = Arg::parse("days:int?").unwrap();
+        assert_eq!(arg.name, "days");
+        assert_eq!(arg.kind, ArgKind::Int);
+        assert!(arg.optional);
+        assert!(!arg.rest);
+
+        let arg = Arg::parse("reason...?").unwrap();
+        assert_eq!(arg.name, "reason");
+        assert_eq!(arg.kind, ArgKind::Text);
+        assert!(arg.optional);
+        assert!(arg.rest);
+
+        assert!(Arg::parse("user:bool").is_err());
+        assert!(command("ban").args(&["user", "days:date"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let command = command("ban")
+            .args(&["user", "days:int?", "reason...?"])
+            .unwrap();
+
+        let args = command.parse_args("john 7 spam and flood").unwrap();
+        assert_eq!(args.get_str("user"), Some("john"));
+        assert_eq!(args.get::<i64>("days"), Some(7));
+        assert_eq!(args.get_str("reason"), Some("spam and flood"));
+
+        let args = command.parse_args("john").unwrap();
+        assert_eq!(args.get_str("user"), Some("john"));
+        assert_eq!(args.get::<i64>("days"), None);
+
+        assert_eq!(
+            command.parse_args("").unwrap_err(),
+            "Missing argument <user>."
+        );
+        assert_eq!(
+            command.parse_args("john seven").unwrap_err(),
+            "Invalid argument <days>: seven."
+        );
+
+        let command = crate::filters::command("kick").args(&["user"]).unwrap();
+        assert_eq!(
+            command.parse_args("john doe").unwrap_err(),
+            "Too many arguments."
+        );
+    }
+
+    #[test]
+    fn test_usage() {
+        let command = command("ban")
+            .args(&["user", "days:int?", "reason...?"])
+            .unwrap();
+        assert_eq!(command.args_usage(), "<user> [days] [reason...]");
+
+        let error = UsageError {
+            reason: "Missing argument <user>.".to_string(),
+            usage: format!("/ban {}", command.args_usage()),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Missing argument <user>.\nUsage: /ban <user> [days] [reason...]"
+        );
+    }
+
+    #[test]
+    fn test_usage_error_flow() {
+        let mut flow = flow::break_with(UsageError {
+            reason: "Too many arguments.".to_string(),
+            usage: "/kick <user>".to_string(),
+        });
+        flow.inject(UsageReply("Try: /kick <user>".to_string()));
+
+        assert!(flow.is_break());
+        assert_eq!(
+            flow.injected::<UsageError>()
+                .map(|error| error.usage.as_str()),
+            Some("/kick <user>")
+        );
+        assert!(flow.injected::<UsageReply>().is_some());
     }
 }
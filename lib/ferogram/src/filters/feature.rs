@@ -0,0 +1,48 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use async_trait::async_trait;
+use grammers_client::{Client, Update};
+
+use crate::{di::Injector, flags::FeatureFlags, flow, logging, Filter, Flow};
+
+/// Pass if the feature flag is enabled, by [`crate::filter::feature`].
+#[derive(Clone, Debug)]
+pub struct Feature {
+    pub(crate) name: &'static str,
+    pub(crate) flags: Option<FeatureFlags>,
+}
+
+#[async_trait]
+impl Filter for Feature {
+    async fn check(&mut self, _: &Client, update: &Update) -> Flow {
+        let Some(flags) = self.flags.as_ref() else {
+            log::warn!(
+                target: logging::FILTERS,
+                "Feature {} checked without feature flags attached to the dispatcher",
+                self.name
+            );
+            return flow::break_now();
+        };
+
+        match flags.is_enabled_for(self.name, update).await {
+            Ok(true) => flow::continue_now(),
+            Ok(false) => flow::break_now(),
+            Err(e) => {
+                log::error!(target: logging::FILTERS, "Error checking the feature {}: {:?}", self.name, e);
+                flow::break_now()
+            }
+        }
+    }
+
+    fn attach(&mut self, services: &Injector) {
+        if let Some(flags) = services.get::<FeatureFlags>() {
+            self.flags = Some(flags.clone());
+        }
+    }
+}
@@ -0,0 +1,63 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use async_trait::async_trait;
+use grammers_client::{types::Chat, Client, Update};
+
+use crate::{di::Injector, flow, ChatMigrations, Filter, Flow};
+
+/// Pass if the chat id is the specified id, by [`crate::filter::id`].
+#[derive(Clone, Debug)]
+pub(crate) struct Id {
+    pub(crate) id: i64,
+    pub(crate) migrations: Option<ChatMigrations>,
+}
+
+impl Id {
+    /// Whether the chat is the specified one, or the one it was migrated to.
+    fn matches(&self, chat: &Chat) -> bool {
+        chat.id() == self.id
+            || self
+                .migrations
+                .as_ref()
+                .is_some_and(|migrations| chat.id() == migrations.resolve(self.id))
+    }
+}
+
+#[async_trait]
+impl Filter for Id {
+    async fn check(&mut self, _: &Client, update: &Update) -> Flow {
+        match update {
+            Update::NewMessage(message) | Update::MessageEdited(message) => {
+                let chat = message.chat();
+
+                if self.matches(&chat) {
+                    return flow::continue_with(chat);
+                }
+
+                flow::break_now()
+            }
+            Update::CallbackQuery(query) => {
+                let chat = query.chat();
+
+                if self.matches(chat) {
+                    return flow::continue_with(chat.clone());
+                }
+
+                flow::break_now()
+            }
+            _ => flow::break_now(),
+        }
+    }
+
+    fn attach(&mut self, services: &Injector) {
+        if let Some(migrations) = services.get::<ChatMigrations>() {
+            self.migrations = Some(migrations.clone());
+        }
+    }
+}
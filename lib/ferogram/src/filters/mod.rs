@@ -6,25 +6,38 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+mod ab_test;
 mod and;
 mod command;
+mod feature;
+mod id;
 mod not;
 mod or;
 
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicI64, Ordering},
+    Arc,
+};
 
+pub use ab_test::{ab_test, Variant};
 pub(crate) use and::And;
-pub(crate) use command::Command;
+pub(crate) use command::UsageReply;
+pub use command::{Command, CommandArgs, MatchedCommand, UsageError};
+pub use feature::Feature;
 use grammers_client::{
     grammers_tl_types as tl,
-    types::{Chat, Media},
+    types::{media::Document, Chat, Media},
     Client, Update,
 };
+use id::Id;
 pub(crate) use not::Not;
 pub(crate) use or::Or;
 use tokio::sync::Mutex;
 
-use crate::{flow, Filter, Flow};
+use crate::{
+    flow, inline::InlineStep, logging, Entity, EntityKind, Filter, Flow, PaidMedia,
+    PaidMediaPurchase,
+};
 
 /// Default prefixes for commands.
 pub const DEFAULT_PREFIXES: [&str; 2] = ["/", "!"];
@@ -89,6 +102,23 @@ pub async fn me(_: Client, update: Update) -> bool {
     }
 }
 
+/// Pass if the inline query starts with the specified prefix.
+///
+/// Injects `InlineStep`: the prefix, the arguments and the offset state.
+pub fn inline_prefix(prefix: &'static str) -> impl Filter {
+    Arc::new(move |_, update| async move {
+        if let Update::InlineQuery(query) = update {
+            if let Some(step) =
+                InlineStep::parse(query.sender().id(), prefix, query.text(), query.offset())
+            {
+                return flow::continue_with(step);
+            }
+        }
+
+        flow::break_now()
+    })
+}
+
 /// Pass if the message contains the specified text.
 pub fn text(pat: &'static str) -> impl Filter {
     Arc::new(move |_client, update| async move {
@@ -120,35 +150,62 @@ pub fn regex(pat: &'static str) -> impl Filter {
 /// Pass if the message matches the specified command.
 ///
 /// This filter is a custom [`regex`] filter, so it accepts regex syntax.
+///
+/// Injects `MatchedCommand`: matched command and its arguments, and
+/// `CommandArgs` if [`Command::args`] is set.
 pub fn command(pat: &'static str) -> Command {
     Command {
         prefixes: DEFAULT_PREFIXES.into_iter().map(regex::escape).collect(),
         command: pat.to_owned(),
         description: String::new(),
+        allow_mention: false,
+        prefix_provider: None,
+        args: Vec::new(),
+        usage_handler: None,
 
         username: Arc::new(Mutex::new(None)),
     }
 }
 
+/// Pass if the feature flag is enabled for the sender in the chat.
+///
+/// Checks the [`crate::flags::FeatureFlags`] attached with
+/// [`crate::Dispatcher::feature_flags`], never passing without them.
+pub fn feature(name: &'static str) -> Feature {
+    Feature { name, flags: None }
+}
+
 /// Pass if the message matches the specified command with custom prefixes.
 ///
 /// This filter is a custom [`regex`] filter, so it accepts a bit of regex syntax.
+///
+/// Injects `MatchedCommand`: matched command and its arguments.
 pub fn command_with(pres: &'static [&'static str], pat: &'static str) -> Command {
     Command {
         prefixes: pres.iter().map(|pre| regex::escape(pre)).collect(),
         command: pat.to_owned(),
         description: String::new(),
+        allow_mention: false,
+        prefix_provider: None,
+        args: Vec::new(),
+        usage_handler: None,
 
         username: Arc::new(Mutex::new(None)),
     }
 }
 
 /// Pass if the message matches any of the specified commands.
+///
+/// Injects `MatchedCommand`: matched command and its arguments.
 pub fn commands(pats: &'static [&'static str]) -> Command {
     Command {
         prefixes: DEFAULT_PREFIXES.into_iter().map(regex::escape).collect(),
         command: pats.join("|"),
         description: String::new(),
+        allow_mention: false,
+        prefix_provider: None,
+        args: Vec::new(),
+        usage_handler: None,
 
         username: Arc::new(Mutex::new(None)),
     }
@@ -157,16 +214,77 @@ pub fn commands(pats: &'static [&'static str]) -> Command {
 /// Pass if the message matches any of the specified commands with custom prefixes.
 ///
 /// This filter is a custom [`regex`] filter, so it accepts a bit of regex syntax.
+///
+/// Injects `MatchedCommand`: matched command and its arguments.
 pub fn commands_with(pres: &'static [&'static str], pats: &'static [&'static str]) -> Command {
     Command {
         prefixes: pres.iter().map(|pre| regex::escape(pre)).collect(),
         command: pats.join("|"),
         description: String::new(),
+        allow_mention: false,
+        prefix_provider: None,
+        args: Vec::new(),
+        usage_handler: None,
 
         username: Arc::new(Mutex::new(None)),
     }
 }
 
+/// Pass if the message has an entity of the specified kind.
+///
+/// Only the kind is compared, the data of the kind is ignored. Ex:
+/// `EntityKind::TextUrl { url: String::new() }` matches any text link.
+///
+/// Injects `Vec<String>`: texts of the entities.
+pub fn entity(kind: EntityKind) -> impl Filter {
+    Arc::new(move |_, update| {
+        let kind = kind.clone();
+
+        async move { entity_texts(&update, &kind) }
+    })
+}
+
+/// Pass if the message has hashtags.
+///
+/// Injects `Vec<String>`: hashtags, with the `#`.
+pub async fn has_hashtag(_: Client, update: Update) -> Flow {
+    entity_texts(&update, &EntityKind::Hashtag)
+}
+
+/// Pass if the message has cashtags.
+///
+/// Injects `Vec<String>`: cashtags, with the `$`.
+pub async fn has_cashtag(_: Client, update: Update) -> Flow {
+    entity_texts(&update, &EntityKind::Cashtag)
+}
+
+/// Pass if the message has entities of the kind, injecting their texts.
+fn entity_texts(update: &Update, kind: &EntityKind) -> Flow {
+    let kind = std::mem::discriminant(kind);
+
+    match update {
+        Update::NewMessage(message) | Update::MessageEdited(message) => {
+            let texts = message
+                .fmt_entities()
+                .map(|entities| {
+                    Entity::parse(message.text(), entities)
+                        .into_iter()
+                        .filter(|entity| std::mem::discriminant(&entity.kind) == kind)
+                        .map(|entity| entity.text)
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+
+            if texts.is_empty() {
+                return flow::break_now();
+            }
+
+            flow::continue_with(texts)
+        }
+        _ => flow::break_now(),
+    }
+}
+
 /// Pass if the message has a url.
 ///
 /// Injects `Vec<String>`: urls.
@@ -176,18 +294,13 @@ pub async fn has_url(_: Client, update: Update) -> Flow {
             let text = message.text();
             let mut urls = Vec::new();
 
-            if let Some(entities) = message.fmt_entities().cloned() {
-                for entity in entities
-                    .into_iter()
-                    .filter(|entity| matches!(entity, tl::enums::MessageEntity::Url(_)))
-                {
-                    let url = text
-                        .chars()
-                        .skip(entity.offset() as usize)
-                        .take(entity.length() as usize)
-                        .collect::<String>();
-                    urls.push(url);
-                }
+            if let Some(entities) = message.fmt_entities() {
+                urls.extend(
+                    Entity::parse(text, entities)
+                        .into_iter()
+                        .filter(|entity| entity.kind == EntityKind::Url)
+                        .map(|entity| entity.text),
+                );
             }
 
             #[cfg(feature = "url")]
@@ -231,6 +344,98 @@ pub async fn has_dice(_: Client, update: Update) -> Flow {
     }
 }
 
+/// Pass if the message has a location, static or live.
+///
+/// Injects `Geo`: location's coordinates, and `GeoLive`: live location, if live.
+pub async fn has_location(_: Client, update: Update) -> Flow {
+    match update {
+        Update::NewMessage(message) | Update::MessageEdited(message) => match message.media() {
+            Some(Media::Geo(geo)) => flow::continue_with(geo),
+            Some(Media::GeoLive(live)) => match live.geo.clone() {
+                Some(geo) => {
+                    let mut flow = flow::continue_with(geo);
+                    flow.injector.insert(live);
+
+                    flow
+                }
+                None => flow::break_now(),
+            },
+            _ => flow::break_now(),
+        },
+        _ => flow::break_now(),
+    }
+}
+
+/// Pass if the message has a venue.
+///
+/// Injects `Venue`: message's venue.
+pub async fn has_venue(_: Client, update: Update) -> Flow {
+    match update {
+        Update::NewMessage(message) | Update::MessageEdited(message) => {
+            if let Some(Media::Venue(venue)) = message.media() {
+                return flow::continue_with(venue);
+            }
+
+            flow::break_now()
+        }
+        _ => flow::break_now(),
+    }
+}
+
+/// Pass if the message has a shared contact.
+///
+/// Injects `Contact`: contact's phone number, name and vCard.
+pub async fn has_contact(_: Client, update: Update) -> Flow {
+    match update {
+        Update::NewMessage(message) | Update::MessageEdited(message) => {
+            if let Some(Media::Contact(contact)) = message.media() {
+                return flow::continue_with(contact);
+            }
+
+            flow::break_now()
+        }
+        _ => flow::break_now(),
+    }
+}
+
+/// Pass if the message has paid media.
+///
+/// Injects `PaidMedia`: media's price and content.
+pub async fn has_paid_media(_: Client, update: Update) -> Flow {
+    match update {
+        Update::NewMessage(message) | Update::MessageEdited(message) => {
+            if let tl::enums::Message::Message(tl::types::Message {
+                media: Some(tl::enums::MessageMedia::PaidMedia(media)),
+                ..
+            }) = message.raw
+            {
+                return flow::continue_with(PaidMedia {
+                    stars: media.stars_amount,
+                    media: media.extended_media,
+                });
+            }
+
+            flow::break_now()
+        }
+        _ => flow::break_now(),
+    }
+}
+
+/// Pass if an user purchased paid media sent by the bot.
+///
+/// Injects `PaidMediaPurchase`: buyer's id and media's payload.
+pub async fn paid_media_purchased(_: Client, update: Update) -> Flow {
+    match update {
+        Update::Raw(tl::enums::Update::BotPurchasedPaidMedia(purchase)) => {
+            flow::continue_with(PaidMediaPurchase {
+                user_id: purchase.user_id,
+                payload: purchase.payload,
+            })
+        }
+        _ => flow::break_now(),
+    }
+}
+
 /// Pass if the message has text or caption.
 ///
 /// Injects `String`: message's text.
@@ -297,6 +502,40 @@ pub async fn has_audio(_: Client, update: Update) -> Flow {
     }
 }
 
+/// Pass if the message has a voice note.
+///
+/// Matches the documents flagged as voice notes by the media or by their audio
+/// attribute, which is the only flag set by some clients.
+///
+/// Injects `Document`: message's voice note.
+pub async fn has_voice(_: Client, update: Update) -> Flow {
+    match update {
+        Update::NewMessage(message) | Update::MessageEdited(message) => {
+            if let Some(Media::Document(document)) = message.media() {
+                if is_voice_note(&document) {
+                    return flow::continue_with(document);
+                }
+            }
+
+            flow::break_now()
+        }
+        _ => flow::break_now(),
+    }
+}
+
+/// Pass if the callback query asks to open a game.
+///
+/// Injects `String`: game's short name.
+pub async fn game_callback(_: Client, update: Update) -> Flow {
+    match update {
+        Update::CallbackQuery(query) => match query.raw.game_short_name {
+            Some(game_short_name) => flow::continue_with(game_short_name),
+            None => flow::break_now(),
+        },
+        _ => flow::break_now(),
+    }
+}
+
 /// Pass if the message has a photo.
 ///
 /// Injects `Photo`: message's photo.
@@ -336,6 +575,42 @@ pub async fn has_video(_: Client, update: Update) -> Flow {
     }
 }
 
+/// Pass if the message has a video note, a round video.
+///
+/// Injects `Document`: message's video note.
+pub async fn has_video_note(_: Client, update: Update) -> Flow {
+    match update {
+        Update::NewMessage(message) | Update::MessageEdited(message) => {
+            if let Some(Media::Document(document)) = message.media() {
+                if is_video_note(&document) {
+                    return flow::continue_with(document);
+                }
+            }
+
+            flow::break_now()
+        }
+        _ => flow::break_now(),
+    }
+}
+
+/// Pass if the message has a GIF, an animation.
+///
+/// Injects `Document`: message's GIF.
+pub async fn has_gif(_: Client, update: Update) -> Flow {
+    match update {
+        Update::NewMessage(message) | Update::MessageEdited(message) => {
+            if let Some(Media::Document(document)) = message.media() {
+                if is_gif(&document) {
+                    return flow::continue_with(document);
+                }
+            }
+
+            flow::break_now()
+        }
+        _ => flow::break_now(),
+    }
+}
+
 /// Pass if the message has a document.
 ///
 /// Injects `Document`: message's document.
@@ -404,6 +679,45 @@ pub async fn left_chat_member(_: Client, update: Update) -> bool {
     false
 }
 
+/// Pass if the message is a service message of members joining the group.
+///
+/// Injects `Vec<i64>`: the IDs of the members.
+pub async fn member_joined(_: Client, update: Update) -> Flow {
+    if let Update::NewMessage(message) = update {
+        let sender_id = message.sender().map(|sender| sender.id());
+
+        match message.action() {
+            Some(tl::enums::MessageAction::ChatAddUser(action)) => {
+                return flow::continue_with(action.users.clone());
+            }
+            Some(
+                tl::enums::MessageAction::ChatJoinedByLink(_)
+                | tl::enums::MessageAction::ChatJoinedByRequest,
+            ) => {
+                if let Some(sender_id) = sender_id {
+                    return flow::continue_with(vec![sender_id]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    flow::break_now()
+}
+
+/// Pass if the message is a service message of a member leaving the group.
+///
+/// Injects `Vec<i64>`: the ID of the member.
+pub async fn member_left(_: Client, update: Update) -> Flow {
+    if let Update::NewMessage(message) = update {
+        if let Some(tl::enums::MessageAction::ChatDeleteUser(action)) = message.action() {
+            return flow::continue_with(vec![action.user_id]);
+        }
+    }
+
+    flow::break_now()
+}
+
 /// Pass if the update is a typing action.
 pub async fn typing(_: Client, update: Update) -> bool {
     if let Update::Raw(raw_update) = update {
@@ -416,6 +730,33 @@ pub async fn typing(_: Client, update: Update) -> bool {
     false
 }
 
+/// Pass if the update is a draft saved or cleared by the account.
+///
+/// Injects `UpdateDraftMessage`: the raw draft update.
+pub async fn draft(_: Client, update: Update) -> Flow {
+    match update {
+        Update::Raw(tl::enums::Update::DraftMessage(draft)) => flow::continue_with(draft),
+        _ => flow::break_now(),
+    }
+}
+
+/// Pass if the message is replying to a story.
+///
+/// Injects `MessageReplyStoryHeader`: the replied story.
+pub async fn reply_to_story(_: Client, update: Update) -> Flow {
+    match update {
+        Update::NewMessage(message) | Update::MessageEdited(message) => {
+            match message.reply_header() {
+                Some(tl::enums::MessageReplyHeader::MessageReplyStoryHeader(header)) => {
+                    flow::continue_with(header)
+                }
+                _ => flow::break_now(),
+            }
+        }
+        _ => flow::break_now(),
+    }
+}
+
 /// Pass if the message is forwarded.
 pub async fn forwarded(_: Client, update: Update) -> Flow {
     if let Update::NewMessage(message) = update {
@@ -427,6 +768,52 @@ pub async fn forwarded(_: Client, update: Update) -> Flow {
     flow::break_now()
 }
 
+/// Pass if the message was sent via the specified inline bot.
+///
+/// Accepts the bot's id or username, with or without the "@" prefix. The
+/// username is resolved once, on the first message sent via a bot.
+///
+/// Injects `i64`: bot's id.
+pub fn via_bot(bot: &'static str) -> impl Filter {
+    let bot_id = Arc::new(AtomicI64::new(bot.parse().unwrap_or(0)));
+
+    Arc::new(move |client: Client, update| {
+        let bot_id = bot_id.clone();
+
+        async move {
+            match update {
+                Update::NewMessage(message) | Update::MessageEdited(message) => {
+                    let Some(via_bot_id) = message.via_bot_id() else {
+                        return flow::break_now();
+                    };
+
+                    let mut id = bot_id.load(Ordering::Relaxed);
+                    if id == 0 {
+                        match client.resolve_username(bot.trim_start_matches('@')).await {
+                            Ok(Some(chat)) => {
+                                id = chat.id();
+                                bot_id.store(id, Ordering::Relaxed);
+                            }
+                            Ok(None) => return flow::break_now(),
+                            Err(e) => {
+                                log::error!(target: logging::FILTERS, "Error resolving the bot {}: {:?}", bot, e);
+                                return flow::break_now();
+                            }
+                        }
+                    }
+
+                    if via_bot_id == id {
+                        return flow::continue_with(via_bot_id);
+                    }
+
+                    flow::break_now()
+                }
+                _ => flow::break_now(),
+            }
+        }
+    })
+}
+
 /// Pass if the message or callback query is sent by an administrator.
 pub async fn administrator(client: Client, update: Update) -> Flow {
     let chat;
@@ -577,33 +964,167 @@ pub async fn channel(_: Client, update: Update) -> Flow {
 
 /// Pass if the chat id is the specified id.
 ///
+/// Follows the group migration to supergroup, passing with the new id after
+/// the migration, as recorded in the [`crate::ChatMigrations`] of the dispatcher.
+///
 /// Injects `Chat`: chat.
 pub fn id(id: i64) -> impl Filter {
+    Id {
+        id,
+        migrations: None,
+    }
+}
+
+/// Pass if the sender id is the specified id.
+///
+/// Injects `Chat`: sender.
+pub fn sender_id(id: i64) -> impl Filter {
     Arc::new(move |_, update| async move {
-        match update {
-            Update::NewMessage(message) | Update::MessageEdited(message) => {
-                let chat = message.chat();
+        match update_sender(&update) {
+            Some(sender) if sender.id() == id => flow::continue_with(sender),
+            _ => flow::break_now(),
+        }
+    })
+}
 
-                if chat.id() == id {
-                    return flow::continue_with(chat);
-                }
+/// Pass if the sender id is any of the specified ids.
+///
+/// Injects `Chat`: sender.
+pub fn sender_ids(ids: &[i64]) -> impl Filter {
+    let ids = Arc::new(ids.to_vec());
 
-                flow::break_now()
+    Arc::new(move |_, update| {
+        let ids = ids.clone();
+
+        async move {
+            match update_sender(&update) {
+                Some(sender) if ids.contains(&sender.id()) => flow::continue_with(sender),
+                _ => flow::break_now(),
             }
-            Update::CallbackQuery(query) => {
-                let chat = query.chat();
+        }
+    })
+}
 
-                if chat.id() == id {
-                    return flow::continue_with(chat.clone());
-                }
+/// Pass if the sender usernames contains the specified username.
+///
+/// The username cannot contain the "@" prefix.
+///
+/// Injects `Chat`: sender.
+pub fn sender_username(username: &'static str) -> impl Filter {
+    Arc::new(move |_, update| async move {
+        match update_sender(&update) {
+            Some(sender)
+                if sender.username() == Some(username)
+                    || sender.usernames().contains(&username) =>
+            {
+                flow::continue_with(sender)
+            }
+            _ => flow::break_now(),
+        }
+    })
+}
 
-                flow::break_now()
+/// Pass if the sender usernames contains any of the specified usernames.
+///
+/// The usernames cannot contain the "@" prefix.
+///
+/// Injects `Chat`: sender.
+pub fn sender_usernames(usernames: &'static [&'static str]) -> impl Filter {
+    Arc::new(move |_, update| async move {
+        match update_sender(&update) {
+            Some(sender)
+                if sender
+                    .username()
+                    .into_iter()
+                    .chain(sender.usernames())
+                    .any(|username| usernames.contains(&username)) =>
+            {
+                flow::continue_with(sender)
             }
             _ => flow::break_now(),
         }
     })
 }
 
+/// Pass if the sender is a bot account.
+///
+/// Injects `User`: sender.
+pub async fn bot(_: Client, update: Update) -> Flow {
+    match update_sender(&update) {
+        Some(Chat::User(user)) if user.is_bot() => flow::continue_with(user),
+        _ => flow::break_now(),
+    }
+}
+
+/// Pass if the sender is a user account, not a bot.
+///
+/// Injects `User`: sender.
+pub async fn human(_: Client, update: Update) -> Flow {
+    match update_sender(&update) {
+        Some(Chat::User(user)) if !user.is_bot() => flow::continue_with(user),
+        _ => flow::break_now(),
+    }
+}
+
+/// Pass if the sender language is the specified language code.
+///
+/// A code without region, like "pt", also matches its regions, like "pt-br".
+///
+/// Injects `User`: sender.
+pub fn lang_code(code: &'static str) -> impl Filter {
+    Arc::new(move |_, update| async move {
+        match update_sender(&update) {
+            Some(Chat::User(user))
+                if user
+                    .lang_code()
+                    .is_some_and(|lang| lang_matches(lang, code)) =>
+            {
+                flow::continue_with(user)
+            }
+            _ => flow::break_now(),
+        }
+    })
+}
+
+/// Pass if the sender language is any of the specified language codes.
+///
+/// A code without region, like "pt", also matches its regions, like "pt-br".
+///
+/// Injects `User`: sender.
+pub fn lang_codes(codes: &'static [&'static str]) -> impl Filter {
+    Arc::new(move |_, update| async move {
+        match update_sender(&update) {
+            Some(Chat::User(user))
+                if user
+                    .lang_code()
+                    .is_some_and(|lang| codes.iter().any(|code| lang_matches(lang, code))) =>
+            {
+                flow::continue_with(user)
+            }
+            _ => flow::break_now(),
+        }
+    })
+}
+
+/// Checks if the language is the code, or one of its regions.
+fn lang_matches(lang: &str, code: &str) -> bool {
+    lang.eq_ignore_ascii_case(code)
+        || lang
+            .split_once('-')
+            .is_some_and(|(primary, _)| primary.eq_ignore_ascii_case(code))
+}
+
+/// Returns the sender of the update, if any.
+fn update_sender(update: &Update) -> Option<Chat> {
+    match update {
+        Update::NewMessage(message) | Update::MessageEdited(message) => message.sender(),
+        Update::CallbackQuery(query) => Some(query.sender().clone()),
+        Update::InlineQuery(query) => Some(Chat::User(query.sender().clone())),
+        Update::InlineSend(inline_send) => Some(Chat::User(inline_send.sender().clone())),
+        _ => None,
+    }
+}
+
 /// Pass if the chat usernames contains the specified username.
 ///
 /// The username cannot contain the "@" prefix.
@@ -855,6 +1376,50 @@ pub async fn reply_video(_: Client, update: Update) -> Flow {
     }
 }
 
+/// Pass if the message is a reply and has a video note, a round video.
+///
+/// Injects `Document`: reply message's video note.
+pub async fn reply_video_note(_: Client, update: Update) -> Flow {
+    match update {
+        Update::NewMessage(message) | Update::MessageEdited(message) => {
+            if message.reply_to_message_id().is_some() {
+                if let Ok(Some(reply)) = message.get_reply().await {
+                    if let Some(Media::Document(document)) = reply.media() {
+                        if is_video_note(&document) {
+                            return flow::continue_with(document);
+                        }
+                    }
+                }
+            }
+
+            flow::break_now()
+        }
+        _ => flow::break_now(),
+    }
+}
+
+/// Pass if the message is a reply and has a GIF, an animation.
+///
+/// Injects `Document`: reply message's GIF.
+pub async fn reply_gif(_: Client, update: Update) -> Flow {
+    match update {
+        Update::NewMessage(message) | Update::MessageEdited(message) => {
+            if message.reply_to_message_id().is_some() {
+                if let Ok(Some(reply)) = message.get_reply().await {
+                    if let Some(Media::Document(document)) = reply.media() {
+                        if is_gif(&document) {
+                            return flow::continue_with(document);
+                        }
+                    }
+                }
+            }
+
+            flow::break_now()
+        }
+        _ => flow::break_now(),
+    }
+}
+
 /// Pass if the message is a reply and has a document.
 ///
 /// Injects `Document`: reply message's document.
@@ -916,3 +1481,47 @@ pub async fn reply_animated_sticker(_: Client, update: Update) -> Flow {
         _ => flow::break_now(),
     }
 }
+
+/// Returns the attributes of the document.
+fn document_attributes(document: &Document) -> &[tl::enums::DocumentAttribute] {
+    match document.raw.document.as_ref() {
+        Some(tl::enums::Document::Document(document)) => &document.attributes,
+        _ => &[],
+    }
+}
+
+/// Whether the document is a voice note, flagged by the media or by its audio
+/// attribute.
+fn is_voice_note(document: &Document) -> bool {
+    document.raw.voice
+        || document_attributes(document)
+            .iter()
+            .any(|attribute| match attribute {
+                tl::enums::DocumentAttribute::Audio(audio) => audio.voice,
+                _ => false,
+            })
+}
+
+/// Whether the document is a video note, flagged by the media or by its video
+/// attribute.
+fn is_video_note(document: &Document) -> bool {
+    document.raw.round
+        || document_attributes(document)
+            .iter()
+            .any(|attribute| match attribute {
+                tl::enums::DocumentAttribute::Video(video) => video.round_message,
+                _ => false,
+            })
+}
+
+/// Whether the document is a GIF, an `image/gif` or a `video/mp4` with the
+/// animated attribute, as Telegram converts the GIFs.
+fn is_gif(document: &Document) -> bool {
+    match document.mime_type() {
+        Some("image/gif") => true,
+        Some("video/mp4") => document_attributes(document)
+            .iter()
+            .any(|attribute| matches!(attribute, tl::enums::DocumentAttribute::Animated)),
+        _ => false,
+    }
+}
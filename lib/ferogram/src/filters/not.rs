@@ -9,7 +9,7 @@
 use async_trait::async_trait;
 use grammers_client::{Client, Update};
 
-use crate::{Filter, Flow};
+use crate::{di::Injector, filters::Command, Filter, Flow};
 
 #[derive(Clone)]
 pub struct Not {
@@ -21,4 +21,17 @@ impl Filter for Not {
     async fn check(&mut self, client: &Client, update: &Update) -> Flow {
         self.filter.check(client, update).await.is_break().into()
     }
+
+    // The negated commands are not offered, since they don't trigger the handler.
+    fn commands(&self) -> Vec<Command> {
+        Vec::new()
+    }
+
+    fn prefix_commands(&mut self, prefix: &str) {
+        self.filter.prefix_commands(prefix);
+    }
+
+    fn attach(&mut self, services: &Injector) {
+        self.filter.attach(services);
+    }
 }
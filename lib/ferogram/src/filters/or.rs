@@ -9,7 +9,7 @@
 use async_trait::async_trait;
 use grammers_client::{Client, Update};
 
-use crate::{flow, Filter, Flow};
+use crate::{di::Injector, filters::Command, Filter, Flow};
 
 #[derive(Clone)]
 pub struct Or {
@@ -20,18 +20,38 @@ pub struct Or {
 #[async_trait]
 impl Filter for Or {
     async fn check(&mut self, client: &Client, update: &Update) -> Flow {
-        let first_flow = self.first.check(client, update).await;
+        let mut first_flow = self.first.check(client, update).await;
 
         if first_flow.is_continue() {
             first_flow
         } else {
-            let other_flow = self.other.check(client, update).await;
+            let mut other_flow = self.other.check(client, update).await;
 
             if other_flow.is_continue() {
                 other_flow
             } else {
-                flow::break_now()
+                // Keeps what the filters injected when breaking. Ex: `UsageError`.
+                first_flow.injector.extend(&mut other_flow.injector);
+
+                first_flow
             }
         }
     }
+
+    fn commands(&self) -> Vec<Command> {
+        let mut commands = self.first.commands();
+        commands.extend(self.other.commands());
+
+        commands
+    }
+
+    fn prefix_commands(&mut self, prefix: &str) {
+        self.first.prefix_commands(prefix);
+        self.other.prefix_commands(prefix);
+    }
+
+    fn attach(&mut self, services: &Injector) {
+        self.first.attach(services);
+        self.other.attach(services);
+    }
 }
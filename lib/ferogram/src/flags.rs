@@ -0,0 +1,246 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Feature flags module.
+
+use std::{fmt, sync::Arc};
+
+use grammers_client::{types::Chat, Update};
+
+use crate::{
+    filters::{self, MatchedCommand},
+    handler, Context, Filter, Result, Router, Storage,
+};
+
+/// The scope of a feature flag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Scope {
+    /// Every chat and user.
+    Global,
+    /// A chat.
+    Chat(i64),
+    /// An user, in every chat.
+    User(i64),
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Global => write!(f, "global"),
+            Self::Chat(id) => write!(f, "chat:{}", id),
+            Self::User(id) => write!(f, "user:{}", id),
+        }
+    }
+}
+
+/// The feature flags, backed by a [`Storage`].
+///
+/// A flag can be set globally, per chat and per user. The user flag overrides
+/// the chat flag, which overrides the global one, and the unset flags are
+/// disabled. Attach it with [`crate::Dispatcher::feature_flags`] to use
+/// [`crate::filter::feature`] and to inject it in the handlers.
+///
+/// [`FeatureFlags::router`] mounts the `/flag` command, so the owners can toggle
+/// the flags without redeploying.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use ferogram::{filters, flags::FeatureFlags, handler, Context, MemoryStorage};
+///
+/// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+/// let flags = FeatureFlags::new(MemoryStorage::default());
+///
+/// let dispatcher = dispatcher
+///     .feature_flags(flags.clone())
+///     .router(|router| flags.router(router, &[123456789]))
+///     .router(|router| {
+///         router.register(
+///             handler::new_message(filters::command("menu").and(filters::feature("new_ui")))
+///                 .then(|ctx: Context| async move { Ok(()) }),
+///         )
+///     });
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct FeatureFlags {
+    /// The storage of the flags.
+    storage: Arc<dyn Storage>,
+}
+
+impl FeatureFlags {
+    /// Creates the flags backed by `storage`.
+    pub fn new<S: Storage>(storage: S) -> Self {
+        Self {
+            storage: Arc::new(storage),
+        }
+    }
+
+    /// Returns the storage key of the flag.
+    fn key(name: &str, scope: Scope) -> String {
+        format!("flag:{}:{}", name.to_lowercase(), scope)
+    }
+
+    /// Gets the flag in the scope.
+    ///
+    /// Returns `None` if the flag is not set in the scope.
+    pub async fn get(&self, name: &str, scope: Scope) -> Result<Option<bool>> {
+        let value = self.storage.get(&Self::key(name, scope)).await?;
+
+        Ok(value.and_then(|value| value.parse().ok()))
+    }
+
+    /// Enables or disables the flag in the scope.
+    pub async fn set(&self, name: &str, scope: Scope, enabled: bool) -> Result<()> {
+        self.storage
+            .set(&Self::key(name, scope), enabled.to_string())
+            .await
+    }
+
+    /// Unsets the flag in the scope, falling back to the broader scopes.
+    pub async fn unset(&self, name: &str, scope: Scope) -> Result<()> {
+        self.storage.remove(&Self::key(name, scope)).await
+    }
+
+    /// Checks if the flag is enabled for the user in the chat.
+    pub async fn is_enabled(
+        &self,
+        name: &str,
+        chat_id: Option<i64>,
+        user_id: Option<i64>,
+    ) -> Result<bool> {
+        let scopes = user_id
+            .map(Scope::User)
+            .into_iter()
+            .chain(chat_id.map(Scope::Chat))
+            .chain([Scope::Global]);
+
+        for scope in scopes {
+            if let Some(enabled) = self.get(name, scope).await? {
+                return Ok(enabled);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Checks if the flag is enabled for the sender of the update in its chat.
+    pub(crate) async fn is_enabled_for(&self, name: &str, update: &Update) -> Result<bool> {
+        let (chat_id, user_id) = match update {
+            Update::NewMessage(message) | Update::MessageEdited(message) => (
+                Some(message.chat().id()),
+                message.sender().map(|sender| sender.id()),
+            ),
+            Update::CallbackQuery(query) => (Some(query.chat().id()), Some(query.sender().id())),
+            Update::InlineQuery(query) => (None, Some(query.sender().id())),
+            Update::InlineSend(inline_send) => (None, Some(inline_send.sender().id())),
+            _ => (None, None),
+        };
+
+        self.is_enabled(name, chat_id, user_id).await
+    }
+
+    /// Registers the `/flag` command in the router, allowed only to the `owners`.
+    ///
+    /// Usage: `/flag <name> [on|off|reset] [global|chat|user <id>]`, applying to
+    /// the current chat by default. `/flag <name>` replies the flag state in the
+    /// current chat.
+    pub fn router(self, router: Router, owners: &[i64]) -> Router {
+        let owners = owners.to_vec();
+        let is_owner = move |_, update| {
+            let owners = owners.clone();
+
+            async move {
+                match update {
+                    Update::NewMessage(message) => match message.sender() {
+                        Some(Chat::User(user)) => owners.contains(&user.id()),
+                        _ => false,
+                    },
+                    _ => false,
+                }
+            }
+        };
+
+        router.register(
+            handler::new_message(
+                filters::command("flag")
+                    .description("Toggle a feature flag.")
+                    .and(is_owner),
+            )
+            .then(move |ctx: Context, command: MatchedCommand| {
+                let flags = self.clone();
+
+                async move { flags.handle_flag(&ctx, &command.args).await }
+            }),
+        )
+    }
+
+    /// Handles `/flag <name> [on|off|reset] [global|chat|user <id>]`.
+    async fn handle_flag(&self, ctx: &Context, args: &str) -> Result<()> {
+        let chat_id = ctx.chat().expect("No chat").id();
+        let mut args = args.split_whitespace();
+
+        let Some(name) = args.next() else {
+            ctx.reply("Usage: /flag <name> [on|off|reset] [global|chat|user <id>]")
+                .await?;
+            return Ok(());
+        };
+        let action = args.next();
+        let scope = match (args.next(), args.next()) {
+            (None | Some("chat"), None) => Scope::Chat(chat_id),
+            (Some("global"), None) => Scope::Global,
+            (Some("user"), Some(id)) => match id.parse() {
+                Ok(id) => Scope::User(id),
+                Err(_) => {
+                    ctx.reply("Invalid user ID.").await?;
+                    return Ok(());
+                }
+            },
+            _ => {
+                ctx.reply("Invalid scope, use global, chat or user <id>.")
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        match action {
+            None => {
+                let state = match self.get(name, scope).await? {
+                    Some(true) => "on",
+                    Some(false) => "off",
+                    None => "unset",
+                };
+
+                ctx.reply(format!("Flag {} is {} in {}.", name, state, scope))
+                    .await?;
+            }
+            Some(state @ ("on" | "off")) => {
+                self.set(name, scope, state == "on").await?;
+                ctx.reply(format!("Flag {} turned {} in {}.", name, state, scope))
+                    .await?;
+            }
+            Some("reset") => {
+                self.unset(name, scope).await?;
+                ctx.reply(format!("Flag {} reset in {}.", name, scope))
+                    .await?;
+            }
+            Some(_) => {
+                ctx.reply("Invalid action, use on, off or reset.").await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for FeatureFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FeatureFlags").finish_non_exhaustive()
+    }
+}
@@ -8,6 +8,8 @@
 
 //! Flow module.
 
+use grammers_client::Update;
+
 use crate::{di::Injector, Result};
 
 /// Represents the control flow of a filter.
@@ -17,9 +19,39 @@ pub struct Flow {
     action: Action,
     /// The injector.
     pub(crate) injector: Injector,
+    /// The update which replaces the current one, if any.
+    pub(crate) update: Option<Update>,
 }
 
 impl Flow {
+    /// Creates a new flow with action [`Action::Continue`] which replaces the update.
+    ///
+    /// When returned by a before-middleware, the next middlewares, the filters and
+    /// the endpoint receive the new update. Useful to normalize texts or resolve
+    /// short commands.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let router = unimplemented!();
+    /// let router = router.middlewares(|middlewares| {
+    ///     middlewares.before(|_, update: &Update, _| {
+    ///         let update = update.clone();
+    ///
+    ///         async move { Ok(Flow::with_update(normalize(update))) }
+    ///     })
+    /// });
+    /// # }
+    /// ```
+    pub fn with_update(update: Update) -> Self {
+        Self {
+            action: Action::Continue,
+            update: Some(update),
+            ..Default::default()
+        }
+    }
+
     /// Changes the current action to [`Action::Break`].
     ///
     /// # Example
@@ -62,6 +94,20 @@ impl Flow {
         self.injector.insert(value);
     }
 
+    /// Returns a reference for an injected value.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let flow = unimplemented!();
+    /// let value = flow.injected::<String>();
+    /// # }
+    /// ```
+    pub fn injected<R: Send + Sync + 'static>(&self) -> Option<&R> {
+        self.injector.get::<R>()
+    }
+
     /// Checks if the current action is [`Action::Break`].
     ///
     /// # Example
@@ -73,7 +119,7 @@ impl Flow {
     /// # }
     /// ```
     pub fn is_break(&self) -> bool {
-        matches!(self.action, Action::Continue)
+        matches!(self.action, Action::Break)
     }
 
     /// Checks if the current action is [`Action::Continue`].
@@ -141,6 +187,14 @@ pub fn break_now() -> Flow {
     }
 }
 
+/// Creates a new flow with action [`Action::Break`] and injects a value.
+pub fn break_with<R: Clone + Send + Sync + 'static>(value: R) -> Flow {
+    let mut flow = break_now();
+    flow.inject(value);
+
+    flow
+}
+
 /// Creates a new flow with action [`Action::Continue`].
 pub fn continue_now() -> Flow {
     Flow {
@@ -0,0 +1,225 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Greeting module.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use grammers_client::types::{Chat, InputMessage, Media};
+
+use crate::{
+    filters, handler,
+    utils::{escape_html, user_link},
+    ChatSettings, Context, Result, Router,
+};
+
+/// Sends the welcome and goodbye messages of the groups.
+///
+/// The templates are HTML and accept the placeholders `{mention}`, `{name}`, `{id}`
+/// and `{title}`. With [`Greeter::chat_settings`], the chats can set their own
+/// templates in the settings `welcome` and `goodbye`, and disable the features
+/// `welcome` and `goodbye`.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use ferogram::greeting::Greeter;
+///
+/// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+/// let greeter = Greeter::default()
+///     .welcome("Hello, {mention}! Welcome to <b>{title}</b>.")
+///     .goodbye("Goodbye, {name}.")
+///     .delete_previous(true);
+///
+/// let dispatcher = dispatcher.router(|router| greeter.router(router));
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Greeter {
+    /// The default welcome template.
+    welcome: Option<String>,
+    /// The default goodbye template.
+    goodbye: Option<String>,
+    /// The media sent with the welcome message.
+    media: Option<Media>,
+    /// Whether to delete the previous welcome message of the chat.
+    delete_previous: bool,
+    /// The per-chat settings.
+    settings: Option<ChatSettings>,
+    /// The last welcome message of each chat.
+    last_welcomes: Arc<Mutex<HashMap<i64, i32>>>,
+}
+
+impl Greeter {
+    /// Sets the default welcome template.
+    pub fn welcome<T: ToString>(mut self, template: T) -> Self {
+        self.welcome = Some(template.to_string());
+        self
+    }
+
+    /// Sets the default goodbye template.
+    pub fn goodbye<T: ToString>(mut self, template: T) -> Self {
+        self.goodbye = Some(template.to_string());
+        self
+    }
+
+    /// Sets the media sent with the welcome message, like a photo or a GIF.
+    pub fn media(mut self, media: Media) -> Self {
+        self.media = Some(media);
+        self
+    }
+
+    /// Whether to delete the previous welcome message when a new one is sent.
+    pub fn delete_previous(mut self, value: bool) -> Self {
+        self.delete_previous = value;
+        self
+    }
+
+    /// Sets the per-chat settings.
+    pub fn chat_settings(mut self, settings: ChatSettings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    /// Registers the welcome and goodbye handlers in the router.
+    pub fn router(self, router: Router) -> Router {
+        let welcome = self.clone();
+        let goodbye = self;
+
+        router
+            .register(handler::new_message(filters::member_joined).then(
+                move |ctx: Context, members: Vec<i64>| {
+                    let greeter = welcome.clone();
+
+                    async move { greeter.send_welcome(&ctx, &members).await }
+                },
+            ))
+            .register(handler::new_message(filters::member_left).then(
+                move |ctx: Context, members: Vec<i64>| {
+                    let greeter = goodbye.clone();
+
+                    async move { greeter.send_goodbye(&ctx, &members).await }
+                },
+            ))
+    }
+
+    /// Welcomes the members in the chat of the context.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message could not be sent.
+    pub async fn send_welcome(&self, ctx: &Context, members: &[i64]) -> Result<()> {
+        let chat = ctx.chat().expect("No chat");
+
+        let Some(template) = self.template(&chat, "welcome", &self.welcome).await else {
+            return Ok(());
+        };
+
+        let mut message = InputMessage::html(render(&template, ctx, &chat, members));
+        if let Some(media) = self.media.as_ref() {
+            message = message.copy_media(media);
+        }
+
+        let sent = ctx.send(message).await?;
+
+        if self.delete_previous {
+            let previous = self
+                .last_welcomes
+                .lock()
+                .expect("Failed to lock the welcomes")
+                .insert(chat.id(), sent.id());
+
+            if let Some(previous) = previous {
+                ctx.client().delete_messages(&chat, &[previous]).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Says goodbye to the members in the chat of the context.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message could not be sent.
+    pub async fn send_goodbye(&self, ctx: &Context, members: &[i64]) -> Result<()> {
+        let chat = ctx.chat().expect("No chat");
+
+        if let Some(template) = self.template(&chat, "goodbye", &self.goodbye).await {
+            ctx.send(InputMessage::html(render(&template, ctx, &chat, members)))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the template of the chat, or `None` if disabled.
+    async fn template(
+        &self,
+        chat: &Chat,
+        feature: &str,
+        default: &Option<String>,
+    ) -> Option<String> {
+        let Some(settings) = self.settings.as_ref() else {
+            return default.clone();
+        };
+
+        if let Ok(Some(false)) = settings.feature(chat.id(), feature).await {
+            return None;
+        }
+
+        match settings.get(chat.id(), feature).await {
+            Ok(Some(template)) => Some(template),
+            _ => default.clone(),
+        }
+    }
+}
+
+impl Default for Greeter {
+    fn default() -> Self {
+        Self {
+            welcome: Some(String::from("Welcome, {mention}, to {title}!")),
+            goodbye: None,
+            media: None,
+            delete_previous: false,
+            settings: None,
+            last_welcomes: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+/// Renders the template for the members.
+///
+/// The name is only known for the sender, the other members are named by their IDs.
+fn render(template: &str, ctx: &Context, chat: &Chat, members: &[i64]) -> String {
+    let sender = ctx.sender();
+
+    let names = members
+        .iter()
+        .map(|id| match sender.as_ref() {
+            Some(sender) if sender.id() == *id => escape_html(sender.name().unwrap_or_default()),
+            _ => id.to_string(),
+        })
+        .collect::<Vec<_>>();
+    let mentions = members
+        .iter()
+        .zip(names.iter())
+        .map(|(id, name)| format!("<a href=\"{}\">{}</a>", user_link(*id), name))
+        .collect::<Vec<_>>();
+    let ids = members.iter().map(i64::to_string).collect::<Vec<_>>();
+
+    template
+        .replace("{mention}", &mentions.join(", "))
+        .replace("{name}", &names.join(", "))
+        .replace("{id}", &ids.join(", "))
+        .replace("{title}", &escape_html(chat.name().unwrap_or_default()))
+}
@@ -8,9 +8,22 @@
 
 //! Handler module.
 
+use std::{any::TypeId, cell::RefCell, future::Future};
+
 use grammers_client::{Client, Update};
 
-use crate::{di, filter::Command, flow, ErrorHandler, Filter, Flow};
+use crate::{
+    di,
+    event::Event,
+    filter::Command,
+    filters::{UsageError, UsageReply},
+    flow, logging, ErrorHandler, Filter, Flow,
+};
+
+tokio::task_local! {
+    /// The name of the handler which failed in the task.
+    static FAILED_HANDLER: RefCell<Option<String>>;
+}
 
 /// A handler.
 ///
@@ -22,89 +35,85 @@ pub struct Handler {
 
     /// The filter.
     pub(crate) filter: Option<Box<dyn Filter>>,
-    /// The command.
-    pub(crate) command: Option<Command>,
+    /// The commands.
+    pub(crate) commands: Vec<Command>,
     /// The endpoint.
     pub(crate) endpoint: Option<di::Endpoint>,
     /// The error handler.
     pub(crate) err_handler: Option<Box<dyn ErrorHandler>>,
+    /// The resources visible only to the endpoint.
+    pub(crate) resources: di::Injector,
+    /// The priority, handlers with lower priority are shed first.
+    pub(crate) priority: i32,
+    /// The name shown in the error logs.
+    pub(crate) name: Option<String>,
 }
 
 impl Handler {
-    /// Creates a new [`HandlerType::NewMessage`] handler.
-    pub fn new_message<F: Filter>(filter: F) -> Self {
-        let command = filter.as_any().downcast_ref::<Command>().cloned();
-
+    /// Creates a handler of the update type, without endpoint.
+    fn with_type(
+        update_type: UpdateType,
+        filter: Option<Box<dyn Filter>>,
+        commands: Vec<Command>,
+    ) -> Self {
         Self {
-            update_type: UpdateType::NewMessage,
+            update_type,
 
-            filter: Some(Box::new(filter)),
-            command,
+            filter,
+            commands,
             endpoint: None,
             err_handler: None,
+            resources: di::Injector::default(),
+            priority: 0,
+            name: None,
         }
     }
 
+    /// Creates a new [`HandlerType::NewMessage`] handler.
+    pub fn new_message<F: Filter>(filter: F) -> Self {
+        let commands = filter.commands();
+
+        Self::with_type(UpdateType::NewMessage, Some(Box::new(filter)), commands)
+    }
+
     /// Creates a new [`HandlerType::Raw`] handler.
     pub fn new_update<F: Filter>(filter: F) -> Self {
-        Self {
-            update_type: UpdateType::Raw,
-
-            filter: Some(Box::new(filter)),
-            command: None,
-            endpoint: None,
-            err_handler: None,
-        }
+        Self::with_type(UpdateType::Raw, Some(Box::new(filter)), Vec::new())
     }
 
     /// Creates a new [`HandlerType::MessageEdited`] handler.
     pub fn message_edited<F: Filter>(filter: F) -> Self {
-        let command = filter.as_any().downcast_ref::<Command>().cloned();
+        let commands = filter.commands();
 
-        Self {
-            update_type: UpdateType::MessageEdited,
-
-            filter: Some(Box::new(filter)),
-            command,
-            endpoint: None,
-            err_handler: None,
-        }
+        Self::with_type(UpdateType::MessageEdited, Some(Box::new(filter)), commands)
     }
 
     /// Creates a new [`HandlerType::MessageDeleted`] handler.
     pub fn message_deleted<F: Filter>(filter: F) -> Self {
-        Self {
-            update_type: UpdateType::MessageDeleted,
-
-            filter: Some(Box::new(filter)),
-            command: None,
-            endpoint: None,
-            err_handler: None,
-        }
+        Self::with_type(
+            UpdateType::MessageDeleted,
+            Some(Box::new(filter)),
+            Vec::new(),
+        )
     }
 
     /// Creates a new [`HandlerType::CallbackQuery`] handler.
     pub fn callback_query<F: Filter>(filter: F) -> Self {
-        Self {
-            update_type: UpdateType::CallbackQuery,
-
-            filter: Some(Box::new(filter)),
-            command: None,
-            endpoint: None,
-            err_handler: None,
-        }
+        Self::with_type(
+            UpdateType::CallbackQuery,
+            Some(Box::new(filter)),
+            Vec::new(),
+        )
     }
 
     /// Creates a new [`HandlerType::InlineQuery`] handler.
     pub fn inline_query<F: Filter>(filter: F) -> Self {
-        Self {
-            update_type: UpdateType::InlineQuery,
+        Self::with_type(UpdateType::InlineQuery, Some(Box::new(filter)), Vec::new())
+    }
 
-            filter: Some(Box::new(filter)),
-            command: None,
-            endpoint: None,
-            err_handler: None,
-        }
+    /// Creates a new [`HandlerType::Event`] handler.
+    pub fn event<E: Send + Sync + 'static>() -> Self {
+        Self::with_type(UpdateType::Event(TypeId::of::<E>()), None, Vec::new())
     }
 
     /// Sets the [`di::Endpoint`].
@@ -112,10 +121,32 @@ impl Handler {
         mut self,
         endpoint: impl di::IntoHandler<I, Handler = H>,
     ) -> Self {
+        if self.name.is_none() {
+            self.name = Some(std::any::type_name_of_val(&endpoint).to_string());
+        }
+
         self.endpoint = Some(Box::new(endpoint.into_handler()));
         self
     }
 
+    /// Sets the name shown in the error logs when the endpoint fails.
+    ///
+    /// Default is the path of the endpoint, like `my_bot::handlers::start`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// let handler = handler::new_message(filters::command("start"))
+    ///     .name("start")
+    ///     .then(|| async { Ok(()) });
+    /// # }
+    /// ```
+    pub fn name<N: ToString>(mut self, name: N) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
     /// Sets the error handler.
     ///
     /// Executed when the [`di::Endpoint`] returns an error.
@@ -128,6 +159,93 @@ impl Handler {
         self
     }
 
+    /// Adds a resource visible only to the endpoint.
+    ///
+    /// Shadows the resources of the same type from the routers and the dispatcher.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// let handler = handler::new_message(filters::command("start"))
+    ///     .then(|greeting: String| async move { Ok(()) })
+    ///     .with_resource(String::from("Hello, world!"));
+    /// # }
+    /// ```
+    pub fn with_resource<R: Clone + Send + Sync + 'static>(mut self, value: R) -> Self {
+        self.resources.insert(value);
+        self
+    }
+
+    /// Sets the priority of the handler.
+    ///
+    /// While overloaded, the [`crate::shedding::LoadShedder`] rejects the handlers
+    /// with a priority lower than its minimum. Doesn't change the order the handlers
+    /// are checked. Default is `0`.
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Prepends `prefix` to the names of the commands.
+    pub(crate) fn prefix_commands(&mut self, prefix: &str) {
+        if let Some(ref mut filter) = self.filter {
+            filter.prefix_commands(prefix);
+        }
+
+        self.commands
+            .iter_mut()
+            .for_each(|command| command.prefix(prefix));
+    }
+
+    /// Attachs the services of the dispatcher consulted by the filter.
+    pub(crate) fn attach(&mut self, services: &di::Injector) {
+        if let Some(ref mut filter) = self.filter {
+            filter.attach(services);
+        }
+    }
+
+    /// Records the handler as the one which failed in the task, see [`failed_handler`].
+    pub(crate) fn record_failure(&self) {
+        let _ = FAILED_HANDLER.try_with(|failed| failed.replace(self.name.clone()));
+    }
+
+    /// Handles the [`UsageError`] injected by a [`Command`] filter which didn't pass.
+    ///
+    /// Runs the error handler with it, if set, otherwise replies the usage.
+    /// Returns `false` if the flow has no usage error.
+    pub(crate) async fn handle_usage_error(
+        &mut self,
+        client: &Client,
+        update: &Update,
+        flow: &Flow,
+    ) -> bool {
+        let Some(error) = flow.injected::<UsageError>().cloned() else {
+            return false;
+        };
+
+        if let Some(err_handler) = self.err_handler.as_ref() {
+            err_handler
+                .run(client.clone(), update.clone(), Box::new(error))
+                .await;
+        } else if let (
+            Some(UsageReply(reply)),
+            Update::NewMessage(message) | Update::MessageEdited(message),
+        ) = (flow.injected::<UsageReply>(), update)
+        {
+            if let Err(e) = message.reply(reply.as_str()).await {
+                log::error!(target: logging::FILTERS, "Error replying the command usage: {:?}", e);
+            }
+        }
+
+        true
+    }
+
+    /// Checks if the handler subscribes to the event.
+    pub(crate) fn is_event(&self, event: &Event) -> bool {
+        self.update_type == UpdateType::Event(event.type_id)
+    }
+
     /// Checks if the update should be handled.
     pub(crate) async fn check(&mut self, client: &Client, update: &Update) -> Flow {
         if self.update_type == *update {
@@ -142,6 +260,19 @@ impl Handler {
     }
 }
 
+/// Runs the future recording the name of the handler which fails, see [`failed_handler`].
+pub(crate) async fn trace_failure<F: Future>(future: F) -> F::Output {
+    FAILED_HANDLER.scope(RefCell::new(None), future).await
+}
+
+/// Returns the name of the handler which failed in the task, if any.
+pub(crate) fn failed_handler() -> Option<String> {
+    FAILED_HANDLER
+        .try_with(|failed| failed.borrow().clone())
+        .ok()
+        .flatten()
+}
+
 /// Update type.
 #[derive(Clone, Debug, Default, PartialEq)]
 pub enum UpdateType {
@@ -160,6 +291,8 @@ pub enum UpdateType {
     /// Raw update handler.
     #[default]
     Raw,
+    /// Internal event handler.
+    Event(TypeId),
 }
 
 impl PartialEq<Update> for UpdateType {
@@ -172,6 +305,7 @@ impl PartialEq<Update> for UpdateType {
             Self::InlineQuery => matches!(other, Update::InlineQuery(_)),
             Self::InlineSend => matches!(other, Update::InlineSend(_)),
             Self::Raw => matches!(other, Update::Raw(_)),
+            Self::Event(_) => false,
         }
     }
 }
@@ -228,12 +362,27 @@ pub fn inline_query<F: Filter>(filter: F) -> Handler {
 ///
 /// Injects [`Option<Update>`].
 pub fn then<I, H: di::Handler>(endpoint: impl di::IntoHandler<I, Handler = H>) -> Handler {
-    Handler {
-        update_type: UpdateType::Raw,
+    Handler::with_type(UpdateType::Raw, None, Vec::new()).then(endpoint)
+}
 
-        filter: None,
-        command: None,
-        endpoint: Some(Box::new(endpoint.into_handler())),
-        err_handler: None,
-    }
+/// Creates a new [`HandlerType::Event`] handler.
+///
+/// Executed when an `E` event is published in the [`crate::EventBus`].
+///
+/// Injects `E`.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// # #[derive(Clone)]
+/// # struct UserVerified(i64);
+/// # let router: ferogram::Router = unimplemented!();
+/// let router = router.register(handler::event::<UserVerified>().then(
+///     |event: UserVerified| async move { Ok(()) },
+/// ));
+/// # }
+/// ```
+pub fn event<E: Send + Sync + 'static>() -> Handler {
+    Handler::event::<E>()
 }
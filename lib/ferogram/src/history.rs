@@ -0,0 +1,521 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! History module.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use grammers_client::{
+    types::{Chat, Media, Message, MessageDeletion},
+    Update,
+};
+
+/// The most words compared by [`diff`] in each text.
+const MAX_DIFF_WORDS: usize = 1000;
+
+/// A message kept by the [`MessageHistory`].
+#[derive(Clone, Debug)]
+pub struct CachedMessage {
+    /// The ID of the chat.
+    pub chat_id: i64,
+    /// The ID of the message.
+    pub id: i32,
+    /// The ID of the sender, if any.
+    pub sender_id: Option<i64>,
+    /// The current text of the message.
+    pub text: String,
+    /// The text before the last edit, if it was edited.
+    pub previous_text: Option<String>,
+    /// The media of the message, if any.
+    pub media: Option<Media>,
+    /// When the message was sent.
+    pub date: SystemTime,
+    /// Whether the chat is a channel, whose message IDs are not shared.
+    in_channel: bool,
+}
+
+impl CachedMessage {
+    /// Creates the cached message from the message.
+    fn from_message(message: &Message) -> Self {
+        Self {
+            chat_id: message.chat().id(),
+            id: message.id(),
+            sender_id: message.sender().map(|sender| sender.id()),
+            text: message.text().to_string(),
+            previous_text: None,
+            media: message.media(),
+            date: message.date().into(),
+            in_channel: match message.chat() {
+                Chat::Channel(_) => true,
+                Chat::Group(group) => group.is_megagroup(),
+                Chat::User(_) => false,
+            },
+        }
+    }
+}
+
+/// The chats cached by the [`MessageHistory`].
+#[derive(Debug, Default)]
+struct Chats {
+    /// The messages of each chat, from the least recently sent or edited, with
+    /// when the chat was last seen.
+    messages: HashMap<i64, (VecDeque<CachedMessage>, u64)>,
+    /// The IDs of the chats by when they were last seen.
+    seen: BTreeMap<u64, i64>,
+    /// The counter of the sightings.
+    tick: u64,
+}
+
+impl Chats {
+    /// Gets the messages of the chat, marking it as the most recently seen.
+    fn touch(&mut self, chat_id: i64) -> &mut VecDeque<CachedMessage> {
+        self.tick += 1;
+        let tick = self.tick;
+
+        let (messages, seen) = self.messages.entry(chat_id).or_default();
+        self.seen.remove(seen);
+        *seen = tick;
+        self.seen.insert(tick, chat_id);
+
+        messages
+    }
+
+    /// Removes the chat and its messages.
+    fn remove(&mut self, chat_id: i64) {
+        if let Some((_, seen)) = self.messages.remove(&chat_id) {
+            self.seen.remove(&seen);
+        }
+    }
+
+    /// Removes the least recently seen chats past `max_chats`.
+    fn evict(&mut self, max_chats: usize) {
+        while self.messages.len() > max_chats {
+            let Some((_, chat_id)) = self.seen.pop_first() else {
+                break;
+            };
+            self.messages.remove(&chat_id);
+        }
+    }
+}
+
+/// A bounded cache of the last messages of each chat.
+///
+/// Keeps the last `capacity` messages of each chat, dropping the least recently
+/// sent or edited ones, so the edited messages can be compared with their
+/// previous text with [`crate::Context::previous_text`], and the deleted ones
+/// retrieved with [`crate::Context::deleted_messages`]. Past `max_chats`, the
+/// least recently active chats are dropped.
+///
+/// The messages older than the retention are dropped, and the chats which opted
+/// out are not cached at all.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use ferogram::{handler, history::{self, MessageHistory}, Context};
+///
+/// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+/// let dispatcher = dispatcher
+///     .message_history(MessageHistory::new(500))
+///     .router(|router| {
+///         router.register(handler::message_edited(ferogram::filters::always).then(
+///             |ctx: Context| async move {
+///                 if let Some(previous) = ctx.previous_text() {
+///                     let diff = history::diff(&previous, &ctx.text().unwrap());
+///                 }
+///
+///                 Ok(())
+///             },
+///         ))
+///     });
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MessageHistory {
+    /// The max messages kept of each chat.
+    capacity: usize,
+    /// The max chats kept.
+    max_chats: usize,
+    /// How long the messages are kept, if limited.
+    retention: Option<Duration>,
+    /// The messages of each chat.
+    chats: Arc<Mutex<Chats>>,
+    /// The chats which are not cached.
+    opted_out: Arc<Mutex<HashSet<i64>>>,
+}
+
+impl MessageHistory {
+    /// Creates a cache keeping the last `capacity` messages of each chat.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            max_chats: 1000,
+            retention: None,
+            chats: Arc::new(Mutex::new(Chats::default())),
+            opted_out: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// Sets the max chats kept, dropping the least recently active ones.
+    ///
+    /// Default is `1000`.
+    pub fn max_chats(mut self, max_chats: usize) -> Self {
+        self.max_chats = max_chats.max(1);
+        self
+    }
+
+    /// Drops the messages sent more than `retention` ago.
+    ///
+    /// By default the messages are only dropped by the capacity.
+    pub fn retention(mut self, retention: Duration) -> Self {
+        self.retention = Some(retention);
+        self
+    }
+
+    /// Stops caching the messages of the chat, removing the cached ones.
+    pub fn opt_out(&self, chat_id: i64) {
+        self.opted_out
+            .lock()
+            .expect("Failed to lock the opted out chats")
+            .insert(chat_id);
+        self.clear(chat_id);
+    }
+
+    /// Caches the messages of the chat again, after [`Self::opt_out`].
+    pub fn opt_in(&self, chat_id: i64) {
+        self.opted_out
+            .lock()
+            .expect("Failed to lock the opted out chats")
+            .remove(&chat_id);
+    }
+
+    /// Returns whether the chat opted out of the cache.
+    pub fn is_opted_out(&self, chat_id: i64) -> bool {
+        self.opted_out
+            .lock()
+            .expect("Failed to lock the opted out chats")
+            .contains(&chat_id)
+    }
+
+    /// Gets the cached message.
+    pub fn get(&self, chat_id: i64, message_id: i32) -> Option<CachedMessage> {
+        self.chats
+            .lock()
+            .expect("Failed to lock the history")
+            .messages
+            .get(&chat_id)?
+            .0
+            .iter()
+            .find(|message| message.id == message_id)
+            .cloned()
+    }
+
+    /// Removes the messages of the chat.
+    pub fn clear(&self, chat_id: i64) {
+        self.chats
+            .lock()
+            .expect("Failed to lock the history")
+            .remove(chat_id);
+    }
+
+    /// Gets the cached messages removed by the deletion.
+    ///
+    /// Deletions outside channels don't tell the chat, so the message IDs are
+    /// searched in every private chat and group.
+    pub(crate) fn deleted(&self, deletion: &MessageDeletion) -> Vec<CachedMessage> {
+        let chats = self.chats.lock().expect("Failed to lock the history");
+        let ids = deletion.messages();
+
+        let candidates = match deletion.channel_id() {
+            Some(channel_id) => chats
+                .messages
+                .get(&channel_id)
+                .into_iter()
+                .flat_map(|(messages, _)| messages)
+                .filter(|message| message.in_channel)
+                .collect::<Vec<_>>(),
+            None => chats
+                .messages
+                .values()
+                .flat_map(|(messages, _)| messages)
+                .filter(|message| !message.in_channel)
+                .collect(),
+        };
+
+        candidates
+            .into_iter()
+            .filter(|message| ids.contains(&message.id) && !self.is_expired(message))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns whether the message is older than the retention.
+    fn is_expired(&self, message: &CachedMessage) -> bool {
+        self.retention.is_some_and(|retention| {
+            message
+                .date
+                .elapsed()
+                .is_ok_and(|elapsed| elapsed > retention)
+        })
+    }
+
+    /// Caches the new and edited messages of the update.
+    ///
+    /// The previous text of an edited message is only replaced when its text
+    /// changes, so the same edit can be recorded again.
+    pub(crate) fn record(&self, update: &Update) {
+        let (message, is_edit) = match update {
+            Update::NewMessage(message) => (message, false),
+            Update::MessageEdited(message) => (message, true),
+            _ => return,
+        };
+
+        let chat_id = message.chat().id();
+        if self.is_opted_out(chat_id) {
+            return;
+        }
+
+        self.store(CachedMessage::from_message(message), is_edit);
+    }
+
+    /// Caches the message, replacing the cached one with the same ID.
+    fn store(&self, message: CachedMessage, is_edit: bool) {
+        let mut chats = self.chats.lock().expect("Failed to lock the history");
+        let messages = chats.touch(message.chat_id);
+
+        let cached = match messages.iter().position(|cached| cached.id == message.id) {
+            Some(index) => {
+                let mut cached = messages.remove(index).expect("Invalid index");
+                if is_edit && cached.text != message.text {
+                    cached.previous_text = Some(std::mem::replace(&mut cached.text, message.text));
+                }
+
+                cached
+            }
+            None => message,
+        };
+
+        messages.push_back(cached);
+        messages.retain(|message| !self.is_expired(message));
+        while messages.len() > self.capacity {
+            messages.pop_front();
+        }
+
+        chats.evict(self.max_chats);
+    }
+}
+
+/// A change between two texts, by [`diff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Diff {
+    /// The words kept.
+    Kept(String),
+    /// The words added.
+    Added(String),
+    /// The words removed.
+    Removed(String),
+}
+
+/// Compares the words of two texts.
+///
+/// Texts longer than 1000 words are not compared, the whole old text is removed
+/// and the whole new text added.
+///
+/// # Example
+///
+/// ```
+/// use ferogram::history::{diff, Diff};
+///
+/// assert_eq!(
+///     diff("I like cats", "I love cats"),
+///     vec![
+///         Diff::Kept(String::from("I")),
+///         Diff::Removed(String::from("like")),
+///         Diff::Added(String::from("love")),
+///         Diff::Kept(String::from("cats")),
+///     ]
+/// );
+/// ```
+pub fn diff(old: &str, new: &str) -> Vec<Diff> {
+    let old = old.split_whitespace().collect::<Vec<_>>();
+    let new = new.split_whitespace().collect::<Vec<_>>();
+
+    // The table below grows with the product of the lengths.
+    if old.len() > MAX_DIFF_WORDS || new.len() > MAX_DIFF_WORDS {
+        let mut changes = Vec::new();
+        if !old.is_empty() {
+            changes.push(Diff::Removed(old.join(" ")));
+        }
+        if !new.is_empty() {
+            changes.push(Diff::Added(new.join(" ")));
+        }
+
+        return changes;
+    }
+
+    // The lengths of the longest common subsequences of the suffixes.
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::<Diff>::new();
+    let mut push = |change: fn(String) -> Diff, word: &str| {
+        let merged = match (changes.last_mut(), change(String::new())) {
+            (Some(Diff::Kept(words)), Diff::Kept(_))
+            | (Some(Diff::Added(words)), Diff::Added(_))
+            | (Some(Diff::Removed(words)), Diff::Removed(_)) => {
+                words.push(' ');
+                words.push_str(word);
+                true
+            }
+            _ => false,
+        };
+
+        if !merged {
+            changes.push(change(word.to_string()));
+        }
+    };
+
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            push(Diff::Kept, old[i]);
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            push(Diff::Removed, old[i]);
+            i += 1;
+        } else {
+            push(Diff::Added, new[j]);
+            j += 1;
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(chat_id: i64, id: i32, text: &str) -> CachedMessage {
+        CachedMessage {
+            chat_id,
+            id,
+            sender_id: None,
+            text: text.to_string(),
+            previous_text: None,
+            media: None,
+            date: SystemTime::now(),
+            in_channel: false,
+        }
+    }
+
+    #[test]
+    fn test_store() {
+        let history = MessageHistory::new(2);
+
+        history.store(message(1, 1, "hello"), false);
+        history.store(message(1, 1, "hello world"), true);
+        let cached = history.get(1, 1).unwrap();
+        assert_eq!(cached.text, "hello world");
+        assert_eq!(cached.previous_text.as_deref(), Some("hello"));
+
+        // The same edit doesn't replace the previous text.
+        history.store(message(1, 1, "hello world"), true);
+        assert_eq!(
+            history.get(1, 1).unwrap().previous_text.as_deref(),
+            Some("hello")
+        );
+
+        history.store(message(1, 2, "two"), false);
+        history.store(message(1, 3, "three"), false);
+        assert!(history.get(1, 1).is_none());
+        assert!(history.get(1, 3).is_some());
+    }
+
+    #[test]
+    fn test_retention() {
+        let history = MessageHistory::new(10).retention(Duration::from_secs(60));
+
+        let mut old = message(1, 1, "old");
+        old.date = SystemTime::now() - Duration::from_secs(120);
+        history.store(old, false);
+        history.store(message(1, 2, "new"), false);
+
+        assert!(history.get(1, 1).is_none());
+        assert!(history.get(1, 2).is_some());
+    }
+
+    #[test]
+    fn test_chat_eviction() {
+        let history = MessageHistory::new(10).max_chats(2);
+
+        history.store(message(1, 1, "one"), false);
+        history.store(message(2, 1, "two"), false);
+        // Chat 1 becomes the most recently active.
+        history.store(message(1, 2, "one again"), false);
+        history.store(message(3, 1, "three"), false);
+
+        assert!(history.get(1, 1).is_some());
+        assert!(history.get(2, 1).is_none());
+        assert!(history.get(3, 1).is_some());
+
+        history.clear(1);
+        assert!(history.get(1, 2).is_none());
+        assert_eq!(history.chats.lock().unwrap().seen.len(), 1);
+    }
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(
+            diff("I like cats", "I love cats"),
+            vec![
+                Diff::Kept(String::from("I")),
+                Diff::Removed(String::from("like")),
+                Diff::Added(String::from("love")),
+                Diff::Kept(String::from("cats")),
+            ]
+        );
+        assert_eq!(
+            diff("a b", "a b c d"),
+            vec![
+                Diff::Kept(String::from("a b")),
+                Diff::Added(String::from("c d")),
+            ]
+        );
+        assert_eq!(diff("", "hi"), vec![Diff::Added(String::from("hi"))]);
+        assert!(diff("", "").is_empty());
+    }
+
+    #[test]
+    fn test_diff_limit() {
+        let long = vec!["word"; MAX_DIFF_WORDS + 1].join(" ");
+
+        assert_eq!(
+            diff("short", &long),
+            vec![
+                Diff::Removed(String::from("short")),
+                Diff::Added(long.clone()),
+            ]
+        );
+        assert_eq!(diff(&long, ""), vec![Diff::Removed(long.clone())]);
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Idempotency module.
+
+use std::sync::Arc;
+
+use tokio::sync::OwnedMutexGuard;
+
+use crate::{utils::KeyedLock, Result, Storage};
+
+/// A storage-backed record of the messages sent for each idempotency key.
+///
+/// Used by [`crate::Context::send_once`] to skip the messages which were already
+/// sent, like when a handler is retried or runs again after a crash, so the same
+/// notification is not sent twice.
+///
+/// The record is attached with [`crate::Dispatcher::idempotency`].
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use ferogram::{idempotency::Idempotency, MemoryStorage};
+///
+/// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+/// let dispatcher = dispatcher.idempotency(Idempotency::new(MemoryStorage::default()));
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Idempotency {
+    /// The storage of the sent messages.
+    storage: Arc<dyn Storage>,
+    /// The mutex of each key, so a key is not sent twice at the same time.
+    locks: KeyedLock<String>,
+}
+
+impl Idempotency {
+    /// Creates the record backed by `storage`.
+    pub fn new<S: Storage>(storage: S) -> Self {
+        Self {
+            storage: Arc::new(storage),
+            locks: KeyedLock::default(),
+        }
+    }
+
+    /// Returns the IDs of the messages sent for the key, if it was sent.
+    pub async fn sent(&self, key: &str) -> Result<Option<Vec<i32>>> {
+        let Some(value) = self.storage.get(&sent_key(key)).await? else {
+            return Ok(None);
+        };
+
+        Ok(Some(
+            value.split(',').filter_map(|id| id.parse().ok()).collect(),
+        ))
+    }
+
+    /// Records the IDs of the messages sent for the key.
+    pub async fn record(&self, key: &str, message_ids: &[i32]) -> Result<()> {
+        let value = message_ids
+            .iter()
+            .map(i32::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        self.storage.set(&sent_key(key), value).await
+    }
+
+    /// Forgets the key, so it can be sent again.
+    pub async fn forget(&self, key: &str) -> Result<()> {
+        self.storage.remove(&sent_key(key)).await
+    }
+
+    /// Locks the key until the guard is dropped.
+    pub(crate) async fn lock(&self, key: &str) -> OwnedMutexGuard<()> {
+        self.locks.lock(key.to_string()).await
+    }
+}
+
+impl std::fmt::Debug for Idempotency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Idempotency").finish_non_exhaustive()
+    }
+}
+
+/// Returns the storage key of the messages sent for the key.
+fn sent_key(key: &str) -> String {
+    format!("sent:{}", key)
+}
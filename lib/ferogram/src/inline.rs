@@ -0,0 +1,159 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Inline module.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::utils::{decode_payload, encode_payload};
+
+/// A step of a multi-step inline query, injected by [`crate::filter::inline_prefix`].
+///
+/// Ex: `@bot remind 5m call mom` has the prefix `remind` and the arguments
+/// `5m`, `call` and `mom`.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use ferogram::{inline::InlineStep, filter, handler};
+/// use grammers_client::types::InlineQuery;
+///
+/// let handler = handler::inline_query(filter::inline_prefix("remind")).then(
+///     |query: InlineQuery, step: InlineStep| async move {
+///         match step.completed() {
+///             0 => { /* suggest durations */ }
+///             _ => { /* confirm the reminder */ }
+///         }
+///
+///         Ok(())
+///     },
+/// );
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InlineStep {
+    /// The ID of the user.
+    pub user_id: i64,
+    /// The prefix of the query, lowercased.
+    pub prefix: String,
+    /// The arguments after the prefix.
+    pub args: Vec<String>,
+    /// Whether the user is still typing the last argument.
+    pub typing: bool,
+    /// The state decoded from the offset, sent when scrolling the results.
+    pub state: Option<Vec<u8>>,
+}
+
+impl InlineStep {
+    /// Returns the number of arguments completely typed, which is the current step.
+    pub fn completed(&self) -> usize {
+        if self.typing {
+            self.args.len().saturating_sub(1)
+        } else {
+            self.args.len()
+        }
+    }
+
+    /// Returns the argument at the index.
+    pub fn arg(&self, index: usize) -> Option<&str> {
+        self.args.get(index).map(String::as_str)
+    }
+
+    /// Returns the arguments from the index, joined by spaces.
+    ///
+    /// Ex: the reminder text `call mom` from `remind 5m call mom`.
+    pub fn rest(&self, index: usize) -> String {
+        self.args.get(index..).unwrap_or_default().join(" ")
+    }
+
+    /// Encodes the state as the next offset of the results.
+    ///
+    /// Returns `None` if the state is longer than the 48 bytes that fit in an offset.
+    pub fn next_offset(state: &[u8]) -> Option<String> {
+        encode_payload(state)
+    }
+
+    /// Parses the inline query text.
+    ///
+    /// Returns `None` if the text does not start with the prefix.
+    pub(crate) fn parse(user_id: i64, prefix: &str, text: &str, offset: &str) -> Option<Self> {
+        let mut words = text.split_whitespace();
+        if !words.next()?.eq_ignore_ascii_case(prefix) {
+            return None;
+        }
+
+        Some(Self {
+            user_id,
+            prefix: prefix.to_lowercase(),
+            args: words.map(String::from).collect(),
+            typing: !text.ends_with(char::is_whitespace),
+            state: (!offset.is_empty())
+                .then(|| decode_payload(offset))
+                .flatten(),
+        })
+    }
+}
+
+/// The states of the multi-step inline queries, keyed by the user and prefix.
+///
+/// Keeps what the previous steps resolved, like a parsed duration, while the user
+/// keeps typing.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use ferogram::inline::{InlineStates, InlineStep};
+///
+/// async fn remind(step: InlineStep, states: InlineStates) -> ferogram::Result<()> {
+///     if step.completed() == 1 {
+///         states.set(&step, step.arg(0).unwrap());
+///     }
+///
+///     let duration = states.get(&step);
+///
+///     Ok(())
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct InlineStates {
+    /// The state of each user and prefix.
+    states: Arc<Mutex<HashMap<(i64, String), String>>>,
+}
+
+impl InlineStates {
+    /// Gets the state of the user in the step's prefix.
+    pub fn get(&self, step: &InlineStep) -> Option<String> {
+        self.states
+            .lock()
+            .expect("Failed to lock the states")
+            .get(&(step.user_id, step.prefix.clone()))
+            .cloned()
+    }
+
+    /// Sets the state of the user in the step's prefix.
+    pub fn set<S: ToString>(&self, step: &InlineStep, state: S) {
+        self.states
+            .lock()
+            .expect("Failed to lock the states")
+            .insert((step.user_id, step.prefix.clone()), state.to_string());
+    }
+
+    /// Removes the state of the user in the step's prefix, like when the result is sent.
+    pub fn remove(&self, step: &InlineStep) {
+        self.states
+            .lock()
+            .expect("Failed to lock the states")
+            .remove(&(step.user_id, step.prefix.clone()));
+    }
+}
@@ -0,0 +1,532 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Jobs module.
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use grammers_client::Client;
+use tokio::sync::{Mutex, Notify};
+
+use crate::{event::Event, utils::random_u64, Dispatcher, Result, Storage};
+
+/// The storage key of the index of the jobs.
+const INDEX_KEY: &str = "jobs:index";
+/// The longest sleep of the worker.
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+/// How many times a job runs before it is kept as failed.
+const MAX_ATTEMPTS: u32 = 5;
+/// The delay before retrying a failed job, doubled after each attempt.
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// A job which can be stored and executed later.
+///
+/// The jobs are executed by the handlers created with [`crate::handler::event`],
+/// like the events of the [`crate::EventBus`].
+///
+/// # Example
+///
+/// ```no_run
+/// use ferogram::jobs::Job;
+///
+/// #[derive(Clone)]
+/// struct RemindJob {
+///     chat_id: i64,
+///     text: String,
+/// }
+///
+/// impl Job for RemindJob {
+///     const NAME: &'static str = "remind";
+///
+///     fn encode(&self) -> String {
+///         format!("{}:{}", self.chat_id, self.text)
+///     }
+///
+///     fn decode(value: &str) -> Option<Self> {
+///         let (chat_id, text) = value.split_once(':')?;
+///
+///         Some(Self {
+///             chat_id: chat_id.parse().ok()?,
+///             text: text.to_string(),
+///         })
+///     }
+/// }
+/// ```
+pub trait Job: Clone + Send + Sync + 'static {
+    /// The unique name of the job type, used to decode the stored jobs.
+    const NAME: &'static str;
+
+    /// Encodes the job to be stored.
+    fn encode(&self) -> String;
+
+    /// Decodes a stored job.
+    fn decode(value: &str) -> Option<Self>;
+}
+
+/// Decodes a stored job as an event.
+type Decoder = fn(&str) -> Option<Event>;
+
+/// A queue of delayed jobs, backed by a [`Storage`].
+///
+/// The jobs survive restarts and are executed when due, by the handlers created
+/// with [`crate::handler::event`], with access to the dependencies. A job is only
+/// removed after being handled, so it runs again if the process dies meanwhile.
+///
+/// A job whose handler fails is retried with backoff, and after 5 attempts
+/// it is kept as failed, see [`JobQueue::failed`].
+///
+/// The index of the jobs is a single key updated in place, so only one process
+/// should use the queue of a storage.
+///
+/// The queue is attached with [`Dispatcher::job_queue`] and injected in the handlers.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use std::time::Duration;
+///
+/// use ferogram::{handler, jobs::JobQueue, Context, MemoryStorage};
+///
+/// # #[derive(Clone)]
+/// # struct RemindJob { chat_id: i64, text: String }
+/// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+/// let queue = JobQueue::new(MemoryStorage::default()).job::<RemindJob>();
+///
+/// let dispatcher = dispatcher.job_queue(queue).router(|router| {
+///     router.register(handler::event::<RemindJob>().then(
+///         |ctx: Context, job: RemindJob| async move {
+///             ctx.client().send_message(job.chat_id, job.text).await?;
+///
+///             Ok(())
+///         },
+///     ))
+/// });
+///
+/// async fn remind(queue: JobQueue) -> ferogram::Result<()> {
+///     queue
+///         .push_in(Duration::from_secs(300), RemindJob { chat_id: 1234, text: String::from("Call mom") })
+///         .await?;
+///
+///     Ok(())
+/// }
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct JobQueue {
+    /// The storage of the jobs.
+    storage: Arc<dyn Storage>,
+    /// The decoders of the registered job types, by name.
+    decoders: HashMap<&'static str, Decoder>,
+    /// Serializes the changes to the index.
+    lock: Arc<Mutex<()>>,
+    /// Wakes the worker when a job is pushed.
+    notify: Arc<Notify>,
+}
+
+impl JobQueue {
+    /// Creates the queue backed by `storage`.
+    pub fn new<S: Storage>(storage: S) -> Self {
+        Self {
+            storage: Arc::new(storage),
+            decoders: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            notify: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Registers the job type, so the stored jobs of it can be executed.
+    ///
+    /// The jobs of unregistered types are kept until a process registers them.
+    pub fn job<J: Job>(mut self) -> Self {
+        self.decoders.insert(J::NAME, decode::<J>);
+        self
+    }
+
+    /// Pushes a job to be executed after the delay.
+    ///
+    /// Returns the ID of the job.
+    pub async fn push_in<J: Job>(&self, delay: Duration, job: J) -> Result<u64> {
+        self.push_at(SystemTime::now() + delay, job).await
+    }
+
+    /// Pushes a job to be executed at the time.
+    ///
+    /// Returns the ID of the job.
+    pub async fn push_at<J: Job>(&self, time: SystemTime, job: J) -> Result<u64> {
+        let id = random_u64();
+        let due = time
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+
+        let _guard = self.lock.lock().await;
+
+        self.storage.set(&job_key(id), job.encode()).await?;
+
+        let mut entries = self.entries().await?;
+        entries.push(Entry {
+            id,
+            due,
+            attempts: 0,
+            name: J::NAME.to_string(),
+        });
+        self.save_entries(&entries).await?;
+
+        self.notify.notify_one();
+
+        Ok(id)
+    }
+
+    /// Cancels the job.
+    ///
+    /// Returns `false` if the job was not found, like when it was already executed.
+    pub async fn cancel(&self, id: u64) -> Result<bool> {
+        let _guard = self.lock.lock().await;
+
+        let mut entries = self.entries().await?;
+        let len = entries.len();
+        entries.retain(|entry| entry.id != id);
+
+        if entries.len() == len {
+            return Ok(false);
+        }
+
+        self.save_entries(&entries).await?;
+        self.storage.remove(&job_key(id)).await?;
+
+        Ok(true)
+    }
+
+    /// Returns the IDs of the jobs which failed every attempt.
+    ///
+    /// They are kept until retried or cancelled.
+    pub async fn failed(&self) -> Result<Vec<u64>> {
+        Ok(self
+            .entries()
+            .await?
+            .into_iter()
+            .filter(Entry::is_failed)
+            .map(|entry| entry.id)
+            .collect())
+    }
+
+    /// Retries the failed job now, with all its attempts.
+    ///
+    /// Returns `false` if the job was not found.
+    pub async fn retry(&self, id: u64) -> Result<bool> {
+        let _guard = self.lock.lock().await;
+
+        let mut entries = self.entries().await?;
+        let Some(entry) = entries.iter_mut().find(|entry| entry.id == id) else {
+            return Ok(false);
+        };
+        entry.due = now();
+        entry.attempts = 0;
+        self.save_entries(&entries).await?;
+
+        self.notify.notify_one();
+
+        Ok(true)
+    }
+
+    /// Returns the number of pending jobs, without the failed ones.
+    pub async fn len(&self) -> Result<usize> {
+        Ok(self
+            .entries()
+            .await?
+            .iter()
+            .filter(|entry| !entry.is_failed())
+            .count())
+    }
+
+    /// Returns whether there are no pending jobs.
+    pub async fn is_empty(&self) -> Result<bool> {
+        Ok(self.len().await? == 0)
+    }
+
+    /// Executes the due jobs, forever.
+    ///
+    /// Spawned by the client when it runs.
+    pub(crate) async fn run(self, client: Client, dispatcher: Dispatcher) {
+        loop {
+            let sleep = match self.run_due(&client, &dispatcher).await {
+                Ok(next_due) => next_due.map_or(MAX_SLEEP, |due| {
+                    Duration::from_secs(due.saturating_sub(now())).min(MAX_SLEEP)
+                }),
+                Err(e) => {
+                    log::error!("Error running the jobs: {:?}", e);
+                    MAX_SLEEP
+                }
+            };
+
+            let _ = tokio::time::timeout(sleep, self.notify.notified()).await;
+        }
+    }
+
+    /// Executes the due jobs, the oldest first.
+    ///
+    /// Returns when the next registered job is due.
+    async fn run_due(&self, client: &Client, dispatcher: &Dispatcher) -> Result<Option<u64>> {
+        let entries = self
+            .entries()
+            .await?
+            .into_iter()
+            .filter(|entry| !entry.is_failed() && self.decoders.contains_key(entry.name.as_str()))
+            .collect::<Vec<_>>();
+        let (due, next_due) = split_due(entries, now());
+
+        for mut entry in due {
+            let value = self.storage.get(&job_key(entry.id)).await?;
+
+            match value
+                .as_deref()
+                .and_then(self.decoders[entry.name.as_str()])
+            {
+                Some(event) => {
+                    let mut dispatcher = dispatcher.clone();
+                    if let Err(e) = dispatcher.handle_event(client, &event).await {
+                        entry.fail(now());
+                        log::error!(
+                            "Error handling job {:?} (attempt {}/{}): {:?}",
+                            entry.name,
+                            entry.attempts,
+                            MAX_ATTEMPTS,
+                            e
+                        );
+
+                        self.update(entry).await?;
+                        continue;
+                    }
+                }
+                None => log::warn!("Dropping undecodable job {:?}", entry.name),
+            }
+
+            self.cancel(entry.id).await?;
+        }
+
+        Ok(next_due)
+    }
+
+    /// Updates the entry in the index, if it was not cancelled meanwhile.
+    async fn update(&self, entry: Entry) -> Result<()> {
+        let _guard = self.lock.lock().await;
+
+        let mut entries = self.entries().await?;
+        if let Some(stored) = entries.iter_mut().find(|stored| stored.id == entry.id) {
+            *stored = entry;
+            self.save_entries(&entries).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads the index of the jobs.
+    async fn entries(&self) -> Result<Vec<Entry>> {
+        let index = self.storage.get(INDEX_KEY).await?.unwrap_or_default();
+
+        Ok(index.lines().filter_map(Entry::decode).collect())
+    }
+
+    /// Saves the index of the jobs.
+    async fn save_entries(&self, entries: &[Entry]) -> Result<()> {
+        let index = entries
+            .iter()
+            .map(Entry::encode)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        self.storage.set(INDEX_KEY, index).await
+    }
+}
+
+/// A job in the index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Entry {
+    /// The ID of the job.
+    id: u64,
+    /// When the job is due, in seconds since the Unix epoch.
+    due: u64,
+    /// How many times the handler of the job failed.
+    attempts: u32,
+    /// The name of the job type.
+    name: String,
+}
+
+impl Entry {
+    /// Encodes the entry as `id:due:attempts:name`.
+    fn encode(&self) -> String {
+        format!("{}:{}:{}:{}", self.id, self.due, self.attempts, self.name)
+    }
+
+    /// Decodes an `id:due:attempts:name` entry.
+    fn decode(value: &str) -> Option<Self> {
+        let mut parts = value.splitn(4, ':');
+
+        Some(Self {
+            id: parts.next()?.parse().ok()?,
+            due: parts.next()?.parse().ok()?,
+            attempts: parts.next()?.parse().ok()?,
+            name: parts.next()?.to_string(),
+        })
+    }
+
+    /// Records a failed attempt, scheduling the next one with backoff.
+    fn fail(&mut self, now: u64) {
+        self.attempts += 1;
+        self.due = now + (RETRY_DELAY.as_secs() << (self.attempts - 1).min(16));
+    }
+
+    /// Checks if the job failed every attempt.
+    fn is_failed(&self) -> bool {
+        self.attempts >= MAX_ATTEMPTS
+    }
+}
+
+/// Splits the entries in the due ones, the oldest first, and when the next one is due.
+fn split_due(mut entries: Vec<Entry>, now: u64) -> (Vec<Entry>, Option<u64>) {
+    entries.sort_by_key(|entry| entry.due);
+
+    let next_due = entries
+        .iter()
+        .find(|entry| entry.due > now)
+        .map(|entry| entry.due);
+    entries.retain(|entry| entry.due <= now);
+
+    (entries, next_due)
+}
+
+/// Returns the storage key of the job.
+fn job_key(id: u64) -> String {
+    format!("jobs:{}", id)
+}
+
+/// Returns the seconds since the Unix epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
+
+/// Decodes a stored job of the type as an event.
+fn decode<J: Job>(value: &str) -> Option<Event> {
+    J::decode(value).map(Event::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryStorage;
+
+    #[derive(Clone)]
+    struct TestJob(String);
+
+    impl Job for TestJob {
+        const NAME: &'static str = "test";
+
+        fn encode(&self) -> String {
+            self.0.clone()
+        }
+
+        fn decode(value: &str) -> Option<Self> {
+            Some(Self(value.to_string()))
+        }
+    }
+
+    fn entry(id: u64, due: u64) -> Entry {
+        Entry {
+            id,
+            due,
+            attempts: 0,
+            name: String::from("test:job"),
+        }
+    }
+
+    #[test]
+    fn test_entry_encoding() {
+        let mut entry = entry(42, 1700000000);
+        entry.attempts = 2;
+
+        assert_eq!(entry.encode(), "42:1700000000:2:test:job");
+        assert_eq!(Entry::decode(&entry.encode()), Some(entry));
+
+        assert_eq!(Entry::decode("42:1700000000:test"), None);
+        assert_eq!(Entry::decode("abc:1:0:test"), None);
+        assert_eq!(Entry::decode(""), None);
+    }
+
+    #[test]
+    fn test_split_due() {
+        let entries = vec![entry(1, 300), entry(2, 100), entry(3, 500), entry(4, 200)];
+
+        let (due, next_due) = split_due(entries, 300);
+        assert_eq!(
+            due.iter().map(|entry| entry.id).collect::<Vec<_>>(),
+            [2, 4, 1]
+        );
+        assert_eq!(next_due, Some(500));
+
+        let (due, next_due) = split_due(vec![entry(1, 100)], 50);
+        assert!(due.is_empty());
+        assert_eq!(next_due, Some(100));
+    }
+
+    #[test]
+    fn test_entry_fail() {
+        let mut entry = entry(1, 0);
+
+        entry.fail(1000);
+        assert_eq!(entry.due, 1000 + RETRY_DELAY.as_secs());
+        entry.fail(1000);
+        assert_eq!(entry.due, 1000 + RETRY_DELAY.as_secs() * 2);
+
+        for _ in 2..MAX_ATTEMPTS {
+            assert!(!entry.is_failed());
+            entry.fail(1000);
+        }
+        assert!(entry.is_failed());
+    }
+
+    #[tokio::test]
+    async fn test_queue() {
+        let queue = JobQueue::new(MemoryStorage::default()).job::<TestJob>();
+
+        let later = queue
+            .push_in(Duration::from_secs(60), TestJob(String::from("later")))
+            .await
+            .unwrap();
+        let sooner = queue
+            .push_at(UNIX_EPOCH, TestJob(String::from("sooner")))
+            .await
+            .unwrap();
+        assert_eq!(queue.len().await.unwrap(), 2);
+
+        let (due, next_due) = split_due(queue.entries().await.unwrap(), now());
+        assert_eq!(
+            due.iter().map(|entry| entry.id).collect::<Vec<_>>(),
+            [sooner]
+        );
+        assert!(next_due.is_some_and(|due| due >= now() + 59));
+
+        let mut failed = due[0].clone();
+        failed.attempts = MAX_ATTEMPTS;
+        queue.update(failed).await.unwrap();
+        assert_eq!(queue.failed().await.unwrap(), [sooner]);
+        assert_eq!(queue.len().await.unwrap(), 1);
+
+        assert!(queue.retry(sooner).await.unwrap());
+        assert!(queue.failed().await.unwrap().is_empty());
+
+        assert!(queue.cancel(later).await.unwrap());
+        assert!(!queue.cancel(later).await.unwrap());
+        assert_eq!(queue.len().await.unwrap(), 1);
+    }
+}
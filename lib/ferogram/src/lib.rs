@@ -10,33 +10,86 @@
 //!
 //! The main module of the library.
 
+mod alias;
+pub mod analytics;
+pub mod antispam;
+pub mod broadcast;
+mod cache;
+mod cancellation;
+pub mod captcha;
 mod client;
 mod context;
+mod correlation;
 pub(crate) mod di;
 mod dispatcher;
+mod entity;
 pub mod error;
 mod error_handler;
+mod event;
+mod export;
 pub mod filter;
 pub(crate) mod filters;
+pub mod flags;
 pub mod flow;
+pub mod greeting;
 pub mod handler;
+pub mod history;
+pub mod idempotency;
+pub mod inline;
+pub mod jobs;
+pub mod logging;
+mod media;
+mod message;
 mod middleware;
+pub mod notes;
+pub mod outbox;
 mod plugin;
+mod poll;
+mod reconnect;
 mod router;
+mod settings;
+pub mod shedding;
+mod slow_mode;
+mod storage;
+mod summary;
+mod supervisor;
+#[cfg(feature = "test-utils")]
+pub mod test;
+mod update_source;
+mod upload;
 pub mod utils;
 
+pub use alias::CommandAliases;
+pub use cache::{Cache, CacheStats, ChatMigrations};
+pub use cancellation::CancellationToken;
 pub use client::{Client, ClientBuilder as Builder};
 pub use context::Context;
+pub use correlation::CorrelationId;
 pub use di::Injector;
-pub use dispatcher::Dispatcher;
+pub use dispatcher::{
+    DispatchStats, Dispatcher, DispatcherHandle, DispatcherSnapshot, PluginState,
+};
+pub use entity::{Entity, EntityKind};
 pub use error::Error;
 pub(crate) use error_handler::ErrorHandler;
+pub use event::EventBus;
+pub use export::{ExportFormat, ExportOptions};
 pub use filter::Filter;
 pub(crate) use flow::Flow;
 pub(crate) use handler::Handler;
+pub use media::{MediaBuilder, PaidMedia, PaidMediaPurchase};
+pub use message::MessageBuilder;
 pub use middleware::{Middleware, MiddlewareStack};
-pub use plugin::Plugin;
+pub use plugin::{Plugin, PluginDisabled};
+pub use poll::PollBuilder;
+pub use reconnect::{Reconnect, ReconnectEvent};
 pub use router::Router;
+pub use settings::{ChatSettings, PrefixProvider};
+pub use storage::{MemoryStorage, Storage};
+pub use summary::{UpdateFormatter, UpdateSummary};
+pub use supervisor::Supervisor;
+pub use update_source::UpdateSource;
+pub use upload::{ProgressCallback, UploadOptions};
 
 #[cfg(feature = "lua")]
 pub mod lua;
@@ -0,0 +1,115 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Logging module.
+//!
+//! The logs of the dispatcher, the filters and the plugins use the [`DISPATCHER`],
+//! [`FILTERS`] and [`PLUGINS`] targets, the others use their module paths.
+
+use std::sync::RwLock;
+
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+/// The target of the dispatcher logs.
+pub const DISPATCHER: &str = "ferogram::dispatcher";
+
+/// The target of the filters logs.
+pub const FILTERS: &str = "ferogram::filters";
+
+/// The target of the plugins logs.
+pub const PLUGINS: &str = "ferogram::plugins";
+
+/// The levels set at runtime, by target prefix.
+static LEVELS: RwLock<Vec<(String, LevelFilter)>> = RwLock::new(Vec::new());
+
+/// Installs the logger, wrapped to apply the levels set at runtime.
+///
+/// The records still pass through the filters of the logger, so a target can only
+/// be silenced or restored within what the logger accepts.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn example() {
+/// let logger = env_logger::Builder::from_default_env().build();
+/// ferogram::logging::init(logger).unwrap();
+///
+/// ferogram::logging::set_level(ferogram::logging::FILTERS, log::LevelFilter::Warn);
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if a logger was already installed.
+pub fn init<L: Log + 'static>(logger: L) -> Result<(), SetLoggerError> {
+    log::set_logger(Box::leak(Box::new(RuntimeLevels { inner: logger })))?;
+    log::set_max_level(LevelFilter::Trace);
+
+    Ok(())
+}
+
+/// Sets the level of the target and its children, like `ferogram::filters`.
+///
+/// Only applies to the logger installed with [`init`].
+pub fn set_level(target: &str, level: LevelFilter) {
+    let mut levels = LEVELS.write().expect("Failed to lock the log levels");
+
+    match levels.iter_mut().find(|(prefix, _)| prefix == target) {
+        Some((_, current)) => *current = level,
+        None => levels.push((target.to_string(), level)),
+    }
+}
+
+/// Removes the level set for the target, falling back to the logger filters.
+pub fn reset_level(target: &str) {
+    LEVELS
+        .write()
+        .expect("Failed to lock the log levels")
+        .retain(|(prefix, _)| prefix != target);
+}
+
+/// Returns the level set for the target, by its longest prefix.
+fn level_of(target: &str) -> Option<LevelFilter> {
+    LEVELS
+        .read()
+        .expect("Failed to lock the log levels")
+        .iter()
+        .filter(|(prefix, _)| {
+            target == prefix
+                || target
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.starts_with("::"))
+        })
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, level)| *level)
+}
+
+/// A logger applying the levels set at runtime.
+struct RuntimeLevels<L> {
+    /// The wrapped logger.
+    inner: L,
+}
+
+impl<L: Log> Log for RuntimeLevels<L> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        match level_of(metadata.target()) {
+            Some(level) => metadata.level() <= level && self.inner.enabled(metadata),
+            None => self.inner.enabled(metadata),
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
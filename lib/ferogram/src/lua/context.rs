@@ -0,0 +1,70 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Context module.
+
+use mlua::{MetaMethod, UserData, UserDataFields, UserDataMethods};
+
+use super::Message;
+
+/// The context of an update.
+#[derive(Clone, Debug)]
+pub struct Context(crate::Context);
+
+impl UserData for Context {
+    fn add_fields<F: UserDataFields<Self>>(fields: &mut F) {
+        fields.add_field_method_get("text", |_, this| Ok(this.0.text()));
+        fields.add_field_method_get("chat_id", |_, this| Ok(this.0.chat().map(|c| c.id())));
+        fields.add_field_method_get("sender_id", |_, this| Ok(this.0.sender().map(|s| s.id())));
+    }
+
+    fn add_methods<M: UserDataMethods<Self>>(methods: &mut M) {
+        methods.add_async_method("message", |_, this, ()| async move {
+            Ok(this.0.message().await.map(Message::from))
+        });
+        methods.add_async_method("send", |_, this, text: String| async move {
+            this.0
+                .send(text)
+                .await
+                .map(Message::from)
+                .map_err(mlua::Error::external)
+        });
+        methods.add_async_method("reply", |_, this, text: String| async move {
+            this.0
+                .reply(text)
+                .await
+                .map(Message::from)
+                .map_err(mlua::Error::external)
+        });
+        methods.add_async_method("edit", |_, this, text: String| async move {
+            this.0.edit(text).await.map_err(mlua::Error::external)
+        });
+
+        methods.add_meta_method(MetaMethod::ToString, |_, this, ()| {
+            Ok(format!("{:?}", this.0))
+        });
+    }
+}
+
+impl From<crate::Context> for Context {
+    fn from(ctx: crate::Context) -> Self {
+        Self(ctx)
+    }
+}
+
+impl From<&crate::Context> for Context {
+    fn from(ctx: &crate::Context) -> Self {
+        Self(ctx.clone())
+    }
+}
+
+impl From<Context> for crate::Context {
+    fn from(ctx: Context) -> Self {
+        ctx.0
+    }
+}
@@ -0,0 +1,58 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Message module.
+
+use grammers_client::types;
+use mlua::{MetaMethod, UserData, UserDataFields, UserDataMethods};
+
+/// A message.
+#[derive(Clone, Debug)]
+pub struct Message(types::Message);
+
+impl UserData for Message {
+    fn add_fields<F: UserDataFields<Self>>(fields: &mut F) {
+        fields.add_field_method_get("id", |_, this| Ok(this.0.id()));
+        fields.add_field_method_get("text", |_, this| Ok(this.0.text().to_string()));
+        fields.add_field_method_get("chat_id", |_, this| Ok(this.0.chat().id()));
+        fields.add_field_method_get("sender_id", |_, this| Ok(this.0.sender().map(|s| s.id())));
+        fields.add_field_method_get("date", |_, this| Ok(this.0.date().timestamp()));
+    }
+
+    fn add_methods<M: UserDataMethods<Self>>(methods: &mut M) {
+        methods.add_async_method("reply", |_, this, text: String| async move {
+            this.0
+                .reply(text)
+                .await
+                .map(Message::from)
+                .map_err(mlua::Error::external)
+        });
+        methods.add_async_method("edit", |_, this, text: String| async move {
+            this.0.edit(text).await.map_err(mlua::Error::external)
+        });
+        methods.add_async_method("delete", |_, this, ()| async move {
+            this.0.delete().await.map_err(mlua::Error::external)
+        });
+
+        methods.add_meta_method(MetaMethod::ToString, |_, this, ()| {
+            Ok(format!("{:?}", this.0))
+        });
+    }
+}
+
+impl From<types::Message> for Message {
+    fn from(msg: types::Message) -> Self {
+        Self(msg)
+    }
+}
+
+impl From<Message> for types::Message {
+    fn from(msg: Message) -> Self {
+        msg.0
+    }
+}
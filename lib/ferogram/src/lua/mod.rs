@@ -7,3 +7,9 @@
 // except according to those terms.
 
 //! Lua module.
+
+mod context;
+mod message;
+
+pub use context::Context;
+pub use message::Message;
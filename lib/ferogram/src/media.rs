@@ -0,0 +1,314 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Media module.
+
+use std::time::Duration;
+
+use grammers_client::{
+    grammers_tl_types as tl,
+    types::{media::Uploaded, Attribute, InputMessage},
+};
+
+/// The kind of media to send.
+#[derive(Clone, Debug, PartialEq)]
+enum MediaKind {
+    Photo,
+    Document,
+    Audio,
+    Voice,
+    Video,
+}
+
+/// A media message builder.
+///
+/// Sets the thumbnail and the metadata of the media, without building the
+/// `DocumentAttribute`s manually.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// # let ctx: ferogram::Context = unimplemented!();
+/// use std::time::Duration;
+///
+/// use ferogram::MediaBuilder;
+///
+/// let file = ctx.upload_file("song.mp3").await?;
+/// let thumb = ctx.upload_file("cover.jpg").await?;
+///
+/// let media = MediaBuilder::audio(file)
+///     .caption("Now playing")
+///     .title("Song")
+///     .performer("Artist")
+///     .duration(Duration::from_secs(180))
+///     .thumbnail(thumb);
+/// ctx.send(media).await?;
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct MediaBuilder {
+    /// The uploaded file.
+    file: Uploaded,
+    /// The kind of media.
+    kind: MediaKind,
+    /// The message holding the caption.
+    caption: InputMessage,
+    /// The thumbnail.
+    thumbnail: Option<Uploaded>,
+    /// The mime type.
+    mime_type: Option<String>,
+    /// The title, for audios.
+    title: Option<String>,
+    /// The performer, for audios.
+    performer: Option<String>,
+    /// The duration, for audios, voices and videos.
+    duration: Duration,
+    /// The width and the height, for videos.
+    dimensions: (i32, i32),
+    /// Whether the video supports streaming.
+    supports_streaming: bool,
+    /// Whether to send the media as a file.
+    force_document: bool,
+}
+
+impl MediaBuilder {
+    /// Creates a new builder.
+    fn new(file: Uploaded, kind: MediaKind) -> Self {
+        Self {
+            file,
+            kind,
+            caption: InputMessage::default(),
+            thumbnail: None,
+            mime_type: None,
+            title: None,
+            performer: None,
+            duration: Duration::ZERO,
+            dimensions: (0, 0),
+            supports_streaming: false,
+            force_document: false,
+        }
+    }
+
+    /// Creates a new photo builder.
+    pub fn photo(file: Uploaded) -> Self {
+        Self::new(file, MediaKind::Photo)
+    }
+
+    /// Creates a new document builder.
+    pub fn document(file: Uploaded) -> Self {
+        Self::new(file, MediaKind::Document)
+    }
+
+    /// Creates a new audio builder.
+    pub fn audio(file: Uploaded) -> Self {
+        Self::new(file, MediaKind::Audio)
+    }
+
+    /// Creates a new voice note builder.
+    pub fn voice(file: Uploaded) -> Self {
+        Self::new(file, MediaKind::Voice)
+    }
+
+    /// Creates a new video builder.
+    pub fn video(file: Uploaded) -> Self {
+        Self::new(file, MediaKind::Video)
+    }
+
+    /// Sets the caption.
+    ///
+    /// Accepts a formatted [`InputMessage`], whose text is used as the caption.
+    pub fn caption<M: Into<InputMessage>>(mut self, caption: M) -> Self {
+        self.caption = caption.into();
+        self
+    }
+
+    /// Sets the thumbnail.
+    ///
+    /// Ignored for photos.
+    pub fn thumbnail(mut self, thumbnail: Uploaded) -> Self {
+        self.thumbnail = Some(thumbnail);
+        self
+    }
+
+    /// Sets the mime type.
+    ///
+    /// If not set, it is guessed from the file name.
+    pub fn mime_type(mut self, mime_type: &str) -> Self {
+        self.mime_type = Some(mime_type.to_string());
+        self
+    }
+
+    /// Sets the title of the audio.
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Sets the performer of the audio.
+    pub fn performer(mut self, performer: &str) -> Self {
+        self.performer = Some(performer.to_string());
+        self
+    }
+
+    /// Sets the duration of the audio, voice note or video.
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Sets the width and the height of the video.
+    pub fn dimensions(mut self, width: i32, height: i32) -> Self {
+        self.dimensions = (width, height);
+        self
+    }
+
+    /// Sets whether the video supports streaming.
+    pub fn supports_streaming(mut self, value: bool) -> Self {
+        self.supports_streaming = value;
+        self
+    }
+
+    /// Sets whether to send the media as a file, instead of a photo, audio or video.
+    pub fn force_document(mut self, value: bool) -> Self {
+        self.force_document = value;
+        self
+    }
+
+    /// Builds the message.
+    pub fn build(self) -> InputMessage {
+        let mut message = self.caption;
+
+        if self.force_document {
+            message = message.file(self.file);
+        } else if self.kind == MediaKind::Photo {
+            return message.photo(self.file);
+        } else {
+            message = message.document(self.file);
+        }
+
+        if let Some(thumbnail) = self.thumbnail {
+            message = message.thumbnail(thumbnail);
+        }
+        if let Some(mime_type) = self.mime_type.as_deref() {
+            message = message.mime_type(mime_type);
+        }
+
+        match self.kind {
+            MediaKind::Audio => message.attribute(Attribute::Audio {
+                duration: self.duration,
+                title: self.title,
+                performer: self.performer,
+            }),
+            MediaKind::Voice => message.attribute(Attribute::Voice {
+                duration: self.duration,
+                waveform: None,
+            }),
+            MediaKind::Video => message.attribute(Attribute::Video {
+                round_message: false,
+                supports_streaming: self.supports_streaming,
+                duration: self.duration,
+                w: self.dimensions.0,
+                h: self.dimensions.1,
+            }),
+            MediaKind::Photo | MediaKind::Document => message,
+        }
+    }
+
+    /// Builds the raw input media, used by the paid media.
+    ///
+    /// The caption is not included.
+    pub(crate) fn input_media(self) -> tl::enums::InputMedia {
+        if self.kind == MediaKind::Photo && !self.force_document {
+            return tl::enums::InputMedia::UploadedPhoto(tl::types::InputMediaUploadedPhoto {
+                spoiler: false,
+                file: self.file.raw,
+                stickers: None,
+                ttl_seconds: None,
+            });
+        }
+
+        let mut attributes = vec![tl::enums::DocumentAttribute::Filename(
+            tl::types::DocumentAttributeFilename {
+                file_name: self.file.name().to_string(),
+            },
+        )];
+        match self.kind {
+            MediaKind::Audio | MediaKind::Voice => attributes.push(
+                tl::enums::DocumentAttribute::Audio(tl::types::DocumentAttributeAudio {
+                    voice: self.kind == MediaKind::Voice,
+                    duration: self.duration.as_secs() as i32,
+                    title: self.title,
+                    performer: self.performer,
+                    waveform: None,
+                }),
+            ),
+            MediaKind::Video => attributes.push(tl::enums::DocumentAttribute::Video(
+                tl::types::DocumentAttributeVideo {
+                    round_message: false,
+                    supports_streaming: self.supports_streaming,
+                    nosound: false,
+                    duration: self.duration.as_secs_f64(),
+                    w: self.dimensions.0,
+                    h: self.dimensions.1,
+                    preload_prefix_size: None,
+                    video_start_ts: None,
+                    video_codec: None,
+                },
+            )),
+            MediaKind::Photo | MediaKind::Document => {}
+        }
+
+        let mime_type = self.mime_type.unwrap_or_else(|| {
+            match self.kind {
+                MediaKind::Audio => "audio/mpeg",
+                MediaKind::Voice => "audio/ogg",
+                MediaKind::Video => "video/mp4",
+                MediaKind::Photo | MediaKind::Document => "application/octet-stream",
+            }
+            .to_string()
+        });
+
+        tl::enums::InputMedia::UploadedDocument(tl::types::InputMediaUploadedDocument {
+            nosound_video: false,
+            force_file: self.force_document,
+            spoiler: false,
+            file: self.file.raw,
+            thumb: self.thumbnail.map(|thumbnail| thumbnail.raw),
+            mime_type,
+            attributes,
+            stickers: None,
+            ttl_seconds: None,
+        })
+    }
+}
+
+impl From<MediaBuilder> for InputMessage {
+    fn from(builder: MediaBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// The paid media of a message, injected by [`crate::filter::has_paid_media`].
+#[derive(Clone, Debug)]
+pub struct PaidMedia {
+    /// The price, in Telegram Stars.
+    pub stars: i64,
+    /// The media, or their previews while not purchased.
+    pub media: Vec<tl::enums::MessageExtendedMedia>,
+}
+
+/// A purchase of a paid media sent by the bot, injected by
+/// [`crate::filter::paid_media_purchased`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PaidMediaPurchase {
+    /// The ID of the buyer.
+    pub user_id: i64,
+    /// The payload set when the media was sent.
+    pub payload: String,
+}
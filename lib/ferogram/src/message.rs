@@ -0,0 +1,155 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Message module.
+
+use grammers_client::{grammers_tl_types as tl, types::InputMessage};
+
+use crate::utils::utf16_len;
+
+/// A text message builder.
+///
+/// Appends the text piece by piece, computing the offsets of the spoilers and
+/// the quotes, and sets the animated effect of the message.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// # let ctx: ferogram::Context = unimplemented!();
+/// use ferogram::MessageBuilder;
+///
+/// let message = MessageBuilder::new()
+///     .text("The answer is ")
+///     .spoiler("42")
+///     .text("\n")
+///     .expandable_quote("A very long explanation...")
+///     .effect(5104841245755180586);
+/// ctx.send_message(message).await?;
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MessageBuilder {
+    /// The text.
+    text: String,
+    /// The entities of the text.
+    entities: Vec<tl::enums::MessageEntity>,
+    /// The ID of the animated effect.
+    effect: Option<i64>,
+    /// Whether to send the message without notification.
+    silent: bool,
+}
+
+impl MessageBuilder {
+    /// Creates a new empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a plain text.
+    pub fn text(mut self, text: &str) -> Self {
+        self.text.push_str(text);
+        self
+    }
+
+    /// Appends a text hidden behind a spoiler.
+    pub fn spoiler(self, text: &str) -> Self {
+        self.push_entity(text, |offset, length| {
+            tl::enums::MessageEntity::Spoiler(tl::types::MessageEntitySpoiler { offset, length })
+        })
+    }
+
+    /// Appends a block quotation.
+    pub fn blockquote(self, text: &str) -> Self {
+        self.push_quote(text, false)
+    }
+
+    /// Appends a block quotation, collapsed until the user expands it.
+    pub fn expandable_quote(self, text: &str) -> Self {
+        self.push_quote(text, true)
+    }
+
+    /// Sets the animated effect shown when the message is sent.
+    ///
+    /// Effects are supported only in private chats. Ignored when the builder is
+    /// converted to an [`InputMessage`].
+    pub fn effect(mut self, effect_id: i64) -> Self {
+        self.effect = Some(effect_id);
+        self
+    }
+
+    /// Sets whether to send the message without notification.
+    pub fn silent(mut self, value: bool) -> Self {
+        self.silent = value;
+        self
+    }
+
+    /// Appends a quotation.
+    fn push_quote(self, text: &str, collapsed: bool) -> Self {
+        self.push_entity(text, |offset, length| {
+            tl::enums::MessageEntity::Blockquote(tl::types::MessageEntityBlockquote {
+                collapsed,
+                offset,
+                length,
+            })
+        })
+    }
+
+    /// Appends a text covered by the entity.
+    fn push_entity<E: FnOnce(i32, i32) -> tl::enums::MessageEntity>(
+        mut self,
+        text: &str,
+        entity: E,
+    ) -> Self {
+        let offset = utf16_len(&self.text) as i32;
+        let length = utf16_len(text) as i32;
+
+        self.text.push_str(text);
+        if length > 0 {
+            self.entities.push(entity(offset, length));
+        }
+
+        self
+    }
+
+    /// Builds the request sending the message to `peer`.
+    pub(crate) fn request(
+        &self,
+        peer: tl::enums::InputPeer,
+        random_id: i64,
+    ) -> tl::functions::messages::SendMessage {
+        tl::functions::messages::SendMessage {
+            no_webpage: false,
+            silent: self.silent,
+            background: false,
+            clear_draft: false,
+            noforwards: false,
+            update_stickersets_order: false,
+            invert_media: false,
+            allow_paid_floodskip: false,
+            peer,
+            reply_to: None,
+            message: self.text.clone(),
+            random_id,
+            reply_markup: None,
+            entities: (!self.entities.is_empty()).then(|| self.entities.clone()),
+            schedule_date: None,
+            send_as: None,
+            quick_reply_shortcut: None,
+            effect: self.effect,
+        }
+    }
+}
+
+impl From<MessageBuilder> for InputMessage {
+    fn from(builder: MessageBuilder) -> Self {
+        InputMessage::text(builder.text)
+            .fmt_entities(builder.entities)
+            .silent(builder.silent)
+    }
+}
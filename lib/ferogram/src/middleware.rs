@@ -13,7 +13,7 @@ use std::future::Future;
 use async_trait::async_trait;
 use grammers_client::{Client, Update};
 
-use crate::{Flow, Injector};
+use crate::{flow, Flow, Injector, Result};
 
 /// A stack of middlewares.
 #[derive(Clone, Default)]
@@ -37,6 +37,23 @@ impl MiddlewareStack {
         self
     }
 
+    /// Adds a fallible middleware after-type in the stack.
+    ///
+    /// The errors are sent to the error handler, instead of only stopping the
+    /// next after-type middlewares. Useful for audit and metrics middlewares.
+    pub fn try_after<F, Fut>(mut self, middleware: F) -> Self
+    where
+        F: for<'a> FnMut(&'a Client, &'a Update, &'a mut Injector) -> Fut
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        Fut: Future<Output = Result<Flow>> + Send,
+    {
+        self.after.push(Box::new(TryMiddleware(middleware)));
+        self
+    }
+
     /// Adds a middleware before-type in the stack.
     pub fn before<M: Middleware>(mut self, middleware: M) -> Self {
         self.before.push(Box::new(middleware));
@@ -51,18 +68,22 @@ impl MiddlewareStack {
     }
 
     /// Handles the after-type middlewares.
+    ///
+    /// Returns the first error of the middlewares.
     pub(crate) async fn handle_after(
         &mut self,
         client: &Client,
         update: &Update,
         injector: &mut Injector,
-    ) {
+    ) -> Result<()> {
         for middleware in self.after.iter_mut() {
-            let flow = middleware.handle(client, update, injector).await;
+            let flow = middleware.try_handle(client, update, injector).await?;
             if flow.is_break() {
                 break;
             }
         }
+
+        Ok(())
     }
 
     /// Handles the before-type middlewares.
@@ -73,13 +94,21 @@ impl MiddlewareStack {
         injector: &mut Injector,
     ) -> Flow {
         let mut flow = Flow::default();
+        let mut new_update = None;
 
         for middleware in self.before.iter_mut() {
-            flow = middleware.handle(client, update, injector).await;
+            flow = middleware
+                .handle(client, new_update.as_ref().unwrap_or(update), injector)
+                .await;
+            if flow.update.is_some() {
+                new_update = flow.update.take();
+            }
+
             if flow.is_break() {
                 break;
             }
         }
+        flow.update = new_update;
 
         flow
     }
@@ -90,6 +119,19 @@ impl MiddlewareStack {
 pub trait Middleware: CloneMiddleware + Send + Sync + 'static {
     /// Handles the middleware.
     async fn handle(&mut self, client: &Client, update: &Update, injector: &mut Injector) -> Flow;
+
+    /// Handles the middleware, returning its errors.
+    ///
+    /// Used by the after-type middlewares, whose errors are sent to the error handler.
+    /// Calls [`Middleware::handle`] by default.
+    async fn try_handle(
+        &mut self,
+        client: &Client,
+        update: &Update,
+        injector: &mut Injector,
+    ) -> Result<Flow> {
+        Ok(self.handle(client, update, injector).await)
+    }
 }
 
 #[async_trait]
@@ -104,6 +146,40 @@ where
     }
 }
 
+/// A middleware which returns its errors.
+#[derive(Clone)]
+struct TryMiddleware<F>(F);
+
+#[async_trait]
+impl<F, Fut> Middleware for TryMiddleware<F>
+where
+    F: for<'a> FnMut(&'a Client, &'a Update, &'a mut Injector) -> Fut
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    Fut: Future<Output = Result<Flow>> + Send,
+{
+    async fn handle(&mut self, client: &Client, update: &Update, injector: &mut Injector) -> Flow {
+        match self.try_handle(client, update, injector).await {
+            Ok(flow) => flow,
+            Err(e) => {
+                log::error!("Error handling middleware: {:?}", e);
+                flow::break_now()
+            }
+        }
+    }
+
+    async fn try_handle(
+        &mut self,
+        client: &Client,
+        update: &Update,
+        injector: &mut Injector,
+    ) -> Result<Flow> {
+        (self.0)(client, update, injector).await
+    }
+}
+
 /// A trait that allows cloning the middleware.
 pub trait CloneMiddleware {
     /// Clones the middleware.
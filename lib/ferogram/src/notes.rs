@@ -0,0 +1,269 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Notes module.
+
+use std::sync::Arc;
+
+use grammers_client::{
+    types::{InputMessage, Message},
+    Update,
+};
+
+use crate::{
+    filter::MatchedCommand, filters, flow, handler, Context, Filter, Result, Router, Storage,
+};
+
+/// A saved reply.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Note {
+    /// The text of the note.
+    pub text: String,
+    /// The ID of the message whose media is sent with the note, in the same chat.
+    ///
+    /// The media is lost if the message is deleted.
+    pub media_message_id: Option<i32>,
+}
+
+impl Note {
+    /// Creates a note from the message, keeping its media.
+    pub fn from_message(message: &Message) -> Self {
+        Self {
+            text: message.text().to_string(),
+            media_message_id: message.media().map(|_| message.id()),
+        }
+    }
+
+    /// Encodes the note to be stored.
+    fn encode(&self) -> String {
+        format!("{}:{}", self.media_message_id.unwrap_or(0), self.text)
+    }
+
+    /// Decodes a stored note.
+    fn decode(value: &str) -> Option<Self> {
+        let (media_message_id, text) = value.split_once(':')?;
+        let media_message_id = media_message_id.parse().ok()?;
+
+        Some(Self {
+            text: text.to_string(),
+            media_message_id: (media_message_id != 0).then_some(media_message_id),
+        })
+    }
+}
+
+/// The note requested by `#name`, injected by [`trigger`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NoteTrigger {
+    /// The name of the note, lowercased.
+    pub name: String,
+}
+
+/// The named replies of the chats, backed by a [`Storage`].
+///
+/// [`Notes::router`] mounts the `/save`, `/get` and `/clear` commands and the
+/// `#name` trigger, like the classic group-manager bots. Only the administrators
+/// can save and clear the notes.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use ferogram::{notes::Notes, MemoryStorage};
+///
+/// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+/// let notes = Notes::new(MemoryStorage::default());
+///
+/// let dispatcher = dispatcher.router(|router| notes.router(router));
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Notes {
+    /// The storage of the notes.
+    storage: Arc<dyn Storage>,
+}
+
+impl Notes {
+    /// Creates the notes backed by `storage`.
+    pub fn new<S: Storage>(storage: S) -> Self {
+        Self {
+            storage: Arc::new(storage),
+        }
+    }
+
+    /// Returns the storage key of the note.
+    fn key(chat_id: i64, name: &str) -> String {
+        format!("note:{}:{}", chat_id, name.to_lowercase())
+    }
+
+    /// Saves the note of the chat, replacing the previous one.
+    pub async fn save(&self, chat_id: i64, name: &str, note: &Note) -> Result<()> {
+        self.storage
+            .set(&Self::key(chat_id, name), note.encode())
+            .await
+    }
+
+    /// Gets the note of the chat.
+    pub async fn get(&self, chat_id: i64, name: &str) -> Result<Option<Note>> {
+        let value = self.storage.get(&Self::key(chat_id, name)).await?;
+
+        Ok(value.as_deref().and_then(Note::decode))
+    }
+
+    /// Removes the note of the chat.
+    pub async fn clear(&self, chat_id: i64, name: &str) -> Result<()> {
+        self.storage.remove(&Self::key(chat_id, name)).await
+    }
+
+    /// Sends the note to the chat of the context.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the note could not be sent.
+    pub async fn send(&self, ctx: &Context, note: &Note) -> Result<()> {
+        let mut message = InputMessage::text(&note.text);
+
+        if let Some(message_id) = note.media_message_id {
+            if let Some(media) = ctx
+                .get_message(message_id)
+                .await?
+                .and_then(|message| message.media())
+            {
+                message = message.copy_media(&media);
+            }
+        }
+
+        ctx.reply(message).await?;
+
+        Ok(())
+    }
+
+    /// Registers the `/save`, `/get` and `/clear` commands and the `#name` trigger in the router.
+    pub fn router(self, router: Router) -> Router {
+        let (save, get, clear, trigger) = (self.clone(), self.clone(), self.clone(), self);
+
+        router
+            .register(
+                handler::new_message(
+                    filters::command("save")
+                        .description("Save a note.")
+                        .and(filters::administrator),
+                )
+                .then(move |ctx: Context, command: MatchedCommand| {
+                    let notes = save.clone();
+
+                    async move { notes.handle_save(&ctx, &command.args).await }
+                }),
+            )
+            .register(
+                handler::new_message(filters::command("get").description("Get a note.")).then(
+                    move |ctx: Context, command: MatchedCommand| {
+                        let notes = get.clone();
+
+                        async move { notes.handle_get(&ctx, command.args.trim()).await }
+                    },
+                ),
+            )
+            .register(
+                handler::new_message(
+                    filters::command("clear")
+                        .description("Clear a note.")
+                        .and(filters::administrator),
+                )
+                .then(move |ctx: Context, command: MatchedCommand| {
+                    let notes = clear.clone();
+
+                    async move { notes.handle_clear(&ctx, command.args.trim()).await }
+                }),
+            )
+            .register(handler::new_message(self::trigger()).then(
+                move |ctx: Context, note: NoteTrigger| {
+                    let notes = trigger.clone();
+
+                    async move { notes.handle_get(&ctx, &note.name).await }
+                },
+            ))
+    }
+
+    /// Handles `/save name text`, or `/save name` replying to the message to save.
+    async fn handle_save(&self, ctx: &Context, args: &str) -> Result<()> {
+        let chat_id = ctx.chat().expect("No chat").id();
+        let (name, text) = match args.trim().split_once(char::is_whitespace) {
+            Some((name, text)) => (name, text.trim()),
+            None => (args.trim(), ""),
+        };
+
+        let note = match ctx.get_reply().await? {
+            Some(reply) => Note::from_message(&reply),
+            None => Note {
+                text: text.to_string(),
+                media_message_id: None,
+            },
+        };
+
+        if name.is_empty() || (note.text.is_empty() && note.media_message_id.is_none()) {
+            ctx.reply("Usage: /save <name> <text>, or reply to a message with /save <name>.")
+                .await?;
+            return Ok(());
+        }
+
+        self.save(chat_id, name, &note).await?;
+        ctx.reply(format!("Note #{} saved.", name.to_lowercase()))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Handles `/get name` and `#name`.
+    async fn handle_get(&self, ctx: &Context, name: &str) -> Result<()> {
+        let chat_id = ctx.chat().expect("No chat").id();
+
+        match self.get(chat_id, name).await? {
+            Some(note) => self.send(ctx, &note).await,
+            None => {
+                ctx.reply(format!("Note #{} not found.", name.to_lowercase()))
+                    .await?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Handles `/clear name`.
+    async fn handle_clear(&self, ctx: &Context, name: &str) -> Result<()> {
+        let chat_id = ctx.chat().expect("No chat").id();
+
+        self.clear(chat_id, name).await?;
+        ctx.reply(format!("Note #{} cleared.", name.to_lowercase()))
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Pass if the message starts with `#name`, requesting a note.
+///
+/// Injects `NoteTrigger`: the requested note.
+pub fn trigger() -> impl Filter {
+    |_, update| async move {
+        if let Update::NewMessage(message) = update {
+            let name = message
+                .text()
+                .split_whitespace()
+                .next()
+                .and_then(|word| word.strip_prefix('#'))
+                .filter(|name| !name.is_empty());
+
+            if let Some(name) = name {
+                return flow::continue_with(NoteTrigger {
+                    name: name.to_lowercase(),
+                });
+            }
+        }
+
+        flow::break_now()
+    }
+}
@@ -0,0 +1,459 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Outbox module.
+
+use std::{sync::Arc, time::Duration};
+
+use futures_util::Future;
+use grammers_client::{grammers_tl_types as tl, types::PackedChat, Client, InvocationError};
+use tokio::sync::{Mutex, Notify};
+
+use crate::{utils::random_u64, CorrelationId, Result, Storage};
+
+/// The storage key of the index of the messages.
+const INDEX_KEY: &str = "outbox:index";
+
+/// A message waiting to be sent.
+struct Intent {
+    /// The chat to send the message to.
+    chat: PackedChat,
+    /// The text of the message.
+    text: String,
+    /// The failed attempts to send the message.
+    attempts: u32,
+    /// The correlation ID of the update which pushed the message, if any.
+    correlation_id: Option<CorrelationId>,
+}
+
+impl Intent {
+    /// Encodes the intent as `attempts:correlation_id:chat:text`.
+    ///
+    /// The correlation ID is empty if there is none.
+    fn encode(&self) -> String {
+        format!(
+            "{}:{}:{}:{}",
+            self.attempts,
+            self.correlation_id
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+            self.chat.to_hex(),
+            self.text
+        )
+    }
+
+    /// Builds the request sending the message, with its idempotency key as the random ID.
+    fn request(&self, random_id: i64) -> tl::functions::messages::SendMessage {
+        tl::functions::messages::SendMessage {
+            no_webpage: false,
+            silent: false,
+            background: false,
+            clear_draft: false,
+            noforwards: false,
+            update_stickersets_order: false,
+            invert_media: false,
+            allow_paid_floodskip: false,
+            peer: self.chat.to_input_peer(),
+            reply_to: None,
+            message: self.text.clone(),
+            random_id,
+            reply_markup: None,
+            entities: None,
+            schedule_date: None,
+            send_as: None,
+            quick_reply_shortcut: None,
+            effect: None,
+        }
+    }
+
+    /// Describes the message in the logs, with its correlation ID.
+    fn describe(&self, id: i64) -> String {
+        match self.correlation_id {
+            Some(correlation_id) => format!("{} of update {}", id, correlation_id),
+            None => id.to_string(),
+        }
+    }
+
+    /// Decodes an `attempts:correlation_id:chat:text` intent.
+    fn decode(value: &str) -> Option<Self> {
+        let mut parts = value.splitn(4, ':');
+
+        Some(Self {
+            attempts: parts.next()?.parse().ok()?,
+            correlation_id: match parts.next()? {
+                "" => None,
+                id => Some(id.parse().ok()?),
+            },
+            chat: PackedChat::from_hex(parts.next()?).ok()?,
+            text: parts.next()?.to_string(),
+        })
+    }
+}
+
+/// A storage-backed outbox for the messages which must not be lost.
+///
+/// The messages are written to the storage before being sent by a background
+/// worker, with retries, so they are sent even if the process dies in the middle
+/// of the handler. Each message keeps the same random ID between the attempts,
+/// which Telegram uses to drop the duplicates.
+///
+/// The index of the messages is a single key updated in place, so only one
+/// process should use the outbox of a storage.
+///
+/// The outbox is attached with [`crate::Dispatcher::outbox`] and used by
+/// [`crate::Context::send_reliable`].
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use std::time::Duration;
+///
+/// use ferogram::{outbox::Outbox, MemoryStorage};
+///
+/// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+/// let outbox = Outbox::new(MemoryStorage::default())
+///     .max_attempts(10)
+///     .retry_delay(Duration::from_secs(30));
+///
+/// let dispatcher = dispatcher.outbox(outbox);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Outbox {
+    /// The storage of the messages.
+    storage: Arc<dyn Storage>,
+    /// The attempts to send a message before dropping it.
+    max_attempts: u32,
+    /// The delay before retrying the failed messages.
+    retry_delay: Duration,
+    /// Serializes the changes to the index.
+    lock: Arc<Mutex<()>>,
+    /// Wakes the worker when a message is pushed.
+    notify: Arc<Notify>,
+}
+
+impl Outbox {
+    /// Creates the outbox backed by `storage`.
+    pub fn new<S: Storage>(storage: S) -> Self {
+        Self {
+            storage: Arc::new(storage),
+            max_attempts: 5,
+            retry_delay: Duration::from_secs(5),
+            lock: Arc::new(Mutex::new(())),
+            notify: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Sets the attempts to send a message before dropping it.
+    ///
+    /// Default is `5`.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the delay before retrying the failed messages.
+    ///
+    /// Default is `5` seconds.
+    pub fn retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Writes the message to the storage, to be sent by the worker.
+    ///
+    /// Returns the idempotency key of the message, its random ID.
+    ///
+    /// The message keeps the [`CorrelationId::current`], shown in the logs.
+    pub async fn push<C: Into<PackedChat>, T: ToString>(&self, chat: C, text: T) -> Result<i64> {
+        self.push_with(chat, text, CorrelationId::current()).await
+    }
+
+    /// Writes the message to the storage, with the correlation ID.
+    pub(crate) async fn push_with<C: Into<PackedChat>, T: ToString>(
+        &self,
+        chat: C,
+        text: T,
+        correlation_id: Option<CorrelationId>,
+    ) -> Result<i64> {
+        let id = random_u64() as i64;
+        let intent = Intent {
+            chat: chat.into(),
+            text: text.to_string(),
+            attempts: 0,
+            correlation_id,
+        };
+
+        let _guard = self.lock.lock().await;
+
+        self.storage.set(&intent_key(id), intent.encode()).await?;
+
+        let mut ids = self.ids().await?;
+        ids.push(id);
+        self.save_ids(&ids).await?;
+
+        self.notify.notify_one();
+
+        Ok(id)
+    }
+
+    /// Returns the number of messages waiting to be sent.
+    pub async fn len(&self) -> Result<usize> {
+        Ok(self.ids().await?.len())
+    }
+
+    /// Returns whether there are no messages waiting to be sent.
+    pub async fn is_empty(&self) -> Result<bool> {
+        Ok(self.len().await? == 0)
+    }
+
+    /// Sends the pending messages, forever.
+    ///
+    /// Spawned by the client when it runs.
+    pub(crate) async fn run(self, client: Client) {
+        loop {
+            match self.flush(&client).await {
+                Ok(false) => self.notify.notified().await,
+                result => {
+                    if let Err(e) = result {
+                        log::error!("Error flushing the outbox: {:?}", e);
+                    }
+
+                    let _ = tokio::time::timeout(self.retry_delay, self.notify.notified()).await;
+                }
+            }
+        }
+    }
+
+    /// Tries to send the pending messages.
+    ///
+    /// Returns whether some message must be retried.
+    async fn flush(&self, client: &Client) -> Result<bool> {
+        self.flush_with(|request| async move { client.invoke(&request).await })
+            .await
+    }
+
+    /// Tries to send the pending messages, invoking the requests with `invoke`.
+    ///
+    /// Returns whether some message must be retried.
+    async fn flush_with<F, Fut>(&self, invoke: F) -> Result<bool>
+    where
+        F: Fn(tl::functions::messages::SendMessage) -> Fut,
+        Fut: Future<Output = std::result::Result<tl::enums::Updates, InvocationError>>,
+    {
+        let mut retry = false;
+
+        for id in self.ids().await? {
+            let Some(mut intent) = self
+                .storage
+                .get(&intent_key(id))
+                .await?
+                .as_deref()
+                .and_then(Intent::decode)
+            else {
+                self.remove(id).await?;
+                continue;
+            };
+
+            match sent(invoke(intent.request(id)).await) {
+                Ok(()) => self.remove(id).await?,
+                Err(e) => {
+                    intent.attempts += 1;
+
+                    if intent.attempts >= self.max_attempts {
+                        log::error!(
+                            "Dropping message {} after {} attempts: {:?}",
+                            intent.describe(id),
+                            intent.attempts,
+                            e
+                        );
+                        self.remove(id).await?;
+                    } else {
+                        log::warn!(
+                            "Error sending message {}, retrying: {:?}",
+                            intent.describe(id),
+                            e
+                        );
+                        self.storage.set(&intent_key(id), intent.encode()).await?;
+                        retry = true;
+                    }
+                }
+            }
+        }
+
+        Ok(retry)
+    }
+
+    /// Removes the message from the outbox.
+    async fn remove(&self, id: i64) -> Result<()> {
+        let _guard = self.lock.lock().await;
+
+        let mut ids = self.ids().await?;
+        ids.retain(|other| *other != id);
+        self.save_ids(&ids).await?;
+
+        self.storage.remove(&intent_key(id)).await
+    }
+
+    /// Loads the index of the messages.
+    async fn ids(&self) -> Result<Vec<i64>> {
+        let index = self.storage.get(INDEX_KEY).await?.unwrap_or_default();
+
+        Ok(index.lines().filter_map(|id| id.parse().ok()).collect())
+    }
+
+    /// Saves the index of the messages.
+    async fn save_ids(&self, ids: &[i64]) -> Result<()> {
+        let index = ids
+            .iter()
+            .map(i64::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        self.storage.set(INDEX_KEY, index).await
+    }
+}
+
+impl std::fmt::Debug for Outbox {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Outbox")
+            .field("max_attempts", &self.max_attempts)
+            .field("retry_delay", &self.retry_delay)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Checks the result of sending a message.
+///
+/// A duplicated random ID means it was already sent by a previous attempt.
+fn sent(result: std::result::Result<tl::enums::Updates, InvocationError>) -> Result<()> {
+    match result {
+        Ok(_) => Ok(()),
+        Err(InvocationError::Rpc(e)) if e.name == "RANDOM_ID_DUPLICATE" => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Returns the storage key of the message.
+fn intent_key(id: i64) -> String {
+    format!("outbox:{}", id)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use grammers_client::{types::PackedType, RpcError};
+
+    use super::*;
+    use crate::MemoryStorage;
+
+    fn chat() -> PackedChat {
+        PackedChat {
+            ty: PackedType::User,
+            id: 1234,
+            access_hash: Some(5678),
+        }
+    }
+
+    fn rpc_error(name: &str) -> InvocationError {
+        InvocationError::Rpc(RpcError {
+            code: 400,
+            name: name.to_string(),
+            value: None,
+            caused_by: None,
+        })
+    }
+
+    #[test]
+    fn test_intent_encoding() {
+        let intent = Intent {
+            chat: chat(),
+            text: String::from("Hello: world"),
+            attempts: 2,
+            correlation_id: None,
+        };
+
+        let decoded = Intent::decode(&intent.encode()).unwrap();
+        assert_eq!(decoded.chat, intent.chat);
+        assert_eq!(decoded.text, intent.text);
+        assert_eq!(decoded.attempts, 2);
+        assert!(decoded.correlation_id.is_none());
+
+        assert!(Intent::decode("x:1:2:3").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_and_flush() {
+        let outbox = Outbox::new(MemoryStorage::default());
+
+        let first = outbox.push_with(chat(), "first", None).await.unwrap();
+        let second = outbox.push_with(chat(), "second", None).await.unwrap();
+        assert_eq!(outbox.len().await.unwrap(), 2);
+
+        let sent_requests = Mutex::new(Vec::new());
+        let retry = outbox
+            .flush_with(|request| {
+                sent_requests
+                    .lock()
+                    .unwrap()
+                    .push((request.random_id, request.message.clone()));
+
+                async { Ok(tl::enums::Updates::TooLong) }
+            })
+            .await
+            .unwrap();
+
+        assert!(!retry);
+        assert!(outbox.is_empty().await.unwrap());
+        assert_eq!(
+            sent_requests.into_inner().unwrap(),
+            [
+                (first, String::from("first")),
+                (second, String::from("second"))
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_flush_retries() {
+        let outbox = Outbox::new(MemoryStorage::default()).max_attempts(2);
+        let id = outbox.push_with(chat(), "hello", None).await.unwrap();
+
+        // Kept for the next attempt, with the same random ID.
+        let retry = outbox
+            .flush_with(|_| async { Err(rpc_error("CHAT_WRITE_FORBIDDEN")) })
+            .await
+            .unwrap();
+        assert!(retry);
+        assert_eq!(outbox.ids().await.unwrap(), [id]);
+
+        // Dropped after the last attempt.
+        let retry = outbox
+            .flush_with(|_| async { Err(rpc_error("CHAT_WRITE_FORBIDDEN")) })
+            .await
+            .unwrap();
+        assert!(!retry);
+        assert!(outbox.is_empty().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_flush_duplicate() {
+        let outbox = Outbox::new(MemoryStorage::default());
+        outbox.push_with(chat(), "hello", None).await.unwrap();
+
+        // Already sent by a previous attempt.
+        let retry = outbox
+            .flush_with(|_| async { Err(rpc_error("RANDOM_ID_DUPLICATE")) })
+            .await
+            .unwrap();
+        assert!(!retry);
+        assert!(outbox.is_empty().await.unwrap());
+    }
+}
@@ -8,7 +8,25 @@
 
 //! Plugin module.
 
-use crate::{Handler, Router};
+use std::{
+    collections::VecDeque,
+    panic::AssertUnwindSafe,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use futures_util::FutureExt;
+use grammers_client::{Client, Update};
+
+use crate::{
+    di::Injector, logging, middleware::MiddlewareStack, EventBus, Handler, Result, Router,
+};
+
+/// The default window of the failures counted by the error budget.
+const ERROR_WINDOW: Duration = Duration::from_secs(60 * 60);
 
 /// A plugin.
 #[derive(Clone, Default)]
@@ -17,9 +35,36 @@ pub struct Plugin {
     version: String,
     authors: Vec<String>,
     description: String,
+    command_prefix: Option<String>,
+    error_budget: Option<usize>,
+    error_window: Option<Duration>,
+    timeout: Option<Duration>,
+    health: Arc<PluginHealth>,
     pub(crate) router: Router,
 }
 
+/// The health of a plugin, shared by its clones.
+#[derive(Debug, Default)]
+struct PluginHealth {
+    /// How many times the plugin failed.
+    failures: AtomicUsize,
+    /// When the plugin failed, within the error window.
+    recent_failures: Mutex<VecDeque<Instant>>,
+    /// Whether the plugin is disabled.
+    disabled: AtomicBool,
+}
+
+/// Published in the [`EventBus`] when a plugin is disabled.
+///
+/// Subscribe to it with [`crate::handler::event`] to notify the owner.
+#[derive(Clone, Debug)]
+pub struct PluginDisabled {
+    /// The name of the plugin.
+    pub name: String,
+    /// The last error of the plugin.
+    pub reason: String,
+}
+
 impl Plugin {
     /// Creates a new plugin builder.
     pub fn builder() -> PluginBuilder {
@@ -46,11 +91,175 @@ impl Plugin {
         &self.description
     }
 
+    /// Returns the commands registered by the plugin, paired with their descriptions.
+    pub fn commands(&self) -> Vec<(String, String)> {
+        self.router
+            .get_commands()
+            .iter()
+            .flat_map(|command| command.bot_commands())
+            .collect()
+    }
+
+    /// Sets the priority of the plugin.
+    ///
+    /// Plugins and routers with higher priority run first, the default is `0`.
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.router.priority = priority;
+        self
+    }
+
+    /// Attachs a injector, visible only to the handlers of the plugin.
+    ///
+    /// Shadows the resources of the same type from the dispatcher.
+    pub fn resources<D: FnOnce(Injector) -> Injector>(mut self, injector: D) -> Self {
+        self.router = self.router.resources(injector);
+        self
+    }
+
+    /// Sets how many times the plugin can fail within the error window before
+    /// being disabled.
+    ///
+    /// Errors, panics and timeouts count as failures. A [`PluginDisabled`] event
+    /// is published when the plugin is disabled.
+    pub fn error_budget(mut self, budget: usize) -> Self {
+        self.error_budget = Some(budget);
+        self
+    }
+
+    /// Sets the window of the failures counted by the error budget.
+    ///
+    /// Default is 1 hour.
+    pub fn error_window(mut self, window: Duration) -> Self {
+        self.error_window = Some(window);
+        self
+    }
+
+    /// Sets how long the plugin can take to handle an update.
+    ///
+    /// Handling is cancelled and counted as a failure when it takes longer.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Returns whether the plugin was disabled by its error budget.
+    pub fn is_disabled(&self) -> bool {
+        self.health.disabled.load(Ordering::Relaxed)
+    }
+
+    /// Returns how many times the plugin failed and whether it is disabled.
+    pub(crate) fn health(&self) -> (usize, bool) {
+        (
+            self.health.failures.load(Ordering::Relaxed),
+            self.health.disabled.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Restores the failures and the disabled flag of the plugin.
+    pub(crate) fn restore_health(&self, failures: usize, disabled: bool) {
+        self.health.failures.store(failures, Ordering::Relaxed);
+        self.health.disabled.store(disabled, Ordering::Relaxed);
+    }
+
+    /// Namespaces the commands of the plugin.
+    ///
+    /// Prepends `namespace` and an underscore to the command names, so two plugins
+    /// can register the same command. Ex: `stats` becomes `namespace_stats`.
+    ///
+    /// Applies to the handlers added before and after it.
+    pub fn command_prefix(mut self, namespace: &str) -> Self {
+        self.router.prefix_commands(namespace);
+        self.command_prefix = Some(namespace.to_owned());
+        self
+    }
+
     /// Adds a handler to the plugin.
-    pub fn handler(mut self, handler: Handler) -> Self {
+    pub fn handler(mut self, mut handler: Handler) -> Self {
+        if let Some(prefix) = self.command_prefix.as_deref() {
+            handler.prefix_commands(prefix);
+        }
+
         self.router.handlers.push(handler);
         self
     }
+
+    /// Sends the update to the router of the plugin.
+    ///
+    /// Panics and timeouts are returned as errors, and every error counts
+    /// towards the error budget.
+    pub(crate) async fn handle_update(
+        &mut self,
+        client: &Client,
+        update: &Update,
+        injector: &mut Injector,
+        middlewares: MiddlewareStack,
+        event_bus: &EventBus,
+    ) -> Result<bool> {
+        let handling =
+            AssertUnwindSafe(
+                self.router
+                    .handle_update(client, update, injector, middlewares),
+            )
+            .catch_unwind();
+
+        let result = match self.timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, handling).await {
+                Ok(result) => result,
+                Err(_) => Ok(Err(format!("Plugin {:?} timed out", self.name).into())),
+            },
+            None => handling.await,
+        };
+        let result =
+            result.unwrap_or_else(|_| Err(format!("Plugin {:?} panicked", self.name).into()));
+
+        if let Err(e) = result.as_ref() {
+            self.record_failure(e.to_string(), event_bus);
+        }
+
+        result
+    }
+
+    /// Counts a failure, disabling the plugin when the failures within the error
+    /// window exceed the error budget.
+    fn record_failure(&self, reason: String, event_bus: &EventBus) {
+        self.health.failures.fetch_add(1, Ordering::Relaxed);
+
+        let failures = {
+            let window = self.error_window.unwrap_or(ERROR_WINDOW);
+            let mut recent = self
+                .health
+                .recent_failures
+                .lock()
+                .expect("Failed to lock the failures");
+
+            while recent
+                .front()
+                .is_some_and(|failed_at| failed_at.elapsed() > window)
+            {
+                recent.pop_front();
+            }
+            recent.push_back(Instant::now());
+
+            recent.len()
+        };
+
+        if let Some(budget) = self.error_budget {
+            if failures > budget && !self.health.disabled.swap(true, Ordering::Relaxed) {
+                log::error!(
+                    target: logging::PLUGINS,
+                    "Plugin {:?} disabled after {} failures within {:?}",
+                    self.name,
+                    failures,
+                    self.error_window.unwrap_or(ERROR_WINDOW)
+                );
+
+                event_bus.publish(PluginDisabled {
+                    name: self.name.clone(),
+                    reason,
+                });
+            }
+        }
+    }
 }
 
 /// A plugin builder.
@@ -100,6 +309,11 @@ impl PluginBuilder {
             version: self.version,
             authors: self.authors,
             description: self.description,
+            command_prefix: None,
+            error_budget: None,
+            error_window: None,
+            timeout: None,
+            health: Arc::default(),
             router: Router::default(),
         }
     }
@@ -0,0 +1,138 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Poll module.
+
+use std::time::Duration;
+
+use grammers_client::grammers_tl_types as tl;
+
+use crate::utils::random_u64;
+
+/// A poll builder.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// # let ctx: ferogram::Context = unimplemented!();
+/// use ferogram::PollBuilder;
+///
+/// let poll = PollBuilder::new("What is 2 + 2?", &["3", "4", "5"])
+///     .quiz(1)
+///     .solution("2 + 2 is 4.");
+/// ctx.send_poll(poll).await?;
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct PollBuilder {
+    /// The question.
+    question: String,
+    /// The answer options.
+    options: Vec<String>,
+    /// Whether the voters are hidden.
+    anonymous: bool,
+    /// Whether many options can be chosen.
+    multiple_choice: bool,
+    /// The index of the correct option, for quizzes.
+    correct_option: Option<usize>,
+    /// The explanation shown after answering a quiz.
+    solution: Option<String>,
+    /// How long the poll stays open.
+    close_period: Option<Duration>,
+}
+
+impl PollBuilder {
+    /// Creates a new anonymous poll builder.
+    pub fn new<Q: Into<String>, O: ToString>(question: Q, options: &[O]) -> Self {
+        Self {
+            question: question.into(),
+            options: options.iter().map(ToString::to_string).collect(),
+            anonymous: true,
+            multiple_choice: false,
+            correct_option: None,
+            solution: None,
+            close_period: None,
+        }
+    }
+
+    /// Sets whether the voters are hidden, `true` by default.
+    pub fn anonymous(mut self, value: bool) -> Self {
+        self.anonymous = value;
+        self
+    }
+
+    /// Sets whether many options can be chosen.
+    ///
+    /// Ignored for quizzes.
+    pub fn multiple_choice(mut self, value: bool) -> Self {
+        self.multiple_choice = value;
+        self
+    }
+
+    /// Turns the poll into a quiz, with the option at `correct_option` as the correct one.
+    pub fn quiz(mut self, correct_option: usize) -> Self {
+        self.correct_option = Some(correct_option);
+        self
+    }
+
+    /// Sets the explanation shown after answering the quiz.
+    pub fn solution(mut self, solution: &str) -> Self {
+        self.solution = Some(solution.to_string());
+        self
+    }
+
+    /// Closes the poll automatically after `period`, between 5 and 600 seconds.
+    pub fn close_after(mut self, period: Duration) -> Self {
+        self.close_period = Some(period);
+        self
+    }
+
+    /// Builds the poll media.
+    pub(crate) fn build(self) -> tl::enums::InputMedia {
+        let is_quiz = self.correct_option.is_some();
+        let solution_entities = self.solution.as_ref().map(|_| Vec::new());
+
+        let answers = self
+            .options
+            .into_iter()
+            .enumerate()
+            .map(|(i, option)| {
+                tl::enums::PollAnswer::Answer(tl::types::PollAnswer {
+                    text: text_with_entities(option),
+                    option: vec![i as u8],
+                })
+            })
+            .collect();
+
+        tl::enums::InputMedia::Poll(tl::types::InputMediaPoll {
+            poll: tl::enums::Poll::Poll(tl::types::Poll {
+                id: random_u64() as i64,
+                closed: false,
+                public_voters: !self.anonymous,
+                multiple_choice: self.multiple_choice && !is_quiz,
+                quiz: is_quiz,
+                question: text_with_entities(self.question),
+                answers,
+                close_period: self.close_period.map(|period| period.as_secs() as i32),
+                close_date: None,
+            }),
+            correct_answers: self.correct_option.map(|i| vec![vec![i as u8]]),
+            solution: self.solution,
+            solution_entities,
+        })
+    }
+}
+
+/// Wraps a plain text.
+fn text_with_entities(text: String) -> tl::enums::TextWithEntities {
+    tl::enums::TextWithEntities::Entities(tl::types::TextWithEntities {
+        text,
+        entities: Vec::new(),
+    })
+}
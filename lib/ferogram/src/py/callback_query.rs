@@ -0,0 +1,111 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Callback query module.
+
+use grammers_client::types;
+use pyo3::{prelude::*, types::PyBytes};
+use pyo3_async_runtimes::tokio::future_into_py;
+
+use super::{to_py_err, Chat, Message};
+
+/// A callback query.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct CallbackQuery(types::CallbackQuery);
+
+#[pymethods]
+impl CallbackQuery {
+    /// The data of the query.
+    #[getter]
+    pub fn data<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, self.0.data())
+    }
+
+    /// The chat of the query.
+    #[getter]
+    pub fn chat(&self) -> Chat {
+        self.0.chat().into()
+    }
+
+    /// The sender of the query.
+    #[getter]
+    pub fn sender(&self) -> Chat {
+        self.0.sender().into()
+    }
+
+    /// Answers the query, with an optional notification text.
+    #[pyo3(signature = (text=None))]
+    pub fn answer<'py>(
+        &self,
+        py: Python<'py>,
+        text: Option<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let query = self.0.clone();
+
+        future_into_py(py, async move {
+            let mut answer = query.answer();
+            if let Some(text) = text {
+                answer = answer.text(text);
+            }
+
+            answer.send().await.map_err(to_py_err)
+        })
+    }
+
+    /// Answers the query with an alert.
+    pub fn alert<'py>(&self, py: Python<'py>, text: String) -> PyResult<Bound<'py, PyAny>> {
+        let query = self.0.clone();
+
+        future_into_py(py, async move {
+            query.answer().alert(text).send().await.map_err(to_py_err)
+        })
+    }
+
+    /// Answers the query and edits its message.
+    pub fn edit<'py>(&self, py: Python<'py>, text: String) -> PyResult<Bound<'py, PyAny>> {
+        let query = self.0.clone();
+
+        future_into_py(py, async move {
+            query.answer().edit(text).await.map_err(to_py_err)
+        })
+    }
+
+    /// Gets the message of the query.
+    pub fn message<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let query = self.0.clone();
+
+        future_into_py(py, async move {
+            query
+                .load_message()
+                .await
+                .map(Message::from)
+                .map_err(to_py_err)
+        })
+    }
+
+    pub fn __str__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+impl From<types::CallbackQuery> for CallbackQuery {
+    fn from(query: types::CallbackQuery) -> Self {
+        Self(query)
+    }
+}
+
+impl From<CallbackQuery> for types::CallbackQuery {
+    fn from(query: CallbackQuery) -> Self {
+        query.0
+    }
+}
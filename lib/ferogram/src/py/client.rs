@@ -0,0 +1,204 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Client module.
+
+use std::sync::{Arc, Mutex};
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+use pyo3_async_runtimes::{into_future_with_locals, tokio::future_into_py, TaskLocals};
+
+use super::{to_py_err, Context};
+use crate::{client::ClientBuilder, filters, handler, Handler, Supervisor};
+
+/// The default session file path.
+const DEFAULT_SESSION_FILE: &str = "./ferogram.session";
+
+/// The inner client and the supervisor of a running client.
+type Running = Arc<Mutex<Option<(grammers_client::Client, Supervisor)>>>;
+
+/// A client.
+///
+/// Runs the Rust client, calling the Python handlers with a [`Context`].
+#[pyclass]
+pub struct Client {
+    /// The client builder, taken when running.
+    builder: Option<ClientBuilder>,
+    /// The session file path.
+    session_file: String,
+    /// The Python handlers.
+    handlers: Vec<PyHandler>,
+    /// The running client, set after connecting.
+    running: Running,
+}
+
+#[pymethods]
+impl Client {
+    #[new]
+    #[pyo3(signature = (bot_token, api_id, api_hash, session_file=None))]
+    pub fn new(
+        bot_token: String,
+        api_id: i32,
+        api_hash: String,
+        session_file: Option<String>,
+    ) -> Self {
+        let session_file = session_file.unwrap_or_else(|| DEFAULT_SESSION_FILE.to_string());
+        let builder = crate::Client::bot(bot_token)
+            .api_id(api_id)
+            .api_hash(api_hash)
+            .session_file(&session_file);
+
+        Self {
+            builder: Some(builder),
+            session_file,
+            handlers: Vec::new(),
+            running: Running::default(),
+        }
+    }
+
+    /// Adds a handler, called with the [`Context`] of each update of the type.
+    ///
+    /// The update type is `new_message`, `message_edited`, `callback_query` or
+    /// `inline_query`. The handler can be a function or a coroutine function.
+    #[pyo3(signature = (callback, update_type="new_message"))]
+    pub fn add_handler(&mut self, callback: PyObject, update_type: &str) -> PyResult<()> {
+        if self.builder.is_none() {
+            return Err(PyValueError::new_err("The client is already running"));
+        }
+
+        let update_type = match update_type {
+            "new_message" => UpdateType::NewMessage,
+            "message_edited" => UpdateType::MessageEdited,
+            "callback_query" => UpdateType::CallbackQuery,
+            "inline_query" => UpdateType::InlineQuery,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown update type: {}",
+                    update_type
+                )))
+            }
+        };
+
+        self.handlers.push(PyHandler {
+            update_type,
+            callback: Arc::new(callback),
+        });
+
+        Ok(())
+    }
+
+    /// Connects the client and starts listening to the updates.
+    ///
+    /// Returns after the client is connected, use `idle` to keep it running.
+    pub fn run<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let builder = self
+            .builder
+            .take()
+            .ok_or_else(|| PyValueError::new_err("The client is already running"))?;
+        let handlers = std::mem::take(&mut self.handlers);
+        let locals = pyo3_async_runtimes::tokio::get_current_locals(py)?;
+        let running = self.running.clone();
+
+        future_into_py(py, async move {
+            let client = builder
+                .build_and_connect()
+                .await
+                .map_err(to_py_err)?
+                .dispatcher(move |dispatcher| {
+                    dispatcher.router(move |mut router| {
+                        for handler in handlers {
+                            router = router.register(handler.into_handler(locals.clone()));
+                        }
+
+                        router
+                    })
+                });
+
+            *running.lock().expect("Failed to lock the client") =
+                Some((client.inner().clone(), client.supervisor().clone()));
+
+            client.run().await.map_err(to_py_err)
+        })
+    }
+
+    /// Waits for a `Ctrl + C` signal, then stops the background tasks and saves the session.
+    pub fn idle<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let running = self.running.clone();
+        let session_file = self.session_file.clone();
+
+        future_into_py(py, async move {
+            tokio::signal::ctrl_c().await.map_err(to_py_err)?;
+
+            let running = running.lock().expect("Failed to lock the client").take();
+            if let Some((client, supervisor)) = running {
+                supervisor.shutdown();
+                client
+                    .session()
+                    .save_to_file(&session_file)
+                    .map_err(to_py_err)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+/// The type of update of a Python handler.
+#[derive(Clone, Copy)]
+enum UpdateType {
+    NewMessage,
+    MessageEdited,
+    CallbackQuery,
+    InlineQuery,
+}
+
+/// A Python handler.
+struct PyHandler {
+    /// The type of update to handle.
+    update_type: UpdateType,
+    /// The Python callback.
+    callback: Arc<PyObject>,
+}
+
+impl PyHandler {
+    /// Converts the Python handler into a [`Handler`].
+    ///
+    /// The coroutines returned by the callback are awaited in the event loop of `locals`.
+    fn into_handler(self, locals: TaskLocals) -> Handler {
+        let handler = match self.update_type {
+            UpdateType::NewMessage => handler::new_message(filters::always),
+            UpdateType::MessageEdited => handler::message_edited(filters::always),
+            UpdateType::CallbackQuery => handler::callback_query(filters::always),
+            UpdateType::InlineQuery => handler::inline_query(filters::always),
+        };
+        let callback = self.callback;
+
+        handler.then(move |ctx: crate::Context| {
+            let callback = callback.clone();
+            let locals = locals.clone();
+
+            async move {
+                let future = Python::with_gil(|py| {
+                    let result = callback.call1(py, (Context::from(ctx),))?.into_bound(py);
+
+                    if result.hasattr("__await__")? {
+                        into_future_with_locals(&locals, result).map(Some)
+                    } else {
+                        Ok(None)
+                    }
+                })?;
+
+                if let Some(future) = future {
+                    future.await?;
+                }
+
+                Ok(())
+            }
+        })
+    }
+}
@@ -8,9 +8,11 @@
 
 //! Context module.
 
+use grammers_client::{types::Media, Update};
 use pyo3::prelude::*;
+use pyo3_async_runtimes::tokio::future_into_py;
 
-use super::{Chat, Message};
+use super::{to_py_err, CallbackQuery, Chat, Document, InlineQuery, Message, Photo, Sticker};
 
 /// The context of an update.
 #[pyclass]
@@ -43,11 +45,121 @@ impl Context {
         self.0.query()
     }
 
+    /// The callback query of the update.
+    #[getter]
+    pub fn callback_query(&self) -> Option<CallbackQuery> {
+        self.0.callback_query().map(|q| q.into())
+    }
+
+    /// The inline query of the update.
+    #[getter]
+    pub fn inline_query(&self) -> Option<InlineQuery> {
+        self.0.inline_query().map(|q| q.into())
+    }
+
     /// Gets the message of the update.
     pub async fn message(&self) -> Option<Message> {
         self.0.message().await.map(|m| m.into())
     }
 
+    /// Gets the photo of the message.
+    pub async fn photo(&self) -> Option<Photo> {
+        let client = self.0.client().clone();
+
+        self.0.photo().await.map(|p| Photo::new(client, p))
+    }
+
+    /// Gets the document of the message.
+    pub async fn document(&self) -> Option<Document> {
+        match self.0.media().await {
+            Some(Media::Document(d)) => Some(Document::new(self.0.client().clone(), d)),
+            _ => None,
+        }
+    }
+
+    /// Gets the sticker of the message.
+    pub async fn sticker(&self) -> Option<Sticker> {
+        match self.0.media().await {
+            Some(Media::Sticker(s)) => Some(Sticker::new(self.0.client().clone(), s)),
+            _ => None,
+        }
+    }
+
+    /// Waits for a new message, in any chat.
+    ///
+    /// If the timeout is `None`, it will be set to 30 seconds.
+    #[pyo3(signature = (timeout=None))]
+    pub fn wait_for_message<'py>(
+        &self,
+        py: Python<'py>,
+        timeout: Option<u64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let ctx = self.0.clone();
+
+        future_into_py(py, async move {
+            ctx.wait_for_message(timeout, None)
+                .await
+                .map(Message::from)
+                .map_err(to_py_err)
+        })
+    }
+
+    /// Waits for a callback query, in any chat.
+    ///
+    /// Returns the context of the callback query.
+    ///
+    /// If the timeout is `None`, it will be set to 30 seconds.
+    #[pyo3(signature = (timeout=None))]
+    pub fn wait_for_callback<'py>(
+        &self,
+        py: Python<'py>,
+        timeout: Option<u64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let ctx = self.0.clone();
+
+        future_into_py(py, async move {
+            let query = ctx
+                .wait_for_callback_query(timeout, None)
+                .await
+                .map_err(to_py_err)?;
+
+            Ok(Context(ctx.clone_with(&Update::CallbackQuery(query))))
+        })
+    }
+
+    /// Sends a question to the chat and waits for the answer of the same sender.
+    ///
+    /// If the timeout is `None`, it will be set to 30 seconds.
+    #[pyo3(signature = (text, timeout=None))]
+    pub fn ask<'py>(
+        &self,
+        py: Python<'py>,
+        text: String,
+        timeout: Option<u64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let ctx = self.0.clone();
+
+        future_into_py(py, async move {
+            let chat_id = ctx.chat().map(|chat| chat.id());
+            let sender_id = ctx.sender().map(|sender| sender.id());
+
+            ctx.send(text).await.map_err(to_py_err)?;
+
+            loop {
+                let message = ctx
+                    .wait_for_message(timeout, None)
+                    .await
+                    .map_err(to_py_err)?;
+
+                if Some(message.chat().id()) == chat_id
+                    && message.sender().map(|sender| sender.id()) == sender_id
+                {
+                    return Ok(Message::from(message));
+                }
+            }
+        })
+    }
+
     pub fn __str__(&self) -> String {
         format!("{:?}", self.0)
     }
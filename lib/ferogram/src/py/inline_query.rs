@@ -0,0 +1,148 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Inline query module.
+
+use grammers_client::types::{self, inline::query::Article as InlineArticle};
+use pyo3::prelude::*;
+use pyo3_async_runtimes::tokio::future_into_py;
+
+use super::to_py_err;
+
+/// An inline query.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct InlineQuery(types::InlineQuery);
+
+#[pymethods]
+impl InlineQuery {
+    /// The ID of the sender.
+    #[getter]
+    pub fn sender_id(&self) -> i64 {
+        self.0.sender().id()
+    }
+
+    /// The text of the query.
+    #[getter]
+    pub fn text(&self) -> String {
+        self.0.text().to_string()
+    }
+
+    /// The offset of the query.
+    #[getter]
+    pub fn offset(&self) -> String {
+        self.0.offset().to_string()
+    }
+
+    /// Answers the query with a list of articles.
+    #[pyo3(signature = (results, next_offset=None, cache_time=None, private=false))]
+    pub fn answer<'py>(
+        &self,
+        py: Python<'py>,
+        results: Vec<Article>,
+        next_offset: Option<String>,
+        cache_time: Option<i32>,
+        private: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let query = self.0.clone();
+
+        future_into_py(py, async move {
+            let mut answer = query.answer(results.into_iter().map(InlineArticle::from));
+            if let Some(next_offset) = next_offset {
+                answer = answer.next_offset(next_offset);
+            }
+            if let Some(cache_time) = cache_time {
+                answer = answer.cache_time(cache_time);
+            }
+            if private {
+                answer = answer.private();
+            }
+
+            answer.send().await.map_err(to_py_err)
+        })
+    }
+
+    pub fn __str__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+impl From<types::InlineQuery> for InlineQuery {
+    fn from(query: types::InlineQuery) -> Self {
+        Self(query)
+    }
+}
+
+impl From<InlineQuery> for types::InlineQuery {
+    fn from(query: InlineQuery) -> Self {
+        query.0
+    }
+}
+
+/// An inline article result.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct Article {
+    /// The ID of the result.
+    id: Option<String>,
+    /// The title of the result.
+    title: String,
+    /// The text sent when the result is chosen.
+    text: String,
+    /// The description of the result.
+    description: Option<String>,
+    /// The thumbnail URL of the result.
+    thumb_url: Option<String>,
+}
+
+#[pymethods]
+impl Article {
+    #[new]
+    #[pyo3(signature = (title, text, id=None, description=None, thumb_url=None))]
+    pub fn new(
+        title: String,
+        text: String,
+        id: Option<String>,
+        description: Option<String>,
+        thumb_url: Option<String>,
+    ) -> Self {
+        Self {
+            id,
+            title,
+            text,
+            description,
+            thumb_url,
+        }
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+impl From<Article> for InlineArticle {
+    fn from(article: Article) -> Self {
+        let mut result = InlineArticle::new(article.title, article.text);
+
+        if let Some(id) = article.id {
+            result = result.id(id);
+        }
+        if let Some(description) = article.description {
+            result = result.description(description);
+        }
+        if let Some(thumb_url) = article.thumb_url {
+            result = result.thumb_url(thumb_url);
+        }
+
+        result
+    }
+}
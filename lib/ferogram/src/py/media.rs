@@ -0,0 +1,192 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Media module.
+
+use grammers_client::{
+    types::media::{Document as DocumentMedia, Photo as PhotoMedia, Sticker as StickerMedia},
+    Client,
+};
+use pyo3::prelude::*;
+use pyo3_async_runtimes::tokio::future_into_py;
+
+use super::to_py_err;
+
+/// A photo.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct Photo(Client, PhotoMedia);
+
+#[pymethods]
+impl Photo {
+    /// The ID of the photo.
+    #[getter]
+    pub fn id(&self) -> i64 {
+        self.1.id()
+    }
+
+    /// The size of the photo, in bytes.
+    #[getter]
+    pub fn size(&self) -> i64 {
+        self.1.size()
+    }
+
+    /// Downloads the photo to `path`.
+    pub fn download<'py>(&self, py: Python<'py>, path: String) -> PyResult<Bound<'py, PyAny>> {
+        let Self(client, photo) = self.clone();
+
+        future_into_py(py, async move {
+            client.download_media(&photo, path).await.map_err(to_py_err)
+        })
+    }
+
+    pub fn __str__(&self) -> String {
+        format!("{:?}", self.1)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.1)
+    }
+}
+
+impl Photo {
+    /// Creates a new photo.
+    pub(crate) fn new(client: Client, photo: PhotoMedia) -> Self {
+        Self(client, photo)
+    }
+}
+
+/// A document.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct Document(Client, DocumentMedia);
+
+#[pymethods]
+impl Document {
+    /// The ID of the document.
+    #[getter]
+    pub fn id(&self) -> i64 {
+        self.1.id()
+    }
+
+    /// The file name of the document.
+    #[getter]
+    pub fn name(&self) -> String {
+        self.1.name().to_string()
+    }
+
+    /// The mime type of the document.
+    #[getter]
+    pub fn mime_type(&self) -> Option<String> {
+        self.1.mime_type().map(ToString::to_string)
+    }
+
+    /// The size of the document, in bytes.
+    #[getter]
+    pub fn size(&self) -> i64 {
+        self.1.size()
+    }
+
+    /// Downloads the document to `path`.
+    pub fn download<'py>(&self, py: Python<'py>, path: String) -> PyResult<Bound<'py, PyAny>> {
+        let Self(client, document) = self.clone();
+
+        future_into_py(py, async move {
+            client
+                .download_media(&document, path)
+                .await
+                .map_err(to_py_err)
+        })
+    }
+
+    pub fn __str__(&self) -> String {
+        format!("{:?}", self.1)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.1)
+    }
+}
+
+impl Document {
+    /// Creates a new document.
+    pub(crate) fn new(client: Client, document: DocumentMedia) -> Self {
+        Self(client, document)
+    }
+}
+
+/// A sticker.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct Sticker(Client, StickerMedia);
+
+#[pymethods]
+impl Sticker {
+    /// The ID of the sticker.
+    #[getter]
+    pub fn id(&self) -> i64 {
+        self.1.document.id()
+    }
+
+    /// The file name of the sticker.
+    #[getter]
+    pub fn name(&self) -> String {
+        self.1.document.name().to_string()
+    }
+
+    /// The mime type of the sticker.
+    #[getter]
+    pub fn mime_type(&self) -> Option<String> {
+        self.1.document.mime_type().map(ToString::to_string)
+    }
+
+    /// The size of the sticker, in bytes.
+    #[getter]
+    pub fn size(&self) -> i64 {
+        self.1.document.size()
+    }
+
+    /// The emoji of the sticker.
+    #[getter]
+    pub fn emoji(&self) -> String {
+        self.1.emoji().to_string()
+    }
+
+    /// Whether the sticker is animated.
+    #[getter]
+    pub fn is_animated(&self) -> bool {
+        self.1.is_animated()
+    }
+
+    /// Downloads the sticker to `path`.
+    pub fn download<'py>(&self, py: Python<'py>, path: String) -> PyResult<Bound<'py, PyAny>> {
+        let Self(client, sticker) = self.clone();
+
+        future_into_py(py, async move {
+            client
+                .download_media(&sticker, path)
+                .await
+                .map_err(to_py_err)
+        })
+    }
+
+    pub fn __str__(&self) -> String {
+        format!("{:?}", self.1)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.1)
+    }
+}
+
+impl Sticker {
+    /// Creates a new sticker.
+    pub(crate) fn new(client: Client, sticker: StickerMedia) -> Self {
+        Self(client, sticker)
+    }
+}
@@ -13,8 +13,9 @@ use pyo3::{
     prelude::*,
     types::{timezone_utc, PyDateTime},
 };
+use pyo3_async_runtimes::tokio::future_into_py;
 
-use super::Chat;
+use super::{to_py_err, Chat};
 
 /// A message.
 #[pyclass]
@@ -51,6 +52,65 @@ impl Message {
         )
     }
 
+    /// Replies to the message.
+    ///
+    /// Returns the sent message.
+    pub fn reply<'py>(&self, py: Python<'py>, text: String) -> PyResult<Bound<'py, PyAny>> {
+        let message = self.0.clone();
+
+        future_into_py(py, async move {
+            message
+                .reply(text)
+                .await
+                .map(Message::from)
+                .map_err(to_py_err)
+        })
+    }
+
+    /// Edits the message.
+    pub fn edit<'py>(&self, py: Python<'py>, text: String) -> PyResult<Bound<'py, PyAny>> {
+        let message = self.0.clone();
+
+        future_into_py(
+            py,
+            async move { message.edit(text).await.map_err(to_py_err) },
+        )
+    }
+
+    /// Deletes the message.
+    pub fn delete<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let message = self.0.clone();
+
+        future_into_py(py, async move { message.delete().await.map_err(to_py_err) })
+    }
+
+    /// Forwards the message to another chat.
+    ///
+    /// Returns the forwarded message.
+    pub fn forward_to<'py>(&self, py: Python<'py>, chat: Chat) -> PyResult<Bound<'py, PyAny>> {
+        let message = self.0.clone();
+        let chat = types::Chat::from(chat).pack();
+
+        future_into_py(py, async move {
+            message
+                .forward_to(chat)
+                .await
+                .map(Message::from)
+                .map_err(to_py_err)
+        })
+    }
+
+    /// Downloads the media of the message to `path`.
+    ///
+    /// Returns `False` if the message has no media.
+    pub fn download<'py>(&self, py: Python<'py>, path: String) -> PyResult<Bound<'py, PyAny>> {
+        let message = self.0.clone();
+
+        future_into_py(py, async move {
+            message.download_media(path).await.map_err(to_py_err)
+        })
+    }
+
     pub fn __str__(&self) -> String {
         format!("{:?}", self.0)
     }
@@ -8,10 +8,25 @@
 
 //! Python module.
 
+mod callback_query;
 mod chat;
+mod client;
 mod context;
+mod inline_query;
+mod media;
 mod message;
 
+use pyo3::{exceptions::PyRuntimeError, PyErr};
+
+pub use callback_query::CallbackQuery;
 pub use chat::{Chat, UserStatus};
+pub use client::Client;
 pub use context::Context;
+pub use inline_query::{Article, InlineQuery};
+pub use media::{Document, Photo, Sticker};
 pub use message::Message;
+
+/// Converts an error into a Python `RuntimeError`.
+pub(crate) fn to_py_err<E: std::fmt::Display>(e: E) -> PyErr {
+    PyRuntimeError::new_err(e.to_string())
+}
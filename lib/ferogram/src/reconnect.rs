@@ -0,0 +1,282 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reconnect module.
+
+use std::{
+    fmt,
+    ops::ControlFlow,
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+};
+
+use grammers_client::ReconnectionPolicy;
+
+/// The max delay between the attempts of the built-in policies.
+const MAX_DELAY: Duration = Duration::from_secs(300);
+
+/// The slots of the policies given to grammers, which needs them `'static`.
+static SLOTS: Mutex<Vec<&'static PolicySlot>> = Mutex::new(Vec::new());
+
+/// Handles the reconnect events, see [`crate::Builder::on_reconnect`].
+pub(crate) type ReconnectHandler = Arc<dyn Fn(ReconnectEvent) + Send + Sync>;
+
+/// How the client reconnects when it loses the connection.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example(client: ferogram::Client) {
+/// use std::time::Duration;
+///
+/// use ferogram::Reconnect;
+///
+/// let client = client.reconnect(Reconnect::exponential(5, Duration::from_secs(1)));
+/// # }
+/// ```
+#[derive(Clone, Copy)]
+pub struct Reconnect {
+    /// The kind of the policy.
+    kind: Kind,
+}
+
+/// The kind of a [`Reconnect`] policy.
+#[derive(Clone, Copy)]
+enum Kind {
+    /// Never reconnects.
+    Never,
+    /// Doubles the delay after each attempt.
+    Exponential {
+        /// The max attempts, unlimited if not set.
+        max_attempts: Option<usize>,
+        /// The delay of the first attempt.
+        base: Duration,
+    },
+    /// A user-defined policy.
+    Custom(&'static dyn ReconnectionPolicy),
+}
+
+impl Reconnect {
+    /// Never reconnects, the default of grammers.
+    pub fn never() -> Self {
+        Self { kind: Kind::Never }
+    }
+
+    /// Reconnects up to `max_attempts` times, waiting `base` before the first
+    /// attempt and doubling it after each one, up to 5 minutes.
+    pub fn exponential(max_attempts: usize, base: Duration) -> Self {
+        Self {
+            kind: Kind::Exponential {
+                max_attempts: Some(max_attempts),
+                base,
+            },
+        }
+    }
+
+    /// Reconnects forever, waiting 1 second before the first attempt and
+    /// doubling it after each one, up to 5 minutes.
+    pub fn forever() -> Self {
+        Self {
+            kind: Kind::Exponential {
+                max_attempts: None,
+                base: Duration::from_secs(1),
+            },
+        }
+    }
+
+    /// Uses a user-defined policy.
+    pub fn custom<P: ReconnectionPolicy>(policy: &'static P) -> Self {
+        Self {
+            kind: Kind::Custom(policy),
+        }
+    }
+
+    /// Returns the delay before the attempt, or `None` to give up.
+    pub fn delay(&self, attempt: usize) -> Option<Duration> {
+        match self.kind {
+            Kind::Never => None,
+            Kind::Exponential { max_attempts, base } => {
+                if max_attempts.is_some_and(|max_attempts| attempt > max_attempts) {
+                    return None;
+                }
+
+                let factor = 2u32.saturating_pow(attempt.saturating_sub(1) as u32);
+                Some(base.saturating_mul(factor).min(MAX_DELAY))
+            }
+            Kind::Custom(policy) => match policy.should_retry(attempt) {
+                ControlFlow::Continue(delay) => Some(delay),
+                ControlFlow::Break(()) => None,
+            },
+        }
+    }
+}
+
+impl Default for Reconnect {
+    fn default() -> Self {
+        Self::never()
+    }
+}
+
+impl fmt::Debug for Reconnect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            Kind::Never => f.write_str("Never"),
+            Kind::Exponential { max_attempts, base } => f
+                .debug_struct("Exponential")
+                .field("max_attempts", &max_attempts)
+                .field("base", &base)
+                .finish(),
+            Kind::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+/// An attempt to reconnect, sent to the [`crate::Builder::on_reconnect`] hook.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReconnectEvent {
+    /// The number of the attempt.
+    pub attempt: usize,
+    /// The delay before the attempt, or `None` if the client gave up.
+    pub delay: Option<Duration>,
+}
+
+/// The policy given to grammers, emitting the reconnect events.
+pub(crate) struct ReconnectPolicy {
+    /// The policy.
+    pub(crate) reconnect: Reconnect,
+    /// The reconnect hook, if any.
+    pub(crate) handler: Option<ReconnectHandler>,
+}
+
+impl ReconnectionPolicy for ReconnectPolicy {
+    fn should_retry(&self, attempts: usize) -> ControlFlow<(), Duration> {
+        let delay = self.reconnect.delay(attempts);
+
+        match delay {
+            Some(delay) => log::warn!(
+                "Connection lost, reconnecting in {:?} (attempt {})",
+                delay,
+                attempts
+            ),
+            None => log::error!("Connection lost, giving up after {} attempts", attempts),
+        }
+
+        if let Some(handler) = self.handler.as_ref() {
+            handler(ReconnectEvent {
+                attempt: attempts,
+                delay,
+            });
+        }
+
+        match delay {
+            Some(delay) => ControlFlow::Continue(delay),
+            None => ControlFlow::Break(()),
+        }
+    }
+}
+
+/// The policy given to grammers in place of a [`ReconnectPolicy`] owned by a client.
+///
+/// The slots are never freed, but are reused once the policy of their client is
+/// dropped, so they are bounded by the clients alive at once.
+#[derive(Default)]
+pub(crate) struct PolicySlot {
+    /// The policy of the client using the slot.
+    policy: Mutex<Weak<ReconnectPolicy>>,
+}
+
+impl PolicySlot {
+    /// Gives the policy a slot, reusing a free one if any.
+    pub(crate) fn attach(policy: &Arc<ReconnectPolicy>) -> &'static Self {
+        let mut slots = SLOTS.lock().expect("Failed to lock the policy slots");
+
+        let slot = match slots.iter().find(|slot| slot.is_free()) {
+            Some(slot) => *slot,
+            None => {
+                let slot: &'static Self = Box::leak(Box::default());
+                slots.push(slot);
+                slot
+            }
+        };
+        *slot.policy.lock().expect("Failed to lock the policy") = Arc::downgrade(policy);
+
+        slot
+    }
+
+    /// Checks if the policy of the slot was dropped.
+    fn is_free(&self) -> bool {
+        self.policy
+            .lock()
+            .expect("Failed to lock the policy")
+            .strong_count()
+            == 0
+    }
+}
+
+impl ReconnectionPolicy for PolicySlot {
+    fn should_retry(&self, attempts: usize) -> ControlFlow<(), Duration> {
+        let policy = self
+            .policy
+            .lock()
+            .expect("Failed to lock the policy")
+            .upgrade();
+
+        match policy {
+            Some(policy) => policy.should_retry(attempts),
+            None => ControlFlow::Break(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exponential_delay() {
+        let reconnect = Reconnect::exponential(3, Duration::from_secs(2));
+
+        assert_eq!(reconnect.delay(1), Some(Duration::from_secs(2)));
+        assert_eq!(reconnect.delay(2), Some(Duration::from_secs(4)));
+        assert_eq!(reconnect.delay(3), Some(Duration::from_secs(8)));
+        assert_eq!(reconnect.delay(4), None);
+    }
+
+    #[test]
+    fn test_forever_delay() {
+        let reconnect = Reconnect::forever();
+
+        assert_eq!(reconnect.delay(1), Some(Duration::from_secs(1)));
+        assert_eq!(reconnect.delay(9), Some(Duration::from_secs(256)));
+        assert_eq!(reconnect.delay(10), Some(MAX_DELAY));
+        assert_eq!(reconnect.delay(1000), Some(MAX_DELAY));
+        assert_eq!(Reconnect::never().delay(1), None);
+    }
+
+    #[test]
+    fn test_policy_slot() {
+        let policy = Arc::new(ReconnectPolicy {
+            reconnect: Reconnect::forever(),
+            handler: None,
+        });
+        let slot = PolicySlot::attach(&policy);
+        assert_eq!(
+            slot.should_retry(1),
+            ControlFlow::Continue(Duration::from_secs(1))
+        );
+
+        drop(policy);
+        assert_eq!(slot.should_retry(1), ControlFlow::Break(()));
+
+        let policy = Arc::new(ReconnectPolicy {
+            reconnect: Reconnect::never(),
+            handler: None,
+        });
+        assert!(std::ptr::eq(slot, PolicySlot::attach(&policy)));
+    }
+}
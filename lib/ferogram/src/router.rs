@@ -8,10 +8,15 @@
 
 //! Router module.
 
+use std::borrow::Cow;
+
 use async_recursion::async_recursion;
 use grammers_client::Update;
 
-use crate::{di::Injector, filter::Command, middleware::MiddlewareStack, Handler, Result};
+use crate::{
+    di::Injector, event::Event, filter::Command, middleware::MiddlewareStack,
+    shedding::LoadShedder, Context, Handler, Result,
+};
 
 /// A router.
 ///
@@ -24,6 +29,10 @@ pub struct Router {
     pub(crate) routers: Vec<Router>,
     /// The middleware stack.
     pub(crate) middlewares: MiddlewareStack,
+    /// The priority, routers with higher priority run first.
+    pub(crate) priority: i32,
+    /// The resources visible only to the handlers and routers of the router.
+    pub(crate) resources: Injector,
 }
 
 impl Router {
@@ -82,6 +91,26 @@ impl Router {
         self
     }
 
+    /// Attachs a injector, visible only to the handlers and routers of the router.
+    ///
+    /// Shadows the resources of the same type from the parent routers and the dispatcher.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() {
+    /// # let router = unimplemented!();
+    /// let router = router.resources(|injector| {
+    ///     injector.with(String::from("Hello, world!"))
+    /// });
+    /// # }
+    /// ```
+    pub fn resources<D: FnOnce(Injector) -> Injector>(mut self, injector: D) -> Self {
+        let mut injector = injector(Injector::default());
+        self.resources.extend(&mut injector);
+        self
+    }
+
     /// Returns the commands from the handlers.
     pub(crate) fn get_commands(&self) -> Vec<Command> {
         let mut commands = Vec::new();
@@ -89,13 +118,34 @@ impl Router {
         commands.extend(
             self.handlers
                 .iter()
-                .filter_map(|handler| handler.command.clone()),
+                .flat_map(|handler| handler.commands.clone()),
         );
         commands.extend(self.routers.iter().flat_map(|router| router.get_commands()));
 
         commands
     }
 
+    /// Prepends `prefix` to the names of the commands, including the ones of the nested routers.
+    pub(crate) fn prefix_commands(&mut self, prefix: &str) {
+        self.handlers
+            .iter_mut()
+            .for_each(|handler| handler.prefix_commands(prefix));
+        self.routers
+            .iter_mut()
+            .for_each(|router| router.prefix_commands(prefix));
+    }
+
+    /// Attachs the services of the dispatcher consulted by the filters, including
+    /// the ones of the nested routers.
+    pub(crate) fn attach(&mut self, services: &Injector) {
+        self.handlers
+            .iter_mut()
+            .for_each(|handler| handler.attach(services));
+        self.routers
+            .iter_mut()
+            .for_each(|router| router.attach(services));
+    }
+
     /// Handle the update sent by Telegram.
     ///
     /// Returns `Ok(())` if the update was handled.
@@ -121,14 +171,50 @@ impl Router {
     ) -> Result<bool> {
         let mut middlewares = middlewares.extend(self.middlewares.clone());
 
+        let mut scoped_injector;
+        let injector = if self.resources.is_empty() {
+            injector
+        } else {
+            scoped_injector = injector.clone();
+            scoped_injector.shadow(&self.resources);
+            &mut scoped_injector
+        };
+
         for handler in self.handlers.iter_mut() {
             let mut middleware_flow = middlewares.handle_before(client, update, injector).await;
             if middleware_flow.is_continue() {
+                // The replaced update is only seen by this handler.
+                let mut replaced_injector;
+                let (update, injector) = match middleware_flow.update.take() {
+                    Some(new_update) => {
+                        replaced_injector = injector.clone();
+                        replaced_injector
+                            .update(|_: Update| new_update.clone())
+                            .ok();
+                        replaced_injector
+                            .update(|ctx: Context| ctx.clone_with(&new_update))
+                            .ok();
+
+                        (Cow::Owned(new_update), &mut replaced_injector)
+                    }
+                    None => (Cow::Borrowed(update), &mut *injector),
+                };
+                let update = update.as_ref();
+
                 let mut flow = handler.check(client, update).await;
                 flow.injector.extend(&mut middleware_flow.injector);
 
                 if flow.is_continue() {
                     if let Some(endpoint) = handler.endpoint.as_mut() {
+                        let shedder = injector.get::<LoadShedder>().cloned();
+                        if let Some(shedder) = shedder.as_ref() {
+                            if shedder.should_shed(handler.priority) {
+                                shedder.reject(update).await;
+                                return Ok(true);
+                            }
+                        }
+                        let _in_flight = shedder.as_ref().map(LoadShedder::start);
+
                         let mut handler_injector = flow.injector;
                         injector.extend(&mut handler_injector);
 
@@ -145,14 +231,14 @@ impl Router {
                             Update::Raw(raw) => injector.insert(raw),
                             _ => {}
                         }
+                        injector.shadow(&handler.resources);
 
                         match endpoint.handle(injector).await {
                             Ok(()) => {
-                                return {
-                                    middlewares.handle_after(client, update, injector).await;
-
-                                    Ok(true)
-                                }
+                                return middlewares
+                                    .handle_after(client, update, injector)
+                                    .await
+                                    .map(|_| true);
                             }
                             Err(e) => {
                                 if let Some(err_filter) = handler.err_handler.as_mut() {
@@ -163,16 +249,23 @@ impl Router {
                                         let mut flow_injector = flow.injector;
                                         injector.extend(&mut flow_injector);
 
-                                        return endpoint.handle(injector).await.map(|_| true);
+                                        return endpoint
+                                            .handle(injector)
+                                            .await
+                                            .inspect_err(|_| handler.record_failure())
+                                            .map(|_| true);
                                     }
 
                                     return Ok(true);
                                 }
 
+                                handler.record_failure();
                                 return Err(e);
                             }
                         }
                     }
+                } else if handler.handle_usage_error(client, update, &flow).await {
+                    return Ok(true);
                 }
             }
         }
@@ -190,12 +283,49 @@ impl Router {
 
         Ok(false)
     }
+
+    /// Handle the event published in the event bus.
+    ///
+    /// Unlike updates, the event is sent to every handler subscribed to it,
+    /// and the middlewares are not executed.
+    #[async_recursion]
+    pub(crate) async fn handle_event(&mut self, event: &Event, injector: &Injector) -> Result<()> {
+        let mut scoped_injector = injector.clone();
+        scoped_injector.shadow(&self.resources);
+        let injector = &scoped_injector;
+
+        for handler in self.handlers.iter_mut() {
+            if handler.is_event(event) {
+                if let Some(endpoint) = handler.endpoint.as_mut() {
+                    let mut injector = injector.clone();
+                    injector.insert_resource(event.type_id, event.resource.clone());
+                    injector.shadow(&handler.resources);
+
+                    endpoint
+                        .handle(&mut injector)
+                        .await
+                        .inspect_err(|_| handler.record_failure())?;
+                }
+            }
+        }
+
+        for router in self.routers.iter_mut() {
+            router.handle_event(event, injector).await?;
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    };
+
     use async_trait::async_trait;
-    use grammers_client::Client;
+    use grammers_client::{grammers_tl_types as tl, Client};
 
     use super::*;
     use crate::{flow, handler, Flow, Middleware};
@@ -235,6 +365,8 @@ mod tests {
             handlers: Vec::new(),
             routers: Vec::new(),
             middlewares: MiddlewareStack::new(),
+            priority: 0,
+            resources: Injector::default(),
         };
 
         let updated_router = router
@@ -243,4 +375,98 @@ mod tests {
         assert_eq!(updated_router.middlewares.before.len(), 1);
         assert_eq!(updated_router.middlewares.after.len(), 1);
     }
+
+    /// Replaces the update only the first time.
+    #[derive(Clone, Default)]
+    struct ReplaceOnce {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Middleware for ReplaceOnce {
+        async fn handle(
+            &mut self,
+            _client: &Client,
+            _update: &Update,
+            _injector: &mut Injector,
+        ) -> Flow {
+            if self.calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                Flow::with_update(raw_update(2))
+            } else {
+                flow::continue_now()
+            }
+        }
+    }
+
+    fn raw_update(id: i32) -> Update {
+        Update::Raw(tl::enums::Update::MessageId(tl::types::UpdateMessageId {
+            id,
+            random_id: 1,
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_replaced_update_scope() {
+        let client = crate::Client::bot(std::env::var("BOT_TOKEN").unwrap_or_default())
+            .api_id(
+                std::env::var("API_ID")
+                    .unwrap_or("123456789".to_string())
+                    .parse::<i32>()
+                    .unwrap(),
+            )
+            .api_hash(std::env::var("API_HASH").unwrap_or_default())
+            .build()
+            .await
+            .unwrap();
+
+        let seen = Arc::new(Mutex::new(None));
+        let endpoint_seen = seen.clone();
+        let mut router = Router::default()
+            .middlewares(|middlewares| middlewares.before(ReplaceOnce::default()))
+            .register(handler::new_update(|_, _| async { false }).then(|| async { Ok(()) }))
+            .register(handler::then(move |update: Update| {
+                let seen = endpoint_seen.clone();
+
+                async move {
+                    *seen.lock().unwrap() = Some(update);
+                    Ok(())
+                }
+            }));
+
+        let update = raw_update(1);
+        let mut injector = Injector::default().with(update.clone());
+        assert!(router
+            .handle_update(
+                client.inner(),
+                &update,
+                &mut injector,
+                MiddlewareStack::new()
+            )
+            .await
+            .unwrap());
+
+        let seen = seen.lock().unwrap().take();
+        assert!(matches!(
+            seen,
+            Some(Update::Raw(tl::enums::Update::MessageId(
+                tl::types::UpdateMessageId { id: 1, .. }
+            )))
+        ));
+    }
+
+    #[test]
+    fn test_resources() {
+        let router = Router::default()
+            .resources(|injector| injector.with(String::from("router")))
+            .register(handler::then(|| async { Ok(()) }).with_resource(String::from("handler")));
+
+        let mut injector = Injector::default().with(String::from("dispatcher"));
+        injector.shadow(&router.resources);
+        injector.shadow(&router.handlers[0].resources);
+
+        assert_eq!(injector.get::<String>().unwrap(), "handler");
+        assert_eq!(*injector.take::<String>().unwrap(), "handler");
+        assert_eq!(*injector.take::<String>().unwrap(), "router");
+        assert_eq!(*injector.take::<String>().unwrap(), "dispatcher");
+    }
 }
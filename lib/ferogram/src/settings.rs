@@ -0,0 +1,171 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Settings module.
+
+use std::{fmt, str::FromStr, sync::Arc};
+
+use async_trait::async_trait;
+
+use crate::{utils::Locale, Result, Storage};
+
+/// Provides the command prefixes of the chats, consulted by [`crate::filter::command`]
+/// when checking the updates.
+///
+/// Implemented by [`ChatSettings`], which returns the `prefix` setting of the chat.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use async_trait::async_trait;
+/// use ferogram::PrefixProvider;
+///
+/// struct DbPrefixes;
+///
+/// #[async_trait]
+/// impl PrefixProvider for DbPrefixes {
+///     async fn prefixes(&self, chat_id: i64) -> Option<Vec<String>> {
+///         // Query the database...
+///         Some(vec![String::from("!"), String::from(".")])
+///     }
+/// }
+///
+/// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+/// let dispatcher = dispatcher.prefix_provider(DbPrefixes);
+/// # }
+/// ```
+#[async_trait]
+pub trait PrefixProvider: Send + Sync + 'static {
+    /// Returns the prefixes of the chat, or `None` to use the static ones.
+    async fn prefixes(&self, chat_id: i64) -> Option<Vec<String>>;
+}
+
+/// The per-chat settings, backed by a [`Storage`].
+///
+/// Injected in the handlers when attached to the dispatcher, and consulted by
+/// [`crate::filter::command`] for the per-chat prefix, as a [`PrefixProvider`].
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use ferogram::{utils::Locale, ChatSettings, Context};
+///
+/// async fn set_portuguese(ctx: Context, settings: ChatSettings) -> ferogram::Result<()> {
+///     let chat = ctx.chat().unwrap();
+///     settings.set_language(chat.id(), Locale::Portuguese).await?;
+///
+///     Ok(())
+/// }
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ChatSettings {
+    /// The storage of the settings.
+    storage: Arc<dyn Storage>,
+}
+
+impl ChatSettings {
+    /// Creates the settings backed by `storage`.
+    pub fn new<S: Storage>(storage: S) -> Self {
+        Self {
+            storage: Arc::new(storage),
+        }
+    }
+
+    /// Returns the storage key of the setting.
+    fn key(chat_id: i64, name: &str) -> String {
+        format!("chat:{}:{}", chat_id, name)
+    }
+
+    /// Gets a setting of the chat.
+    ///
+    /// Returns `None` if the setting is not set or can't be parsed.
+    pub async fn get<T: FromStr>(&self, chat_id: i64, name: &str) -> Result<Option<T>> {
+        let value = self.storage.get(&Self::key(chat_id, name)).await?;
+
+        Ok(value.and_then(|value| value.parse().ok()))
+    }
+
+    /// Sets a setting of the chat.
+    pub async fn set<T: ToString>(&self, chat_id: i64, name: &str, value: T) -> Result<()> {
+        self.storage
+            .set(&Self::key(chat_id, name), value.to_string())
+            .await
+    }
+
+    /// Removes a setting of the chat.
+    pub async fn remove(&self, chat_id: i64, name: &str) -> Result<()> {
+        self.storage.remove(&Self::key(chat_id, name)).await
+    }
+
+    /// Gets the language of the chat.
+    pub async fn language(&self, chat_id: i64) -> Result<Option<Locale>> {
+        let code = self.get::<String>(chat_id, "language").await?;
+
+        Ok(code.map(|code| Locale::from_lang_code(&code)))
+    }
+
+    /// Sets the language of the chat.
+    pub async fn set_language(&self, chat_id: i64, locale: Locale) -> Result<()> {
+        self.set(chat_id, "language", locale.code()).await
+    }
+
+    /// Returns the locale of the chat.
+    ///
+    /// Uses the language of the chat when set, otherwise the language code of the user.
+    pub async fn locale(&self, chat_id: i64, lang_code: Option<&str>) -> Locale {
+        match self.language(chat_id).await {
+            Ok(Some(locale)) => locale,
+            _ => lang_code.map(Locale::from_lang_code).unwrap_or_default(),
+        }
+    }
+
+    /// Gets the command prefix of the chat.
+    pub async fn prefix(&self, chat_id: i64) -> Result<Option<String>> {
+        self.get(chat_id, "prefix").await
+    }
+
+    /// Sets the command prefix of the chat, replacing the prefixes of the commands.
+    pub async fn set_prefix(&self, chat_id: i64, prefix: &str) -> Result<()> {
+        self.set(chat_id, "prefix", prefix).await
+    }
+
+    /// Checks if the feature is enabled in the chat.
+    ///
+    /// Returns `None` if the chat did not enable or disable it.
+    pub async fn feature(&self, chat_id: i64, feature: &str) -> Result<Option<bool>> {
+        self.get(chat_id, &format!("feature:{}", feature)).await
+    }
+
+    /// Enables or disables the feature in the chat.
+    pub async fn set_feature(&self, chat_id: i64, feature: &str, enabled: bool) -> Result<()> {
+        self.set(chat_id, &format!("feature:{}", feature), enabled)
+            .await
+    }
+}
+
+impl fmt::Debug for ChatSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChatSettings").finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl PrefixProvider for ChatSettings {
+    async fn prefixes(&self, chat_id: i64) -> Option<Vec<String>> {
+        match self.prefix(chat_id).await {
+            Ok(prefix) => prefix.map(|prefix| vec![prefix]),
+            Err(e) => {
+                log::error!("Error getting the prefix of the chat: {:?}", e);
+                None
+            }
+        }
+    }
+}
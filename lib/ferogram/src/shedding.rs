@@ -0,0 +1,329 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Load shedding module.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use grammers_client::Update;
+
+/// How many of the last handler latencies are kept to compute the p95.
+const LATENCY_SAMPLES: usize = 200;
+
+/// The latencies needed before the p95 is considered.
+const MIN_LATENCY_SAMPLES: usize = 20;
+
+/// How long a latency is kept to compute the p95.
+const LATENCY_WINDOW: Duration = Duration::from_secs(60);
+
+/// How long a chat isn't replied again after a busy reply.
+const BUSY_REPLY_WINDOW: Duration = Duration::from_secs(60);
+
+/// A dispatcher-level load shedder.
+///
+/// While the handlers in flight exceed the threshold, or the p95 latency of the
+/// last handlers of the last minute crosses the limit, the handlers with a priority lower than the
+/// minimum are rejected, optionally replying a "busy" message, so the important
+/// handlers keep responsive under load.
+///
+/// The priority of the handlers is set with [`crate::handler::Handler::priority`], `0` by
+/// default.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use std::time::Duration;
+///
+/// use ferogram::{filters, handler, shedding::LoadShedder, Context};
+///
+/// # let dispatcher: ferogram::Dispatcher = unimplemented!();
+/// let shedder = LoadShedder::new()
+///     .max_in_flight(500)
+///     .max_p95(Duration::from_secs(2))
+///     .busy_reply("I'm busy right now, try again later.");
+///
+/// let dispatcher = dispatcher.load_shedder(shedder).router(|router| {
+///     router.register(
+///         handler::new_message(filters::command("meme"))
+///             .then(|ctx: Context| async move { Ok(()) })
+///             .priority(-1),
+///     )
+/// });
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct LoadShedder {
+    /// The max handlers in flight before shedding.
+    max_in_flight: Option<usize>,
+    /// The max p95 latency of the handlers before shedding.
+    max_p95: Option<Duration>,
+    /// The handlers with a lower priority are shed.
+    min_priority: i32,
+    /// The reply sent to the rejected updates, if any.
+    busy_reply: Option<String>,
+    /// The handlers in flight.
+    in_flight: Arc<AtomicUsize>,
+    /// The latencies of the last handlers, with when they finished.
+    latencies: Arc<Mutex<VecDeque<(Instant, Duration)>>>,
+    /// When the chats were last replied the busy text.
+    replied: Arc<Mutex<HashMap<i64, Instant>>>,
+}
+
+impl LoadShedder {
+    /// Creates a load shedder without limits.
+    pub fn new() -> Self {
+        Self {
+            max_in_flight: None,
+            max_p95: None,
+            min_priority: 0,
+            busy_reply: None,
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            latencies: Arc::new(Mutex::new(VecDeque::with_capacity(LATENCY_SAMPLES))),
+            replied: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Sheds when more than `max` handlers are in flight.
+    pub fn max_in_flight(mut self, max: usize) -> Self {
+        self.max_in_flight = Some(max);
+        self
+    }
+
+    /// Sheds when the p95 latency of the last handlers is longer than `max`.
+    pub fn max_p95(mut self, max: Duration) -> Self {
+        self.max_p95 = Some(max);
+        self
+    }
+
+    /// Sets the minimum priority of the handlers kept while overloaded.
+    ///
+    /// Default is `0`, so only the handlers with a negative priority are shed.
+    pub fn min_priority(mut self, priority: i32) -> Self {
+        self.min_priority = priority;
+        self
+    }
+
+    /// Replies the text to the rejected messages and callback queries.
+    ///
+    /// Each chat is replied at most once a minute. By default, the rejected
+    /// updates are ignored.
+    pub fn busy_reply<T: ToString>(mut self, text: T) -> Self {
+        self.busy_reply = Some(text.to_string());
+        self
+    }
+
+    /// Returns the handlers in flight.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Returns the p95 latency of the handlers finished in the last minute.
+    ///
+    /// Returns `None` until enough handlers are measured.
+    pub fn p95(&self) -> Option<Duration> {
+        let mut latencies = self.latencies.lock().expect("Failed to lock the latencies");
+        while latencies
+            .front()
+            .is_some_and(|(finished_at, _)| finished_at.elapsed() > LATENCY_WINDOW)
+        {
+            latencies.pop_front();
+        }
+        if latencies.len() < MIN_LATENCY_SAMPLES {
+            return None;
+        }
+
+        let mut latencies = latencies
+            .iter()
+            .map(|(_, latency)| *latency)
+            .collect::<Vec<_>>();
+        latencies.sort_unstable();
+
+        let index = (latencies.len() * 95).div_ceil(100) - 1;
+        Some(latencies[index])
+    }
+
+    /// Checks if the in flight handlers or the latency crossed the limits.
+    pub fn is_overloaded(&self) -> bool {
+        self.max_in_flight.is_some_and(|max| self.in_flight() > max)
+            || self
+                .max_p95
+                .is_some_and(|max| self.p95().is_some_and(|p95| p95 > max))
+    }
+
+    /// Checks if a handler with the priority must be rejected.
+    pub(crate) fn should_shed(&self, priority: i32) -> bool {
+        priority < self.min_priority && self.is_overloaded()
+    }
+
+    /// Counts a handler in flight, until the guard is dropped.
+    pub(crate) fn start(&self) -> InFlight {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+
+        InFlight {
+            shedder: self.clone(),
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Replies the busy text to the rejected update, if set.
+    pub(crate) async fn reject(&self, update: &Update) {
+        let Some(text) = self.busy_reply.as_deref() else {
+            return;
+        };
+
+        let chat_id = match update {
+            Update::NewMessage(message) => message.chat().id(),
+            Update::CallbackQuery(query) => query.chat().id(),
+            _ => return,
+        };
+        if !self.should_reply(chat_id, Instant::now()) {
+            return;
+        }
+
+        let result = match update {
+            Update::NewMessage(message) => message.reply(text).await.map(|_| ()),
+            Update::CallbackQuery(query) => query.answer().alert(text).send().await,
+            _ => Ok(()),
+        };
+
+        if let Err(e) = result {
+            log::error!("Error replying the rejected update: {:?}", e);
+        }
+    }
+
+    /// Checks if the chat wasn't replied the busy text in the window, marking it
+    /// as replied if so.
+    fn should_reply(&self, chat_id: i64, now: Instant) -> bool {
+        let mut replied = self.replied.lock().expect("Failed to lock the replies");
+        replied.retain(|_, replied_at| now.duration_since(*replied_at) < BUSY_REPLY_WINDOW);
+
+        if replied.contains_key(&chat_id) {
+            return false;
+        }
+        replied.insert(chat_id, now);
+
+        true
+    }
+
+    /// Records the latency of a finished handler.
+    fn record(&self, latency: Duration) {
+        let mut latencies = self.latencies.lock().expect("Failed to lock the latencies");
+        if latencies.len() == LATENCY_SAMPLES {
+            latencies.pop_front();
+        }
+        latencies.push_back((Instant::now(), latency));
+    }
+}
+
+impl Default for LoadShedder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A handler in flight, counted by the [`LoadShedder`] until dropped.
+pub(crate) struct InFlight {
+    /// The shedder counting the handler.
+    shedder: LoadShedder,
+    /// When the handler started.
+    started_at: Instant,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.shedder.in_flight.fetch_sub(1, Ordering::Relaxed);
+        self.shedder.record(self.started_at.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_flight() {
+        let shedder = LoadShedder::new().max_in_flight(2);
+
+        let first = shedder.start();
+        let second = shedder.start();
+        assert_eq!(shedder.in_flight(), 2);
+        assert!(!shedder.is_overloaded());
+
+        let third = shedder.start();
+        assert!(shedder.is_overloaded());
+        assert!(shedder.should_shed(-1));
+        assert!(!shedder.should_shed(0));
+
+        drop(third);
+        assert!(!shedder.is_overloaded());
+        drop((first, second));
+        assert_eq!(shedder.in_flight(), 0);
+    }
+
+    #[test]
+    fn test_p95() {
+        let shedder = LoadShedder::new().max_p95(Duration::from_millis(95));
+
+        for ms in 1..MIN_LATENCY_SAMPLES as u64 {
+            shedder.record(Duration::from_millis(ms * 100));
+        }
+        assert_eq!(shedder.p95(), None);
+        assert!(!shedder.is_overloaded());
+
+        shedder.latencies.lock().unwrap().clear();
+        for ms in 1..=100 {
+            shedder.record(Duration::from_millis(ms));
+        }
+        assert_eq!(shedder.p95(), Some(Duration::from_millis(95)));
+        assert!(!shedder.is_overloaded());
+
+        shedder.record(Duration::from_millis(200));
+        assert_eq!(shedder.p95(), Some(Duration::from_millis(96)));
+        assert!(shedder.is_overloaded());
+    }
+
+    #[test]
+    fn test_p95_window() {
+        let shedder = LoadShedder::new();
+
+        for _ in 0..MIN_LATENCY_SAMPLES {
+            shedder.record(Duration::from_secs(1));
+        }
+        assert_eq!(shedder.p95(), Some(Duration::from_secs(1)));
+
+        if let Some(old) = Instant::now().checked_sub(LATENCY_WINDOW * 2) {
+            shedder
+                .latencies
+                .lock()
+                .unwrap()
+                .iter_mut()
+                .for_each(|(finished_at, _)| *finished_at = old);
+            assert_eq!(shedder.p95(), None);
+        }
+    }
+
+    #[test]
+    fn test_should_reply() {
+        let shedder = LoadShedder::new();
+        let now = Instant::now();
+
+        assert!(shedder.should_reply(1, now));
+        assert!(!shedder.should_reply(1, now + Duration::from_secs(1)));
+        assert!(shedder.should_reply(2, now + Duration::from_secs(1)));
+        assert!(shedder.should_reply(1, now + BUSY_REPLY_WINDOW));
+        assert_eq!(shedder.replied.lock().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,191 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Slow mode module.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures_util::Future;
+use grammers_client::InvocationError;
+
+/// How many times a send is retried after a `SLOWMODE_WAIT` error.
+const MAX_RETRIES: usize = 3;
+
+/// Per-chat send queue which respects the supergroups' slow mode.
+///
+/// The slow mode interval of a chat is learned from the `SLOWMODE_WAIT` errors,
+/// and forgotten once a message is sent without waiting. Only the chats with a
+/// known interval are queued, the others are sent at once.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SlowMode {
+    /// The state of the chats with a known interval.
+    chats: Arc<Mutex<HashMap<i64, Arc<tokio::sync::Mutex<ChatState>>>>>,
+}
+
+/// The slow mode state of a chat.
+#[derive(Debug, Default)]
+struct ChatState {
+    /// The slow mode interval, if any.
+    interval: Option<Duration>,
+    /// When the next message can be sent.
+    next_send: Option<Instant>,
+}
+
+impl ChatState {
+    /// Checks if the chat is waiting for the next message.
+    fn is_waiting(&self) -> bool {
+        self.next_send
+            .is_some_and(|next_send| next_send > Instant::now())
+    }
+
+    /// Waits `wait` before the next message, learning the interval from it.
+    fn wait(&mut self, wait: Duration) {
+        self.interval = Some(self.interval.map_or(wait, |interval| interval.max(wait)));
+        self.next_send = Some(Instant::now() + wait);
+    }
+}
+
+impl SlowMode {
+    /// Runs `send` in the queue of the chat.
+    ///
+    /// Waits until the chat accepts a new message, and retries `send` after
+    /// the requested time when a `SLOWMODE_WAIT` error is returned, up to
+    /// [`MAX_RETRIES`] times.
+    pub(crate) async fn send<T, S, F>(
+        &self,
+        chat_id: i64,
+        mut send: S,
+    ) -> Result<T, InvocationError>
+    where
+        S: FnMut() -> F,
+        F: Future<Output = Result<T, InvocationError>>,
+    {
+        let mut retries = 0;
+        let mut wait = None;
+
+        let state = {
+            let mut chats = self.chats.lock().expect("Failed to lock the chats");
+            chats.retain(|_, state| {
+                Arc::strong_count(state) > 1
+                    || state.try_lock().map_or(true, |state| state.is_waiting())
+            });
+
+            chats.get(&chat_id).cloned()
+        };
+        let state = match state {
+            Some(state) => state,
+            None => match send().await {
+                Err(InvocationError::Rpc(e))
+                    if e.name == "SLOWMODE_WAIT" && retries < MAX_RETRIES =>
+                {
+                    wait = Some(slow_mode_wait(chat_id, e.value));
+                    retries += 1;
+
+                    let mut chats = self.chats.lock().expect("Failed to lock the chats");
+                    chats.entry(chat_id).or_default().clone()
+                }
+                result => return result,
+            },
+        };
+        let mut state = state.lock().await;
+        if let Some(wait) = wait {
+            state.wait(wait);
+        }
+
+        let mut waited = false;
+
+        loop {
+            if state.is_waiting() {
+                waited = true;
+            }
+            if let Some(next_send) = state.next_send.take() {
+                tokio::time::sleep_until(next_send.into()).await;
+            }
+
+            match send().await {
+                Err(InvocationError::Rpc(e))
+                    if e.name == "SLOWMODE_WAIT" && retries < MAX_RETRIES =>
+                {
+                    state.wait(slow_mode_wait(chat_id, e.value));
+                    retries += 1;
+                }
+                result => {
+                    if result.is_ok() {
+                        if !waited {
+                            state.interval = None;
+                        }
+                        state.next_send = state.interval.map(|interval| Instant::now() + interval);
+                    }
+
+                    return result;
+                }
+            }
+        }
+    }
+}
+
+/// Returns the wait requested by a `SLOWMODE_WAIT` error.
+fn slow_mode_wait(chat_id: i64, seconds: Option<u32>) -> Duration {
+    let wait = Duration::from_secs(seconds.unwrap_or(1) as u64);
+    log::debug!("Slow mode in chat {}, waiting {:?}", chat_id, wait);
+
+    wait
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use grammers_client::RpcError;
+
+    use super::*;
+
+    fn slow_mode_error() -> InvocationError {
+        InvocationError::Rpc(RpcError {
+            code: 420,
+            name: String::from("SLOWMODE_WAIT"),
+            value: Some(0),
+            caused_by: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_send_without_slow_mode() {
+        let slow_mode = SlowMode::default();
+
+        let result = slow_mode.send(1, || async { Ok(1) }).await;
+        assert_eq!(result.unwrap(), 1);
+        assert!(slow_mode.chats.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_send_with_slow_mode() {
+        let slow_mode = SlowMode::default();
+        let attempts = AtomicUsize::new(0);
+
+        let result = slow_mode
+            .send(1, || async {
+                match attempts.fetch_add(1, Ordering::Relaxed) {
+                    0 => Err(slow_mode_error()),
+                    attempt => Ok(attempt),
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 1);
+        assert!(slow_mode.chats.lock().unwrap().contains_key(&1));
+
+        let result = slow_mode
+            .send(1, || async { Err::<(), _>(slow_mode_error()) })
+            .await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Storage module.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+
+use crate::Result;
+
+/// A key-value storage, used by the services which persist data.
+///
+/// Implement it to keep the data in a database.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use ferogram::{MemoryStorage, Storage};
+///
+/// let storage = MemoryStorage::default();
+/// storage.set("key", String::from("value")).await?;
+///
+/// assert_eq!(storage.get("key").await?, Some(String::from("value")));
+/// # }
+/// ```
+#[async_trait]
+pub trait Storage: Send + Sync + 'static {
+    /// Gets the value of the key.
+    async fn get(&self, key: &str) -> Result<Option<String>>;
+
+    /// Sets the value of the key.
+    async fn set(&self, key: &str, value: String) -> Result<()>;
+
+    /// Removes the key.
+    async fn remove(&self, key: &str) -> Result<()>;
+}
+
+/// A storage which keeps the data in memory.
+///
+/// The data is lost when the process exits.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStorage {
+    /// The values of the keys.
+    values: Arc<Mutex<HashMap<String, String>>>,
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        let values = self.values.lock().expect("Failed to lock the values");
+
+        Ok(values.get(key).cloned())
+    }
+
+    async fn set(&self, key: &str, value: String) -> Result<()> {
+        let mut values = self.values.lock().expect("Failed to lock the values");
+        values.insert(key.to_string(), value);
+
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        let mut values = self.values.lock().expect("Failed to lock the values");
+        values.remove(key);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Update summary module.
+
+use std::{fmt, sync::Arc};
+
+use grammers_client::Update;
+
+/// The max chars of the text kept in the summary.
+const MAX_TEXT_LEN: usize = 64;
+
+/// Formats the updates in the error logs, see [`crate::Builder::update_formatter`].
+pub type UpdateFormatter = Arc<dyn Fn(&Update) -> String + Send + Sync>;
+
+/// A compact summary of an update, used in the error logs.
+///
+/// Displayed as `kind=NewMessage chat=123 sender=456 text="Hello"`, with the text
+/// or the callback data trimmed.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// # let update = unimplemented!();
+/// use ferogram::UpdateSummary;
+///
+/// let summary = UpdateSummary::new(&update);
+/// log::info!("Received {}", summary);
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpdateSummary {
+    /// The kind of the update.
+    pub kind: &'static str,
+    /// The ID of the chat, if any.
+    pub chat_id: Option<i64>,
+    /// The ID of the sender, if any.
+    pub sender_id: Option<i64>,
+    /// The text, query or callback data, trimmed.
+    pub text: Option<String>,
+}
+
+impl UpdateSummary {
+    /// Summarizes the update.
+    pub fn new(update: &Update) -> Self {
+        let (kind, chat_id, sender_id, text) = match update {
+            Update::NewMessage(message) | Update::MessageEdited(message) => (
+                if matches!(update, Update::NewMessage(_)) {
+                    "NewMessage"
+                } else {
+                    "MessageEdited"
+                },
+                Some(message.chat().id()),
+                message.sender().map(|sender| sender.id()),
+                Some(message.text().to_string()),
+            ),
+            Update::MessageDeleted(deletion) => {
+                ("MessageDeleted", deletion.channel_id(), None, None)
+            }
+            Update::CallbackQuery(query) => (
+                "CallbackQuery",
+                Some(query.chat().id()),
+                Some(query.sender().id()),
+                Some(String::from_utf8_lossy(query.data()).into_owned()),
+            ),
+            Update::InlineQuery(query) => (
+                "InlineQuery",
+                None,
+                Some(query.sender().id()),
+                Some(query.text().to_string()),
+            ),
+            Update::InlineSend(inline_send) => (
+                "InlineSend",
+                None,
+                Some(inline_send.sender().id()),
+                Some(inline_send.text().to_string()),
+            ),
+            Update::Raw(_) => ("Raw", None, None, None),
+            _ => ("Unknown", None, None, None),
+        };
+
+        Self {
+            kind,
+            chat_id,
+            sender_id,
+            text: text.filter(|text| !text.is_empty()).map(|text| trim(&text)),
+        }
+    }
+
+    /// Returns the default formatter, displaying the summary.
+    pub fn formatter() -> UpdateFormatter {
+        Arc::new(|update| Self::new(update).to_string())
+    }
+}
+
+impl fmt::Display for UpdateSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "kind={}", self.kind)?;
+
+        if let Some(chat_id) = self.chat_id {
+            write!(f, " chat={}", chat_id)?;
+        }
+        if let Some(sender_id) = self.sender_id {
+            write!(f, " sender={}", sender_id)?;
+        }
+        if let Some(text) = self.text.as_ref() {
+            write!(f, " text={:?}", text)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Trims the text to [`MAX_TEXT_LEN`] chars.
+fn trim(text: &str) -> String {
+    match text.char_indices().nth(MAX_TEXT_LEN) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
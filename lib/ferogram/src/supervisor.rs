@@ -0,0 +1,111 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Supervisor module.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures_util::Future;
+use tokio::task::JoinHandle;
+
+/// The first delay before restarting a task.
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+/// The max delay before restarting a task.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A supervised task, with its name.
+type Task = (String, JoinHandle<()>);
+
+/// A background task supervisor.
+///
+/// Restarts the tasks when they panic or exit, and aborts them on shutdown.
+#[derive(Clone, Default)]
+pub struct Supervisor {
+    /// The supervised tasks.
+    tasks: Arc<Mutex<Vec<Task>>>,
+}
+
+impl Supervisor {
+    /// Spawns a supervised task.
+    ///
+    /// `task` is called again to restart the task, waiting between `1` and `60`
+    /// seconds, doubling the delay each time it stops in less than a minute.
+    pub fn spawn<T, F>(&self, name: &str, task: T)
+    where
+        T: Fn() -> F + Send + Sync + 'static,
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let task_name = name.to_string();
+
+        let handle = tokio::task::spawn(async move {
+            let mut backoff = MIN_BACKOFF;
+
+            loop {
+                let started_at = Instant::now();
+
+                let mut running = AbortOnDrop(tokio::task::spawn(task()));
+
+                match (&mut running.0).await {
+                    Ok(()) => log::warn!("Task {:?} exited, restarting", task_name),
+                    Err(e) if e.is_panic() => {
+                        log::error!("Task {:?} panicked, restarting", task_name)
+                    }
+                    Err(_) => break,
+                }
+
+                if started_at.elapsed() > MAX_BACKOFF {
+                    backoff = MIN_BACKOFF;
+                }
+
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        });
+
+        self.tasks
+            .lock()
+            .expect("Failed to lock the tasks")
+            .push((name.to_string(), handle));
+    }
+
+    /// Returns the names of the running tasks.
+    pub fn tasks(&self) -> Vec<String> {
+        self.tasks
+            .lock()
+            .expect("Failed to lock the tasks")
+            .iter()
+            .filter(|(_, handle)| !handle.is_finished())
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Aborts all the tasks.
+    pub fn shutdown(&self) {
+        for (name, handle) in self
+            .tasks
+            .lock()
+            .expect("Failed to lock the tasks")
+            .drain(..)
+        {
+            log::debug!("Stopping task {:?}", name);
+            handle.abort();
+        }
+    }
+}
+
+/// Aborts the task when dropped, so aborting the supervisor also stops it.
+struct AbortOnDrop(JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
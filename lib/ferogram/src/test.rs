@@ -0,0 +1,77 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Test utilities module.
+//!
+//! Helpers to test filters against recorded updates.
+
+use grammers_client::{Client, Update};
+
+pub use crate::assert_filter;
+use crate::{Filter, Flow};
+
+/// Runs the filter against each update and returns the flows, in the same order.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// # let client = unimplemented!();
+/// # let updates: Vec<grammers_client::Update> = unimplemented!();
+/// use ferogram::{filter, test};
+///
+/// let flows = test::check_filter(filter::has_photo, &client, &updates).await;
+/// # }
+/// ```
+pub async fn check_filter<F: Filter>(
+    mut filter: F,
+    client: &Client,
+    updates: &[Update],
+) -> Vec<Flow> {
+    let mut flows = Vec::with_capacity(updates.len());
+
+    for update in updates {
+        flows.push(filter.check(client, update).await);
+    }
+
+    flows
+}
+
+/// Asserts that a filter passes or fails for an update, and the types it injects.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// # let client = unimplemented!();
+/// # let (photo_update, text_update): (grammers_client::Update, grammers_client::Update) = unimplemented!();
+/// use ferogram::{filter, test::assert_filter};
+/// use grammers_client::types::Photo;
+///
+/// assert_filter!(filter::has_photo, &client, &photo_update, pass, [Photo]);
+/// assert_filter!(filter::has_photo, &client, &text_update, fail);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! assert_filter {
+    ($filter:expr, $client:expr, $update:expr, pass $(, [$($ty:ty),* $(,)?])?) => {{
+        let flow = $crate::Filter::check(&mut $filter, $client, $update).await;
+        assert!(flow.is_continue(), "Filter didn't pass");
+        $($(
+            assert!(
+                flow.injected::<$ty>().is_some(),
+                "Filter didn't inject `{}`",
+                stringify!($ty)
+            );
+        )*)?
+    }};
+    ($filter:expr, $client:expr, $update:expr, fail) => {{
+        let flow = $crate::Filter::check(&mut $filter, $client, $update).await;
+        assert!(!flow.is_continue(), "Filter passed");
+    }};
+}
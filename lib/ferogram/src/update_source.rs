@@ -0,0 +1,75 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Update source module.
+
+use async_trait::async_trait;
+use grammers_client::Update;
+use tokio::sync::mpsc;
+
+use crate::Result;
+
+/// A source of updates for the dispatcher.
+///
+/// [`crate::Client::run`] pulls the updates from the source and sends them to the
+/// dispatcher, so the same routers can be fed by the MTProto connection, a Bot API
+/// poller, a webhook server, a replay file or the tests.
+///
+/// By default, the updates come from the inner grammers' `Client`. A channel receiver
+/// is also a source, handy to bridge webhooks or to feed the tests.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use async_trait::async_trait;
+/// use ferogram::{Result, UpdateSource};
+/// use grammers_client::Update;
+///
+/// struct Replay(Vec<Update>);
+///
+/// #[async_trait]
+/// impl UpdateSource for Replay {
+///     async fn next_update(&mut self) -> Result<Option<Update>> {
+///         Ok(self.0.pop())
+///     }
+/// }
+/// # }
+/// ```
+#[async_trait]
+pub trait UpdateSource: Send + 'static {
+    /// Waits for the next update.
+    ///
+    /// Returns `Ok(None)` once the source is exhausted, stopping the listener.
+    /// The errors are logged, and the listener keeps pulling the updates.
+    async fn next_update(&mut self) -> Result<Option<Update>>;
+}
+
+#[async_trait]
+impl UpdateSource for grammers_client::Client {
+    async fn next_update(&mut self) -> Result<Option<Update>> {
+        grammers_client::Client::next_update(self)
+            .await
+            .map(Some)
+            .map_err(Into::into)
+    }
+}
+
+#[async_trait]
+impl UpdateSource for mpsc::Receiver<Update> {
+    async fn next_update(&mut self) -> Result<Option<Update>> {
+        Ok(self.recv().await)
+    }
+}
+
+#[async_trait]
+impl UpdateSource for mpsc::UnboundedReceiver<Update> {
+    async fn next_update(&mut self) -> Result<Option<Update>> {
+        Ok(self.recv().await)
+    }
+}
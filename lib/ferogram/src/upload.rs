@@ -0,0 +1,271 @@
+// Copyright 2024-2025 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Upload module.
+
+use std::{io::ErrorKind, sync::Arc};
+
+use grammers_client::{
+    grammers_tl_types as tl,
+    types::{media::Uploaded, Downloadable},
+    Client, InvocationError,
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    sync::Semaphore,
+    task::JoinSet,
+};
+
+use crate::utils::random_u64;
+
+/// The max part size accepted by Telegram.
+const MAX_PART_SIZE: usize = 512 * 1024;
+/// Files bigger than this are uploaded as big files.
+const BIG_FILE_SIZE: usize = 10 * 1024 * 1024;
+
+/// A progress callback, called with the uploaded and the total bytes.
+pub type ProgressCallback = Arc<dyn Fn(usize, usize) + Send + Sync>;
+
+/// Upload options.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// # let ctx: ferogram::Context = unimplemented!();
+/// use ferogram::UploadOptions;
+///
+/// let options = UploadOptions::default()
+///     .workers(8)
+///     .progress(|uploaded, total| println!("{}/{}", uploaded, total));
+/// let file = ctx.upload_file_with("path/to/file", options).await?;
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct UploadOptions {
+    /// The size of each part.
+    part_size: usize,
+    /// How many parts are uploaded in parallel.
+    workers: usize,
+    /// How many times a failed part is retried.
+    retries: usize,
+    /// The progress callback.
+    progress: Option<ProgressCallback>,
+}
+
+impl UploadOptions {
+    /// Sets the size of each part, in bytes.
+    ///
+    /// Must be divisible by `1024` and divide `512 KiB`, the default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the part size is not accepted by Telegram.
+    pub fn part_size(mut self, part_size: usize) -> Self {
+        assert!(
+            part_size > 0
+                && part_size.is_multiple_of(1024)
+                && MAX_PART_SIZE.is_multiple_of(part_size),
+            "Invalid part size"
+        );
+
+        self.part_size = part_size;
+        self
+    }
+
+    /// Sets how many parts are uploaded in parallel, `4` by default.
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// Sets how many times a failed part is retried, `3` by default.
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Sets the progress callback.
+    ///
+    /// Called with the uploaded and the total bytes after each part.
+    pub fn progress<F: Fn(usize, usize) + Send + Sync + 'static>(mut self, progress: F) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+}
+
+impl Default for UploadOptions {
+    fn default() -> Self {
+        Self {
+            part_size: MAX_PART_SIZE,
+            workers: 4,
+            retries: 3,
+            progress: None,
+        }
+    }
+}
+
+/// Uploads a stream with `size` bytes, in parts.
+pub(crate) async fn upload_stream<S: AsyncRead + Unpin>(
+    client: &Client,
+    stream: &mut S,
+    size: usize,
+    name: String,
+    options: UploadOptions,
+) -> Result<Uploaded, InvocationError> {
+    let file_id = random_u64() as i64;
+    let is_big = size > BIG_FILE_SIZE;
+    let total_parts = size.div_ceil(options.part_size).max(1);
+
+    let semaphore = Arc::new(Semaphore::new(options.workers));
+    let mut tasks = JoinSet::new();
+    let mut uploaded = 0;
+
+    for part in 0..total_parts {
+        let part_size = options.part_size.min(size - part * options.part_size);
+        let mut bytes = vec![0; part_size];
+        stream.read_exact(&mut bytes).await?;
+
+        let permit = semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("Failed to acquire permit");
+        let client = client.clone();
+        let retries = options.retries;
+
+        tasks.spawn(async move {
+            let _permit = permit;
+
+            upload_part(&client, file_id, part, total_parts, is_big, bytes, retries).await
+        });
+
+        while let Some(result) = tasks.try_join_next() {
+            uploaded += result.expect("Failed to join the upload task")?;
+            report(&options, uploaded, size);
+        }
+    }
+
+    while let Some(result) = tasks.join_next().await {
+        uploaded += result.expect("Failed to join the upload task")?;
+        report(&options, uploaded, size);
+    }
+
+    let raw = if is_big {
+        tl::enums::InputFile::Big(tl::types::InputFileBig {
+            id: file_id,
+            parts: total_parts as i32,
+            name,
+        })
+    } else {
+        tl::enums::InputFile::File(tl::types::InputFile {
+            id: file_id,
+            parts: total_parts as i32,
+            name,
+            md5_checksum: String::new(),
+        })
+    };
+
+    Ok(Uploaded { raw })
+}
+
+/// Downloads the media with `size` bytes and uploads it again, in chunks.
+///
+/// The downloaded chunks are piped to the upload, without buffering the entire file.
+pub(crate) async fn reupload<D: Downloadable>(
+    client: &Client,
+    downloadable: &D,
+    size: usize,
+    name: String,
+    options: UploadOptions,
+) -> Result<Uploaded, InvocationError> {
+    let (mut reader, mut writer) = tokio::io::duplex(options.part_size * options.workers);
+    let mut download = client
+        .iter_download(downloadable)
+        .chunk_size(MAX_PART_SIZE as i32);
+
+    let downloader = tokio::task::spawn(async move {
+        while let Some(chunk) = download.next().await? {
+            writer.write_all(&chunk).await?;
+        }
+
+        Ok::<(), InvocationError>(())
+    });
+
+    let result = upload_stream(client, &mut reader, size, name, options).await;
+    drop(reader);
+
+    // The download error is the cause when the upload stops with an unexpected end.
+    match (
+        result,
+        downloader.await.expect("Failed to join the download task"),
+    ) {
+        (Ok(uploaded), _) => Ok(uploaded),
+        (Err(e), Ok(())) => Err(e),
+        (Err(e), Err(InvocationError::Io(io))) if io.kind() == ErrorKind::BrokenPipe => Err(e),
+        (Err(_), Err(e)) => Err(e),
+    }
+}
+
+/// Uploads a part, retrying up to `retries` times.
+///
+/// Returns the size of the part.
+async fn upload_part(
+    client: &Client,
+    file_id: i64,
+    part: usize,
+    total_parts: usize,
+    is_big: bool,
+    bytes: Vec<u8>,
+    retries: usize,
+) -> Result<usize, InvocationError> {
+    let size = bytes.len();
+    let mut attempt = 0;
+
+    loop {
+        let result = if is_big {
+            client
+                .invoke(&tl::functions::upload::SaveBigFilePart {
+                    file_id,
+                    file_part: part as i32,
+                    file_total_parts: total_parts as i32,
+                    bytes: bytes.clone(),
+                })
+                .await
+        } else {
+            client
+                .invoke(&tl::functions::upload::SaveFilePart {
+                    file_id,
+                    file_part: part as i32,
+                    bytes: bytes.clone(),
+                })
+                .await
+        };
+
+        match result {
+            Ok(true) => return Ok(size),
+            Ok(false) if attempt < retries => {}
+            Ok(false) => {
+                return Err(std::io::Error::other(format!("Failed to upload part {}", part)).into())
+            }
+            Err(e) if attempt < retries => {
+                log::warn!("Failed to upload part {}, retrying: {:?}", part, e);
+            }
+            Err(e) => return Err(e),
+        }
+
+        attempt += 1;
+    }
+}
+
+/// Calls the progress callback, if any.
+fn report(options: &UploadOptions, uploaded: usize, total: usize) {
+    if let Some(progress) = options.progress.as_ref() {
+        progress(uploaded, total);
+    }
+}
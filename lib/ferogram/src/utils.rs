@@ -8,12 +8,28 @@
 
 //! Utils module.
 
-use std::io::{BufRead, Write};
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    future::Future,
+    hash::{BuildHasher, Hash, Hasher},
+    io::{BufRead, Write},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
 
-use grammers_client::button::Inline;
+use futures_util::future::BoxFuture;
+use grammers_client::{
+    button::Inline, grammers_tl_types as tl, types::Chat, Client, InvocationError,
+};
+use tokio::sync::OwnedMutexGuard;
 
 use crate::Result;
 
+/// Returns a random number, used for the random IDs of the requests.
+pub(crate) fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
 /// Ask the user in the terminal.
 ///
 /// # Example
@@ -118,3 +134,1257 @@ pub fn split_btns_into_rows(buttons: Vec<Inline>, row_count: usize) -> Vec<Vec<I
     let per_column = buttons.len().abs_diff(row_count);
     split_btns_into_columns(buttons, per_column)
 }
+
+/// Returns the length of the text in UTF-16 code units, the unit used by Telegram.
+///
+/// # Example
+///
+/// ```
+/// use ferogram::utils::utf16_len;
+///
+/// assert_eq!(utf16_len("hi 👋"), 5);
+/// ```
+pub fn utf16_len(text: &str) -> usize {
+    text.chars().map(char::len_utf16).sum()
+}
+
+/// Converts an UTF-16 offset, like the ones of the message entities, to a char index.
+///
+/// Returns `None` if the offset is out of bounds or splits a surrogate pair.
+///
+/// # Example
+///
+/// ```
+/// use ferogram::utils::utf16_to_char_offset;
+///
+/// assert_eq!(utf16_to_char_offset("👋 hi", 3), Some(2));
+/// assert_eq!(utf16_to_char_offset("👋 hi", 1), None);
+/// ```
+pub fn utf16_to_char_offset(text: &str, offset: usize) -> Option<usize> {
+    let mut utf16_offset = 0;
+
+    for (index, char) in text.chars().enumerate() {
+        if utf16_offset >= offset {
+            return (utf16_offset == offset).then_some(index);
+        }
+        utf16_offset += char.len_utf16();
+    }
+
+    (utf16_offset == offset).then(|| text.chars().count())
+}
+
+/// Converts a char index to an UTF-16 offset, the unit used by Telegram.
+///
+/// Returns `None` if the index is out of bounds.
+///
+/// # Example
+///
+/// ```
+/// use ferogram::utils::char_to_utf16_offset;
+///
+/// assert_eq!(char_to_utf16_offset("👋 hi", 2), Some(3));
+/// ```
+pub fn char_to_utf16_offset(text: &str, index: usize) -> Option<usize> {
+    let mut chars = text.chars();
+    let mut utf16_offset = 0;
+
+    for _ in 0..index {
+        utf16_offset += chars.next()?.len_utf16();
+    }
+
+    Some(utf16_offset)
+}
+
+/// Converts an UTF-16 offset to a byte index.
+///
+/// Returns `None` if the offset is out of bounds or splits a surrogate pair.
+fn utf16_to_byte_offset(text: &str, offset: usize) -> Option<usize> {
+    let mut utf16_offset = 0;
+
+    for (index, char) in text.char_indices() {
+        if utf16_offset >= offset {
+            return (utf16_offset == offset).then_some(index);
+        }
+        utf16_offset += char.len_utf16();
+    }
+
+    (utf16_offset == offset).then_some(text.len())
+}
+
+/// Slices the text with an UTF-16 offset and length, like the ones of the message entities.
+///
+/// Unlike slicing the chars, it's correct for texts with emojis and other chars
+/// outside the BMP, which take two UTF-16 code units.
+///
+/// Returns `None` if the range is out of bounds or splits a surrogate pair.
+///
+/// # Example
+///
+/// ```
+/// use ferogram::utils::utf16_slice;
+///
+/// let text = "👋 https://example.com";
+/// assert_eq!(utf16_slice(text, 3, 19), Some("https://example.com"));
+/// ```
+pub fn utf16_slice(text: &str, offset: usize, length: usize) -> Option<&str> {
+    let start = utf16_to_byte_offset(text, offset)?;
+    let end = start + utf16_to_byte_offset(&text[start..], length)?;
+
+    Some(&text[start..end])
+}
+
+/// A keyed mutex, which serializes the critical sections of each key.
+///
+/// The mutex of a key is created when locked, and dropped once nobody holds it.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use ferogram::utils::KeyedLock;
+///
+/// let locks = KeyedLock::<String>::default();
+/// let _guard = locks.lock(String::from("report")).await;
+/// // Only one task runs this part for the key at a time.
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct KeyedLock<K> {
+    /// The mutex of each key.
+    locks: Arc<Mutex<HashMap<K, Arc<tokio::sync::Mutex<()>>>>>,
+}
+
+impl<K: Eq + Hash> KeyedLock<K> {
+    /// Locks the key, waiting until the other holders release it.
+    ///
+    /// The key is released when the guard is dropped.
+    pub async fn lock(&self, key: K) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.locks.lock().expect("Failed to lock the keys");
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+
+            locks.entry(key).or_default().clone()
+        };
+
+        lock.lock_owned().await
+    }
+}
+
+impl<K> Clone for KeyedLock<K> {
+    fn clone(&self) -> Self {
+        Self {
+            locks: self.locks.clone(),
+        }
+    }
+}
+
+impl<K> Default for KeyedLock<K> {
+    fn default() -> Self {
+        Self {
+            locks: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+/// A keyed mutex, which serializes the critical sections of each chat.
+///
+/// The dispatcher handles the updates concurrently, so handlers which mutate
+/// per-chat state can lock the chat to not race. Injected in the handlers.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use ferogram::utils::ChatLock;
+///
+/// # let (locks, chat_id): (ChatLock, i64) = unimplemented!();
+/// let _guard = locks.lock(chat_id).await;
+/// // Only one handler runs this part for the chat at a time.
+/// # }
+/// ```
+pub type ChatLock = KeyedLock<i64>;
+
+/// Coalesces rapid successive calls into at most one action per interval.
+///
+/// The first call runs at once, the calls made during the interval replace each
+/// other and only the last one runs when the interval ends. Useful to edit progress
+/// messages or answer live searches without flooding Telegram.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// use std::time::Duration;
+///
+/// use ferogram::utils::Debouncer;
+///
+/// let debouncer = Debouncer::default();
+/// for progress in 0..=100 {
+///     debouncer.call("progress", Duration::from_secs(1), async move {
+///         println!("{}%", progress);
+///     });
+/// }
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct Debouncer {
+    /// The state of each key.
+    keys: Arc<Mutex<HashMap<String, DebounceState>>>,
+}
+
+/// The debounce state of a key.
+struct DebounceState {
+    /// When the next action can run.
+    next_run: Instant,
+    /// The last action called during the interval, if any.
+    pending: Option<BoxFuture<'static, ()>>,
+}
+
+impl std::fmt::Debug for Debouncer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Debouncer").finish_non_exhaustive()
+    }
+}
+
+impl Debouncer {
+    /// Runs the action of the key, at most once per `interval`.
+    ///
+    /// The action is spawned, so it must not borrow anything.
+    pub fn call<K: ToString, F>(&self, key: K, interval: Duration, action: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let key = key.to_string();
+        let now = Instant::now();
+
+        let mut keys = self.keys.lock().expect("Failed to lock the keys");
+        keys.retain(|_, state| state.pending.is_some() || state.next_run > now);
+
+        match keys.get_mut(&key) {
+            Some(state) if state.pending.is_some() => {
+                state.pending = Some(Box::pin(action));
+            }
+            Some(state) => {
+                state.pending = Some(Box::pin(action));
+
+                let keys = self.keys.clone();
+                let run_at = state.next_run;
+                tokio::spawn(async move {
+                    tokio::time::sleep_until(run_at.into()).await;
+
+                    let action = keys
+                        .lock()
+                        .expect("Failed to lock the keys")
+                        .get_mut(&key)
+                        .and_then(|state| {
+                            state.next_run = Instant::now() + interval;
+                            state.pending.take()
+                        });
+                    if let Some(action) = action {
+                        action.await;
+                    }
+                });
+            }
+            None => {
+                keys.insert(
+                    key,
+                    DebounceState {
+                        next_run: now + interval,
+                        pending: None,
+                    },
+                );
+                tokio::spawn(action);
+            }
+        }
+    }
+}
+
+/// The language of the humanized texts.
+///
+/// # Example
+///
+/// ```
+/// use ferogram::utils::Locale;
+///
+/// assert_eq!(Locale::from_lang_code("pt-br"), Locale::Portuguese);
+/// assert_eq!(Locale::from_lang_code("fr"), Locale::English);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Locale {
+    /// English.
+    #[default]
+    English,
+    /// Portuguese.
+    Portuguese,
+}
+
+impl Locale {
+    /// Gets the locale from the language code of a user, like `user.lang_code()`.
+    ///
+    /// Falls back to English when the language is not supported.
+    pub fn from_lang_code(lang_code: &str) -> Self {
+        match lang_code.split(['-', '_']).next() {
+            Some(lang) if lang.eq_ignore_ascii_case("pt") => Self::Portuguese,
+            _ => Self::English,
+        }
+    }
+
+    /// Returns the language code of the locale.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::Portuguese => "pt",
+        }
+    }
+
+    /// Returns the singular and plural names of the unit.
+    fn unit(&self, unit: TimeUnit) -> (&'static str, &'static str) {
+        match (self, unit) {
+            (Self::English, TimeUnit::Second) => ("second", "seconds"),
+            (Self::English, TimeUnit::Minute) => ("minute", "minutes"),
+            (Self::English, TimeUnit::Hour) => ("hour", "hours"),
+            (Self::English, TimeUnit::Day) => ("day", "days"),
+            (Self::Portuguese, TimeUnit::Second) => ("segundo", "segundos"),
+            (Self::Portuguese, TimeUnit::Minute) => ("minuto", "minutos"),
+            (Self::Portuguese, TimeUnit::Hour) => ("hora", "horas"),
+            (Self::Portuguese, TimeUnit::Day) => ("dia", "dias"),
+        }
+    }
+
+    /// Formats an amount of the unit. Ex: `1 minute`, `5 minutes`.
+    fn amount(&self, amount: u64, unit: TimeUnit) -> String {
+        let (singular, plural) = self.unit(unit);
+        format!("{} {}", amount, if amount == 1 { singular } else { plural })
+    }
+}
+
+/// A unit of time.
+#[derive(Clone, Copy)]
+enum TimeUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+}
+
+impl TimeUnit {
+    /// The units from the largest, with their lengths in seconds.
+    const ALL: [(TimeUnit, u64); 4] = [
+        (TimeUnit::Day, 86400),
+        (TimeUnit::Hour, 3600),
+        (TimeUnit::Minute, 60),
+        (TimeUnit::Second, 1),
+    ];
+}
+
+/// Formats a size in bytes, using binary units.
+///
+/// # Example
+///
+/// ```
+/// use ferogram::utils::{human_bytes, Locale};
+///
+/// assert_eq!(human_bytes(512, Locale::English), "512 B");
+/// assert_eq!(human_bytes(1536, Locale::English), "1.5 KB");
+/// assert_eq!(human_bytes(1536, Locale::Portuguese), "1,5 KB");
+/// ```
+pub fn human_bytes(bytes: u64, locale: Locale) -> String {
+    const UNITS: [&str; 5] = ["KB", "MB", "GB", "TB", "PB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    let size = format!("{:.1}", size);
+    let size = size.strip_suffix(".0").unwrap_or(&size);
+    match locale {
+        Locale::English => format!("{} {}", size, UNITS[unit]),
+        Locale::Portuguese => format!("{} {}", size.replace('.', ","), UNITS[unit]),
+    }
+}
+
+/// Formats a duration with its two largest units, like ETAs and uptimes.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use ferogram::utils::{human_duration, Locale};
+///
+/// let duration = Duration::from_secs(3900);
+/// assert_eq!(human_duration(duration, Locale::English), "1 hour and 5 minutes");
+/// assert_eq!(human_duration(duration, Locale::Portuguese), "1 hora e 5 minutos");
+/// ```
+pub fn human_duration(duration: Duration, locale: Locale) -> String {
+    let mut seconds = duration.as_secs();
+    let mut parts = Vec::new();
+
+    for (unit, length) in TimeUnit::ALL {
+        if seconds >= length && parts.len() < 2 {
+            parts.push(locale.amount(seconds / length, unit));
+            seconds %= length;
+        } else if !parts.is_empty() {
+            break;
+        }
+    }
+
+    match parts.as_slice() {
+        [] => locale.amount(0, TimeUnit::Second),
+        [part] => part.clone(),
+        [first, second, ..] => match locale {
+            Locale::English => format!("{} and {}", first, second),
+            Locale::Portuguese => format!("{} e {}", first, second),
+        },
+    }
+}
+
+/// Formats a timestamp relative to now, with its largest unit.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, SystemTime};
+///
+/// use ferogram::utils::{human_time, Locale};
+///
+/// let time = SystemTime::now() - Duration::from_secs(300);
+/// assert_eq!(human_time(time, Locale::English), "5 minutes ago");
+/// assert_eq!(human_time(time, Locale::Portuguese), "há 5 minutos");
+/// ```
+pub fn human_time(time: SystemTime, locale: Locale) -> String {
+    let (elapsed, is_past) = match SystemTime::now().duration_since(time) {
+        Ok(elapsed) => (elapsed, true),
+        Err(e) => (e.duration(), false),
+    };
+
+    let seconds = elapsed.as_secs();
+    let Some(amount) = TimeUnit::ALL
+        .into_iter()
+        .find(|(_, length)| seconds >= *length)
+        .map(|(unit, length)| locale.amount(seconds / length, unit))
+    else {
+        return match locale {
+            Locale::English => "now".to_string(),
+            Locale::Portuguese => "agora".to_string(),
+        };
+    };
+
+    match (locale, is_past) {
+        (Locale::English, true) => format!("{} ago", amount),
+        (Locale::English, false) => format!("in {}", amount),
+        (Locale::Portuguese, true) => format!("há {}", amount),
+        (Locale::Portuguese, false) => format!("em {}", amount),
+    }
+}
+
+/// Parses a duration typed by the user, in English or Portuguese.
+///
+/// Accepts amounts followed by their units, like `2h30m`, `1 day 6 hours` or
+/// `2 semanas`, and returns `None` if the text has anything else.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use ferogram::utils::parse_duration;
+///
+/// assert_eq!(parse_duration("2h30m"), Some(Duration::from_secs(9000)));
+/// assert_eq!(parse_duration("1 dia e 2 horas"), Some(Duration::from_secs(93600)));
+/// assert_eq!(parse_duration("soon"), None);
+/// ```
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim().to_lowercase();
+    let mut chars = text.chars().peekable();
+    let mut seconds = 0u64;
+    let mut parsed = false;
+
+    loop {
+        while chars
+            .next_if(|char| char.is_whitespace() || *char == ',')
+            .is_some()
+        {}
+
+        let mut word = String::new();
+        while let Some(char) = chars.next_if(|char| char.is_alphabetic()) {
+            word.push(char);
+        }
+        if !word.is_empty() {
+            // Allows the conjunctions between the amounts, like `1h and 30m`.
+            if !parsed || !matches!(word.as_str(), "and" | "e") {
+                return None;
+            }
+            continue;
+        }
+
+        let mut amount = String::new();
+        while let Some(char) = chars.next_if(char::is_ascii_digit) {
+            amount.push(char);
+        }
+        if amount.is_empty() {
+            break;
+        }
+
+        while chars.next_if(|char| char.is_whitespace()).is_some() {}
+        let mut unit = String::new();
+        while let Some(char) = chars.next_if(|char| char.is_alphabetic()) {
+            unit.push(char);
+        }
+
+        let length = match unit.as_str() {
+            "s" | "sec" | "secs" | "second" | "seconds" | "seg" | "segundo" | "segundos" => 1,
+            "m" | "min" | "mins" | "minute" | "minutes" | "minuto" | "minutos" => 60,
+            "h" | "hr" | "hrs" | "hour" | "hours" | "hora" | "horas" => 3600,
+            "d" | "day" | "days" | "dia" | "dias" => 86400,
+            "w" | "week" | "weeks" | "semana" | "semanas" => 604800,
+            _ => return None,
+        };
+
+        seconds = seconds.checked_add(amount.parse::<u64>().ok()?.checked_mul(length)?)?;
+        parsed = true;
+    }
+
+    (parsed && chars.peek().is_none()).then(|| Duration::from_secs(seconds))
+}
+
+/// Parses a date and time typed by the user, in English or Portuguese, in the
+/// time zone.
+///
+/// Accepts a relative duration, like `in 2h` or `em 30 minutos`, or a day and a
+/// time, in any order, like `tomorrow 9am`, `amanhã às 21h`, `friday 18:30` or
+/// `2025-03-10 9:00`. A missing day is today, or tomorrow if the time passed,
+/// and a missing time is the current one.
+///
+/// Requires the `datetime` feature.
+///
+/// # Example
+///
+/// ```
+/// use chrono::FixedOffset;
+/// use ferogram::utils::parse_datetime;
+///
+/// let tz = FixedOffset::west_opt(3 * 3600).unwrap();
+/// let reminder = parse_datetime("tomorrow 9am", tz).unwrap();
+/// assert_eq!(reminder.format("%H:%M").to_string(), "09:00");
+/// ```
+#[cfg(feature = "datetime")]
+pub fn parse_datetime(
+    text: &str,
+    tz: chrono::FixedOffset,
+) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    use chrono::{Datelike, Days, NaiveDate, Utc};
+
+    let now = Utc::now().with_timezone(&tz);
+    let text = text.trim().to_lowercase();
+
+    if let Some(duration) = ["in ", "em ", "daqui a "]
+        .iter()
+        .find_map(|prefix| text.strip_prefix(prefix))
+        .or(Some(text.as_str()))
+        .and_then(parse_duration)
+    {
+        return now.checked_add_signed(chrono::Duration::from_std(duration).ok()?);
+    }
+
+    let mut date = None;
+    let mut time = None;
+
+    let words = text
+        .split_whitespace()
+        .filter(|word| !matches!(*word, "at" | "on" | "às" | "as" | "à" | "a" | "de"))
+        .collect::<Vec<_>>();
+    let mut i = 0;
+    while i < words.len() {
+        let word = words[i];
+        // Joins the meridiem typed apart, like `9 am`.
+        let word = match words.get(i + 1) {
+            Some(next)
+                if matches!(*next, "am" | "pm")
+                    && word.chars().all(|c| c.is_ascii_digit() || c == ':') =>
+            {
+                i += 1;
+                format!("{}{}", word, next)
+            }
+            _ => word.to_string(),
+        };
+        i += 1;
+
+        let weekday = [
+            ["monday", "segunda", "segunda-feira"],
+            ["tuesday", "terça", "terça-feira"],
+            ["wednesday", "quarta", "quarta-feira"],
+            ["thursday", "quinta", "quinta-feira"],
+            ["friday", "sexta", "sexta-feira"],
+            ["saturday", "sábado", "sabado"],
+            ["sunday", "domingo", "domingo"],
+        ]
+        .iter()
+        .position(|names| names.contains(&word.as_str()));
+
+        if let Some(weekday) = weekday {
+            let today = now.weekday().num_days_from_monday() as u64;
+            let days = (weekday as u64 + 7 - today) % 7;
+            date = now
+                .date_naive()
+                .checked_add_days(Days::new(if days == 0 { 7 } else { days }));
+        } else if matches!(word.as_str(), "today" | "hoje") {
+            date = Some(now.date_naive());
+        } else if matches!(word.as_str(), "tomorrow" | "amanhã" | "amanha") {
+            date = now.date_naive().checked_add_days(Days::new(1));
+        } else if let Ok(parsed) = NaiveDate::parse_from_str(&word, "%Y-%m-%d") {
+            date = Some(parsed);
+        } else if let Some(parsed) = parse_time(&word) {
+            time = Some(parsed);
+        } else {
+            return None;
+        }
+    }
+
+    if date.is_none() && time.is_none() {
+        return None;
+    }
+
+    let time = time.unwrap_or_else(|| now.time());
+    let date = match date {
+        Some(date) => date,
+        None if time <= now.time() => now.date_naive().checked_add_days(Days::new(1))?,
+        None => now.date_naive(),
+    };
+
+    date.and_time(time).and_local_timezone(tz).single()
+}
+
+/// Parses a time typed by the user, like `9am`, `9:30pm`, `21:00`, `21h` or `21h30`.
+#[cfg(feature = "datetime")]
+fn parse_time(word: &str) -> Option<chrono::NaiveTime> {
+    let (word, offset) = if let Some(word) = word.strip_suffix("am") {
+        (word, Some(0))
+    } else if let Some(word) = word.strip_suffix("pm") {
+        (word, Some(12))
+    } else {
+        (word, None)
+    };
+
+    let (hour, minute) = match word.split_once([':', 'h']) {
+        Some((hour, "")) => (hour, "0"),
+        Some((hour, minute)) => (hour, minute),
+        None if offset.is_some() => (word, "0"),
+        None => return None,
+    };
+
+    let mut hour = hour.parse::<u32>().ok()?;
+    if let Some(offset) = offset {
+        if !(1..=12).contains(&hour) {
+            return None;
+        }
+        hour = hour % 12 + offset;
+    }
+
+    chrono::NaiveTime::from_hms_opt(hour, minute.parse().ok()?, 0)
+}
+
+/// Renders a progress bar with `width` cells.
+///
+/// # Example
+///
+/// ```
+/// use ferogram::utils::progress_bar;
+///
+/// assert_eq!(progress_bar(50, 100, 10), "█████░░░░░");
+/// ```
+pub fn progress_bar(current: usize, total: usize, width: usize) -> String {
+    let filled = match total {
+        0 => width,
+        total => width * current.min(total) / total,
+    };
+
+    "█".repeat(filled) + &"░".repeat(width - filled)
+}
+
+/// Returns the edit distance between two texts, in chars.
+///
+/// # Example
+///
+/// ```
+/// use ferogram::utils::levenshtein;
+///
+/// assert_eq!(levenshtein("strat", "start"), 2);
+/// ```
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+
+    // The distances between the prefix of `a` and each prefix of `b`.
+    let mut distances = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a) in a.chars().enumerate() {
+        let mut previous = distances[0];
+        distances[0] = i + 1;
+
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous + usize::from(a != *b);
+            previous = distances[j + 1];
+            distances[j + 1] = substitution.min(distances[j] + 1).min(previous + 1);
+        }
+    }
+
+    distances[b.len()]
+}
+
+/// Reports the progress of a download or upload by editing a message.
+///
+/// The edits are debounced, so it is safe to report every chunk.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// # let ctx = unimplemented!();
+/// use ferogram::UploadOptions;
+///
+/// let reporter = ctx.progress_reporter().await.title("Uploading...");
+/// let options = UploadOptions::default().progress(move |uploaded, total| {
+///     reporter.report(uploaded, total);
+/// });
+/// let file = ctx.upload_file_with("path/to/file", options).await?;
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ProgressReporter {
+    /// Edits the progress message.
+    edit: Arc<dyn Fn(String) -> BoxFuture<'static, ()> + Send + Sync>,
+    /// The debouncer of the edits.
+    debouncer: Debouncer,
+    /// The debouncer key.
+    key: String,
+    /// The minimum interval between the edits.
+    interval: Duration,
+    /// The title shown above the bar.
+    title: Option<String>,
+    /// The width of the bar.
+    width: usize,
+    /// The locale of the sizes.
+    locale: Locale,
+}
+
+impl ProgressReporter {
+    /// Creates a new reporter, which edits the progress message with `edit`.
+    pub fn new<F, Fut>(edit: F) -> Self
+    where
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Self {
+            edit: Arc::new(move |text| Box::pin(edit(text))),
+            debouncer: Debouncer::default(),
+            key: String::new(),
+            interval: Duration::from_secs(3),
+            title: None,
+            width: 10,
+            locale: Locale::default(),
+        }
+    }
+
+    /// Shares the debouncer with other reporters, using `key` to tell them apart.
+    pub fn debouncer<K: ToString>(mut self, debouncer: &Debouncer, key: K) -> Self {
+        self.debouncer = debouncer.clone();
+        self.key = key.to_string();
+        self
+    }
+
+    /// Sets the minimum interval between the edits, the default is 3 seconds.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the title shown above the bar.
+    pub fn title<T: ToString>(mut self, title: T) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Sets the width of the bar, the default is `10`.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets the locale of the sizes.
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Renders the progress message.
+    ///
+    /// Ex: `█████░░░░░ 50%` followed by `512 KB / 1 MB`.
+    pub fn render(&self, current: usize, total: usize) -> String {
+        let percent = match total {
+            0 => 100,
+            total => current.min(total) * 100 / total,
+        };
+        let progress = format!(
+            "{} {}%\n{} / {}",
+            progress_bar(current, total, self.width),
+            percent,
+            human_bytes(current as u64, self.locale),
+            human_bytes(total as u64, self.locale)
+        );
+
+        match self.title.as_ref() {
+            Some(title) => format!("{}\n{}", title, progress),
+            None => progress,
+        }
+    }
+
+    /// Reports the progress, editing the message at most once per interval.
+    pub fn report(&self, current: usize, total: usize) {
+        let text = self.render(current, total);
+        self.debouncer
+            .call(&self.key, self.interval, (self.edit)(text));
+    }
+}
+
+impl std::fmt::Debug for ProgressReporter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProgressReporter")
+            .field("key", &self.key)
+            .field("interval", &self.interval)
+            .field("title", &self.title)
+            .field("width", &self.width)
+            .field("locale", &self.locale)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The maximum length of a deep link payload.
+const MAX_PAYLOAD_LEN: usize = 64;
+
+/// The base64url alphabet.
+const BASE64_URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Builds a link which starts the bot with the payload.
+///
+/// Telegram only accepts payloads up to 64 characters of `A-Z`, `a-z`, `0-9`, `_`
+/// and `-`, so returns `None` for other payloads. Use [`encode_payload`] to send
+/// arbitrary data.
+///
+/// # Example
+///
+/// ```
+/// use ferogram::utils::deep_link;
+///
+/// assert_eq!(
+///     deep_link("@my_bot", "ref_123").as_deref(),
+///     Some("https://t.me/my_bot?start=ref_123")
+/// );
+/// assert_eq!(deep_link("my_bot", "not valid"), None);
+/// ```
+pub fn deep_link(bot_username: &str, payload: &str) -> Option<String> {
+    let is_valid = payload.len() <= MAX_PAYLOAD_LEN
+        && payload
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-');
+
+    is_valid.then(|| {
+        format!(
+            "https://t.me/{}?start={}",
+            bot_username.trim_start_matches('@'),
+            payload
+        )
+    })
+}
+
+/// Encodes arbitrary data as a deep link payload, using unpadded base64url.
+///
+/// Returns `None` if the encoded data is longer than the 64 characters
+/// accepted by Telegram, which happens above 48 bytes.
+///
+/// # Example
+///
+/// ```
+/// use ferogram::utils::{decode_payload, encode_payload};
+///
+/// let payload = encode_payload(b"chat:-100123").unwrap();
+/// assert_eq!(decode_payload(&payload).unwrap(), b"chat:-100123");
+/// ```
+pub fn encode_payload(data: &[u8]) -> Option<String> {
+    let mut payload = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, byte)| {
+            bits | (*byte as u32) << (16 - i * 8)
+        });
+
+        for i in 0..=chunk.len() {
+            payload.push(BASE64_URL[(bits >> (18 - i * 6)) as usize & 0x3F] as char);
+        }
+    }
+
+    (payload.len() <= MAX_PAYLOAD_LEN).then_some(payload)
+}
+
+/// Decodes a deep link payload encoded by [`encode_payload`].
+///
+/// Returns `None` if the payload is not valid unpadded base64url.
+pub fn decode_payload(payload: &str) -> Option<Vec<u8>> {
+    if payload.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut data = Vec::with_capacity(payload.len() * 3 / 4);
+    for chunk in payload.as_bytes().chunks(4) {
+        let mut bits = 0u32;
+        for (i, char) in chunk.iter().enumerate() {
+            let value = BASE64_URL.iter().position(|c| c == char)? as u32;
+            bits |= value << (18 - i * 6);
+        }
+
+        for i in 0..chunk.len() - 1 {
+            data.push((bits >> (16 - i * 8)) as u8);
+        }
+    }
+
+    Some(data)
+}
+
+/// Builds the link of a group invite from its hash.
+///
+/// # Example
+///
+/// ```
+/// use ferogram::utils::group_invite_link;
+///
+/// assert_eq!(group_invite_link("AbCdEf"), "https://t.me/+AbCdEf");
+/// ```
+pub fn group_invite_link(hash: &str) -> String {
+    format!("https://t.me/+{}", hash.trim_start_matches('+'))
+}
+
+/// Builds a link which opens the profile of the user, even without a username.
+///
+/// # Example
+///
+/// ```
+/// use ferogram::utils::user_link;
+///
+/// assert_eq!(user_link(123), "tg://user?id=123");
+/// ```
+pub fn user_link(id: i64) -> String {
+    format!("tg://user?id={}", id)
+}
+
+/// Escapes the text to be inserted in an HTML message.
+///
+/// # Example
+///
+/// ```
+/// use ferogram::utils::escape_html;
+///
+/// assert_eq!(escape_html("<b>Tom & Jerry</b>"), "&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;");
+/// ```
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Builds the link of a message.
+///
+/// Returns `None` if the chat has no message links, like private chats and basic groups.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// # let message = unimplemented!();
+/// use ferogram::utils::message_link;
+///
+/// if let Some(link) = message_link(&message.chat(), message.id()) {
+///     println!("{}", link);
+/// }
+/// # }
+/// ```
+pub fn message_link(chat: &Chat, message_id: i32) -> Option<String> {
+    match chat {
+        Chat::User(_) => None,
+        Chat::Group(group) if !group.is_megagroup() => None,
+        _ => Some(match chat.username() {
+            Some(username) => format!("https://t.me/{}/{}", username, message_id),
+            None => format!("https://t.me/c/{}/{}", chat.id(), message_id),
+        }),
+    }
+}
+
+/// Builds the banned rights of the member.
+///
+/// Muted members can still view the messages, but not send anything. The rights
+/// last until `until_date`, or forever if it is `0`.
+pub(crate) fn banned_rights(
+    muted: bool,
+    view_messages: bool,
+    until_date: i32,
+) -> tl::enums::ChatBannedRights {
+    tl::enums::ChatBannedRights::Rights(tl::types::ChatBannedRights {
+        view_messages,
+        send_messages: muted,
+        send_media: muted,
+        send_stickers: muted,
+        send_gifs: muted,
+        send_games: muted,
+        send_inline: muted,
+        embed_links: muted,
+        send_polls: muted,
+        change_info: false,
+        invite_users: false,
+        pin_messages: false,
+        manage_topics: false,
+        send_photos: muted,
+        send_videos: muted,
+        send_roundvideos: muted,
+        send_audios: muted,
+        send_voices: muted,
+        send_docs: muted,
+        send_plain: muted,
+        until_date,
+    })
+}
+
+/// Edits the banned rights of the member, if the chat is a supergroup.
+pub(crate) async fn edit_banned(
+    client: &Client,
+    chat: &Chat,
+    member: &Chat,
+    banned_rights: tl::enums::ChatBannedRights,
+) -> std::result::Result<(), InvocationError> {
+    if let Some(channel) = chat.pack().try_to_input_channel() {
+        client
+            .invoke(&tl::functions::channels::EditBanned {
+                channel,
+                participant: member.pack().to_input_peer(),
+                banned_rights,
+            })
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_human_bytes() {
+        assert_eq!(human_bytes(0, Locale::English), "0 B");
+        assert_eq!(human_bytes(1023, Locale::English), "1023 B");
+        assert_eq!(human_bytes(1024, Locale::English), "1 KB");
+        assert_eq!(human_bytes(1536, Locale::English), "1.5 KB");
+        assert_eq!(human_bytes(1536, Locale::Portuguese), "1,5 KB");
+        assert_eq!(human_bytes(1024 * 1024, Locale::English), "1 MB");
+        assert_eq!(human_bytes(u64::MAX, Locale::English), "16384 PB");
+    }
+
+    #[test]
+    fn test_human_duration() {
+        let duration = Duration::from_secs(3900);
+        assert_eq!(
+            human_duration(duration, Locale::English),
+            "1 hour and 5 minutes"
+        );
+        assert_eq!(
+            human_duration(duration, Locale::Portuguese),
+            "1 hora e 5 minutos"
+        );
+
+        assert_eq!(human_duration(Duration::ZERO, Locale::English), "0 seconds");
+        assert_eq!(
+            human_duration(Duration::from_secs(1), Locale::English),
+            "1 second"
+        );
+        assert_eq!(
+            human_duration(Duration::from_secs(90061), Locale::English),
+            "1 day and 1 hour"
+        );
+        // Skips the units after a gap.
+        assert_eq!(
+            human_duration(Duration::from_secs(86460), Locale::English),
+            "1 day"
+        );
+    }
+
+    #[test]
+    fn test_utf16() {
+        assert_eq!(utf16_len("hi"), 2);
+        assert_eq!(utf16_len("hi 👋"), 5);
+
+        let text = "👋 hi";
+        assert_eq!(utf16_to_char_offset(text, 0), Some(0));
+        assert_eq!(utf16_to_char_offset(text, 1), None);
+        assert_eq!(utf16_to_char_offset(text, 2), Some(1));
+        assert_eq!(utf16_to_char_offset(text, 3), Some(2));
+        assert_eq!(utf16_to_char_offset(text, 5), Some(4));
+        assert_eq!(utf16_to_char_offset(text, 6), None);
+
+        assert_eq!(char_to_utf16_offset(text, 0), Some(0));
+        assert_eq!(char_to_utf16_offset(text, 1), Some(2));
+        assert_eq!(char_to_utf16_offset(text, 4), Some(5));
+        assert_eq!(char_to_utf16_offset(text, 5), None);
+
+        let text = "👋 https://example.com";
+        assert_eq!(utf16_slice(text, 3, 19), Some("https://example.com"));
+        assert_eq!(utf16_slice(text, 0, 2), Some("👋"));
+        assert_eq!(utf16_slice(text, 0, 1), None);
+        assert_eq!(utf16_slice(text, 1, 2), None);
+        assert_eq!(utf16_slice(text, 3, 20), None);
+    }
+
+    #[test]
+    fn test_deep_link() {
+        assert_eq!(
+            deep_link("@my_bot", "ref_123").as_deref(),
+            Some("https://t.me/my_bot?start=ref_123")
+        );
+        assert_eq!(deep_link("my_bot", "not valid"), None);
+        assert_eq!(deep_link("my_bot", "ação"), None);
+        assert!(deep_link("my_bot", &"a".repeat(64)).is_some());
+        assert_eq!(deep_link("my_bot", &"a".repeat(65)), None);
+    }
+
+    #[test]
+    fn test_payload() {
+        assert_eq!(encode_payload(b"").as_deref(), Some(""));
+        assert_eq!(encode_payload(b"f").as_deref(), Some("Zg"));
+        assert_eq!(encode_payload(b"fo").as_deref(), Some("Zm8"));
+        assert_eq!(encode_payload(b"foo").as_deref(), Some("Zm9v"));
+        assert_eq!(encode_payload(&[0xFB, 0xFF]).as_deref(), Some("-_8"));
+
+        for data in [&b"f"[..], b"fo", b"foo", b"\xFB\xFF", b"chat:-100123"] {
+            let payload = encode_payload(data).unwrap();
+            assert_eq!(decode_payload(&payload).as_deref(), Some(data));
+        }
+
+        assert_eq!(
+            encode_payload(&[0; 48]).map(|payload| payload.len()),
+            Some(64)
+        );
+        assert_eq!(encode_payload(&[0; 49]), None);
+
+        assert_eq!(decode_payload("Z"), None);
+        assert_eq!(decode_payload("Zm8="), None);
+        assert_eq!(decode_payload("Zm+v"), None);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("2h30m"), Some(Duration::from_secs(9000)));
+        assert_eq!(
+            parse_duration("1h and 30m"),
+            Some(Duration::from_secs(5400))
+        );
+        assert_eq!(
+            parse_duration("1h, 30 min"),
+            Some(Duration::from_secs(5400))
+        );
+        assert_eq!(
+            parse_duration("1 dia e 2 horas"),
+            Some(Duration::from_secs(93600))
+        );
+        assert_eq!(
+            parse_duration("2 Semanas"),
+            Some(Duration::from_secs(1209600))
+        );
+
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("soon"), None);
+        assert_eq!(parse_duration("5"), None);
+        assert_eq!(parse_duration("and 1h"), None);
+        assert_eq!(parse_duration("1h later"), None);
+        assert_eq!(parse_duration("99999999999999999999w"), None);
+    }
+
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn test_parse_datetime() {
+        use chrono::{Days, FixedOffset, TimeZone, Utc};
+
+        let tz = FixedOffset::west_opt(3 * 3600).unwrap();
+        let now = Utc::now().with_timezone(&tz);
+
+        let date = parse_datetime("in 2h", tz).unwrap();
+        assert!(
+            (date - now - chrono::Duration::hours(2))
+                .num_seconds()
+                .abs()
+                < 5
+        );
+
+        let date = parse_datetime("tomorrow 9am", tz).unwrap();
+        assert_eq!(
+            date.date_naive(),
+            now.date_naive().checked_add_days(Days::new(1)).unwrap()
+        );
+        assert_eq!(date.format("%H:%M").to_string(), "09:00");
+
+        let date = parse_datetime("amanhã às 21h30", tz).unwrap();
+        assert_eq!(date.format("%H:%M").to_string(), "21:30");
+
+        assert_eq!(
+            parse_datetime("2025-03-10 9:00", tz),
+            tz.with_ymd_and_hms(2025, 3, 10, 9, 0, 0).single()
+        );
+        assert_eq!(
+            parse_datetime("9 pm 2025-03-10", tz),
+            tz.with_ymd_and_hms(2025, 3, 10, 21, 0, 0).single()
+        );
+
+        assert!(parse_datetime("9pm", tz).unwrap() > now);
+
+        assert_eq!(parse_datetime("someday", tz), None);
+        assert_eq!(parse_datetime("13pm", tz), None);
+        assert_eq!(parse_datetime("at", tz), None);
+    }
+
+    #[tokio::test]
+    async fn test_keyed_lock() {
+        let locks = KeyedLock::<i64>::default();
+
+        let guard = locks.lock(1).await;
+        assert!(
+            tokio::time::timeout(Duration::from_millis(10), locks.lock(1))
+                .await
+                .is_err()
+        );
+        let _other = locks.lock(2).await;
+
+        drop(guard);
+        let _guard = locks.lock(1).await;
+        assert_eq!(locks.locks.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("start", "start"), 0);
+        assert_eq!(levenshtein("strat", "start"), 2);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("abc", ""), 3);
+        assert_eq!(levenshtein("a👋", "b👋"), 1);
+    }
+
+    #[test]
+    fn test_progress_bar() {
+        assert_eq!(progress_bar(50, 100, 10), "█████░░░░░");
+        assert_eq!(progress_bar(0, 100, 4), "░░░░");
+        assert_eq!(progress_bar(150, 100, 4), "████");
+        assert_eq!(progress_bar(0, 0, 3), "███");
+    }
+}